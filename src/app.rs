@@ -0,0 +1,1517 @@
+use crate::Options;
+use crate::config::Config;
+use crate::debug::FrameStats;
+use crate::error::Result;
+use crate::input::{
+    AutoShift, GravityClock, InitialActions, SoftDropRepeat, key_kind, soft_drop_interval,
+};
+use crate::keys::{Action, KeyBindings};
+use crate::menu::{ControlsChange, Menu};
+use crate::net::{Message, Peer};
+use crate::recording::{PlayClock, Playback};
+use crate::render::{
+    CELL_W, Layout, NARROW_CELL_W, Renderer, banner_for, draw_banner, draw_confirm, draw_countdown,
+    draw_debug, draw_game_over, draw_menu, draw_paused, draw_stats, draw_status, draw_too_small,
+    draw_versus_result,
+};
+use crate::sound::Sound;
+use crate::theme::{Glyphs, Theme};
+use constris::{
+    EndReason, Game, GameConfig, GameEvent, GameMode, Input, Replay, SoftDropLock, plan_moves,
+};
+use crossterm::{
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    queue,
+    terminal::{self, ClearType},
+};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// 메뉴에서 시작한 뒤 3, 2, 1을 세는 시간
+const COUNTDOWN: Duration = Duration::from_secs(3);
+
+// 리플레이 저장 키를 누르면 현재 디렉터리에 이 이름으로 쓴다
+const REPLAY_FILE: &str = "constris.replay";
+// 게임 저장 키로 쓰고 --resume으로 읽는 파일
+pub const SAVE_FILE: &str = "constris.save";
+
+// 막혀서 졌을 때 결과 패널을 띄우기 전에 겹친 피스를 보여 주는 시간
+const TOP_OUT_REVEAL: Duration = Duration::from_millis(1200);
+
+// 퍼펙트 클리어 같은 알림을 보드 가운데 띄워 두는 시간
+const BANNER_TIME: Duration = Duration::from_millis(1500);
+
+// 메뉴에서 아무 키도 누르지 않고 이만큼 지나면 데모를 보여 준다
+const ATTRACT_DELAY: Duration = Duration::from_secs(20);
+// 데모 봇이 입력 하나를 넣는 간격
+const DEMO_STEP: Duration = Duration::from_millis(80);
+
+// 이만큼 아무 키도 누르지 않았고 움직이는 것이 없으면 입력을 더 오래 기다린다
+const IDLE_AFTER: Duration = Duration::from_secs(2);
+const IDLE_TICK: Duration = Duration::from_millis(250);
+// --low-power면 바쁠 때도 한 프레임을 이보다 짧게 잡지 않는다
+const LOW_POWER_TICK: Duration = Duration::from_millis(100);
+
+// 대전에서 1P 화면과 2P 보드 사이의 빈 열 수
+const VERSUS_GAP: u16 = 2;
+
+/// 메인 루프가 어떤 화면에 있는지
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppState {
+    /// 시작 화면
+    Menu,
+    /// 봇이 혼자 두는 데모(`--demo`, 또는 메뉴에서 한동안 가만히 있을 때). 아무 키나 누르면 메뉴로.
+    Demo,
+    /// 게임 시작 전 카운트다운
+    Countdown {
+        since: Instant,
+    },
+    Playing,
+    Paused,
+    /// 플레이 중 재시작 키를 눌러 Y/N을 묻는 중(게임은 멈춰 있다)
+    ConfirmRestart,
+    /// 플레이 중 종료 키를 눌러 Y로 확인받는 중(게임은 멈춰 있다)
+    ConfirmQuit,
+    /// 게임이 끝난 뒤. 막혀서 끝났으면 잠시 보드를 보여 준 뒤 결과 패널을 띄운다.
+    /// `stats`이면 결과 패널 대신 통계 화면을 보여 준다(닫으면 결과 패널).
+    GameOver {
+        since: Instant,
+        stats: bool,
+    },
+}
+
+/// 터미널 프론트엔드 전체 상태. 이벤트 하나를 처리하고 필요하면 상태를 바꾼다.
+pub struct App {
+    // 조작 화면에서 바꾸면 바로 쓰고 설정 파일에도 적는다
+    keys: KeyBindings,
+    // 고스트 표시 여부. 새 게임에도 이어지고, 바꾸면 설정 파일에 저장한다.
+    show_ghost: bool,
+    // 플레이 중 종료를 확인받는지(설정 파일의 confirm_quit)
+    confirm_quit: bool,
+    // 줄 제거 애니메이션 시간(0이면 바로 지운다)
+    clear_delay: Duration,
+    // 락 딜레이 길이와 리셋 횟수 제한
+    lock_delay: Duration,
+    max_lock_resets: u32,
+    // 하드 드롭을 연달아 받지 않는 시간
+    hard_drop_lockout: Duration,
+    // 0이 아니면 하드 드롭을 이 시간 안에 두 번 눌러야 떨어뜨린다(끈끈한 드롭)
+    sticky_drop: Duration,
+    // 고정 뒤 다음 피스가 나오기까지 기다리는 시간(ARE)
+    entry_delay: Duration,
+    // 스폰 대기 중에 누른 입력(다음 피스가 나오면 차례로 넣는다)
+    buffered: Vec<Buffered>,
+    // 새 게임의 벽 킥 여부(재생 중에는 기록에 적힌 값을 쓴다)
+    wall_kicks: bool,
+    // 새 게임의 종류별 스폰 회전 상태(재생 중에는 기록에 적힌 값을 쓴다)
+    spawn_rotations: Vec<u8>,
+    // 낙하 속도가 멈추는 레벨
+    speed_cap: Option<u32>,
+    // 새 게임의 20G 시작 레벨(재생 중에는 기록에 적힌 값을 쓴다)
+    instant_gravity: Option<u32>,
+    // 새 게임의 홀드 횟수 제한(재생 중에는 기록에 적힌 값을 쓴다)
+    hold_limit: Option<u32>,
+    // 새 게임의 되감기 횟수, 피스 수, 감점(재생 중에는 기록에 적힌 값을 쓴다)
+    rewinds: u32,
+    rewind_pieces: usize,
+    rewind_penalty: u32,
+    // 소프트 드롭으로 바닥에 닿은 피스를 바로 고정하는지(재생 중에는 기록에 적힌 값을 쓴다)
+    soft_drop_lock: SoftDropLock,
+    // 쓰레기 줄 구멍을 모두 같은 열에 내는지
+    same_garbage_hole: bool,
+    das_enabled: bool,
+    state: AppState,
+    menu: Menu,
+    // 메뉴에서 고른 설정. 재시작할 때도 그대로 쓴다.
+    config: GameConfig,
+    // --seed로 고정한 시드(없으면 판마다 새로 뽑는다)
+    seed: Option<u64>,
+    game: Game,
+    theme: Theme,
+    renderer: Renderer,
+    das: AutoShift,
+    // 누르고 있는 소프트 드롭 키의 반복(DAS와 따로 돈다)
+    soft_drop: SoftDropRepeat,
+    // 새 피스가 나올 때 넣을, 누르고 있는 회전/홀드 키
+    initial: InitialActions,
+    // 프레임 사이에 입력을 기다리는 최대 시간과, 덜 자주 그릴지
+    tick: Duration,
+    low_power: bool,
+    // 마지막으로 키나 마우스 입력이 들어온 시각(한가하면 덜 자주 깨어난다)
+    last_input: Instant,
+    // 있으면 소프트 드롭 간격을 지금 중력의 이 배수 빠르기로 맞춘다
+    soft_drop_multiplier: Option<u32>,
+    gravity: GravityClock,
+    size: (u16, u16),
+    // 터미널이 보드보다 작아서 자동으로 멈췄는지(다시 커지면 자동으로 재개)
+    paused_for_size: bool,
+    // 창이 포커스를 잃어서 자동으로 멈췄는지(포커스가 돌아오면 재개)
+    paused_for_focus: bool,
+    // 일시정지를 뺀 플레이 시간. 리플레이 시각의 기준이다.
+    clock: PlayClock,
+    /// 다음 쓰레기 줄이 올라올 플레이 시각(ms), 치즈 모드가 아니면 없음
+    next_garbage: Option<u64>,
+    // 이번 판의 입력 기록
+    recording: Replay,
+    // --resume으로 이어 하는 판인지(기록이 중간부터라 리플레이로 저장할 수 없다)
+    resumed: bool,
+    // --replay로 시작했으면 재생 중인 기록(플레이어 조작은 받지 않는다)
+    playback: Option<Playback>,
+    // 리플레이 저장 결과처럼 사이드 패널 아래에 잠깐 보여 줄 한 줄
+    status: Option<String>,
+    // 메뉴에서 마지막으로 키를 누른 시각(오래되면 데모를 띄운다)
+    menu_since: Instant,
+    // 데모 봇이 지금 피스에 넣을 남은 입력과 마지막으로 넣은 시각
+    demo_moves: VecDeque<Input>,
+    last_demo_step: Instant,
+    // 보드 가운데 잠깐 띄우는 알림과 띄운 시각
+    banner: Option<(&'static str, Instant)>,
+    // 잠깐 보였다 사라지는 화면 효과를 모두 끄는지(설정 파일의 reduce_motion)
+    reduce_motion: bool,
+    // 켜고 끈 값과 바꾼 키를 적어 둘 설정 파일(--config나 기본 위치)
+    config_path: Option<PathBuf>,
+    // 상태가 바뀌거나 창 크기가 바뀌어 다음 프레임 전에 화면을 지워야 하는지
+    needs_clear: bool,
+    // --debug로 시작했을 때만 있는 프레임 통계와, 오버레이를 보여 주는 중인지
+    debug: Option<FrameStats>,
+    show_debug: bool,
+    // 게임 이벤트 효과음(`audio` 기능이 있을 때만 소리가 난다)
+    sound: Sound,
+    // --versus로 시작했으면 오른쪽 보드의 2P
+    rival: Option<Rival>,
+    // --host/--connect로 시작했으면 네트워크 대전 상대
+    remote: Option<Remote>,
+}
+
+/// 네트워크 대전 상대. 상대 보드는 받지 않고, 공격과 고정한 피스 수, 졌는지만 안다.
+/// 두 판은 접속할 때 정한 같은 시드로 같은 피스를 받는다.
+struct Remote {
+    peer: Peer,
+    // 상대가 고정한 피스 수
+    pieces: u32,
+    // 상대가 막혔거나 연결이 끊겼는지(어느 쪽이든 남은 쪽이 이긴다)
+    lost: bool,
+    disconnected: bool,
+}
+
+impl Remote {
+    fn send(&mut self, message: Message) {
+        if self.peer.send(message).is_err() {
+            self.lost = true;
+            self.disconnected = true;
+        }
+    }
+
+    /// 도착한 메시지를 처리한다. 받은 공격은 내 게임에 쌓아 둔다.
+    fn receive(&mut self, game: &mut Game) {
+        match self.peer.poll() {
+            Ok(messages) => {
+                for message in messages {
+                    match message {
+                        // 보드 높이보다 많이 받아도 막히는 것은 같다
+                        Message::Attack(rows) => {
+                            game.receive_garbage(rows.min(game.board.height() as u32))
+                        }
+                        Message::Lock => self.pieces += 1,
+                        Message::GameOver => self.lost = true,
+                        Message::Hello { .. } => {}
+                    }
+                }
+            }
+            Err(_) => {
+                self.lost = true;
+                self.disconnected = true;
+            }
+        }
+    }
+}
+
+/// 스폰 대기 중에 받아 두었다가 다음 피스가 나오면 넣는 입력
+#[derive(Clone, Copy)]
+enum Buffered {
+    Input(Input),
+    // 마우스로 누른 보드 칸(나온 피스를 그 칸까지 옮긴다)
+    Column(i32),
+}
+
+/// 둘이 하는 대전의 2P. 1P와 같은 시드로 같은 피스를 받고, 보드를 1P 화면 오른쪽에 그린다.
+/// DAS와 소프트 드롭 반복 없이 키를 누를 때마다 한 번씩 움직이고, 입력은 기록하지 않는다.
+struct Rival {
+    game: Game,
+    renderer: Renderer,
+    keys: KeyBindings,
+    gravity: GravityClock,
+    // 스폰 대기 중에 누른 입력(다음 피스가 나오면 차례로 넣는다)
+    buffered: Vec<Input>,
+}
+
+impl Rival {
+    fn press(&mut self, input: Input) {
+        let now = Instant::now();
+        self.game.note_input(now);
+        // 줄 제거 애니메이션 중에는 하드 드롭으로 건너뛰기만 받고,
+        // 스폰 대기 중에 누른 입력은 다음 피스가 나올 때 넣는다
+        if self.game.is_clearing() {
+            if input == Input::HardDrop {
+                self.game.apply(Input::Clear);
+                self.gravity.reset(now);
+            }
+            return;
+        }
+        if self.game.is_entering() {
+            self.buffered.push(input);
+            return;
+        }
+        if input == Input::HardDrop && self.game.hard_drop_locked_out(now) {
+            return;
+        }
+        self.game.apply(input);
+        if matches!(input, Input::SoftDrop | Input::SonicDrop | Input::HardDrop) {
+            self.gravity.reset(now);
+        }
+    }
+
+    /// 시간이 흐르며 일어나는 일(줄 제거 애니메이션, 스폰 대기, 중력, 락 딜레이)
+    fn update(&mut self) {
+        let now = Instant::now();
+        if self.game.is_clearing() {
+            if self.game.update_clear(now) {
+                self.gravity.reset(now);
+            }
+        } else if self.game.is_entering() {
+            if self.game.update_entry(now) {
+                self.gravity.reset(now);
+                for input in std::mem::take(&mut self.buffered) {
+                    self.press(input);
+                }
+            }
+        } else {
+            let interval = Duration::from_millis(self.game.drop_interval_ms());
+            for _ in 0..self.gravity.take_drops(interval, now) {
+                if self.game.is_grounded() {
+                    break;
+                }
+                self.game.apply(Input::Gravity);
+            }
+            self.game.update_lock(now);
+        }
+        self.game.update_timer();
+    }
+}
+
+impl App {
+    /// `replay`가 있으면 메뉴를 건너뛰고 그 기록을 재생하고, `resume`이 있으면 그 게임을 이어 한다.
+    pub fn new(
+        options: &Options,
+        user: &Config,
+        das_enabled: bool,
+        replay: Option<Replay>,
+        resume: Option<Game>,
+        peer: Option<(Peer, u64)>,
+    ) -> Result<Self> {
+        let show_ghost = user.show_ghost && !options.no_ghost;
+        let cell_w = if options.narrow || user.narrow {
+            NARROW_CELL_W
+        } else {
+            CELL_W
+        };
+        let wall_kicks = user.wall_kicks && !options.no_kicks;
+        let same_garbage_hole = user.same_garbage_hole || options.same_garbage_hole;
+        let lock_delay = options.lock_delay.unwrap_or(user.lock_delay);
+        let max_lock_resets = options.lock_resets.unwrap_or(user.max_lock_resets);
+        let entry_delay = options.entry_delay.unwrap_or(user.entry_delay);
+        let hold_limit = options.hold_limit.or(user.hold_limit);
+        let rewinds = options.rewinds.unwrap_or(user.rewinds);
+        // 화면 효과를 줄이면 줄 제거 애니메이션 없이 바로 지운다
+        let clear_delay = if user.reduce_motion {
+            Duration::ZERO
+        } else {
+            options.clear_delay
+        };
+        let config = game_config(options, user);
+        let theme = options.theme.unwrap_or(user.theme);
+        let mut game = new_game(options.seed, config);
+        game.show_ghost = show_ghost;
+        game.clear_delay = clear_delay;
+        game.entry_delay = entry_delay;
+        game.lock_delay = lock_delay;
+        game.max_lock_resets = max_lock_resets;
+        game.hard_drop_lockout = user.hard_drop_lockout;
+        game.sticky_drop = user.sticky_drop;
+        game.wall_kicks = wall_kicks;
+        game.spawn_rotations = user.spawn_rotations.clone();
+        game.speed_cap = user.speed_cap;
+        game.instant_gravity = user.instant_gravity;
+        game.holds_remaining = hold_limit;
+        game.rewinds_remaining = rewinds;
+        game.rewind_pieces = user.rewind_pieces;
+        game.rewind_penalty = user.rewind_penalty;
+        game.soft_drop_lock = user.soft_drop_lock;
+        game.respawn_current();
+        game.same_garbage_hole = same_garbage_hole;
+        let mut recording = Replay::new(game.seed, config);
+        recording.wall_kicks = wall_kicks;
+        recording.spawn_rotations = user.spawn_rotations.clone();
+        recording.instant_gravity = user.instant_gravity;
+        recording.hold_limit = hold_limit;
+        recording.rewinds = rewinds;
+        recording.rewind_pieces = user.rewind_pieces;
+        recording.rewind_penalty = user.rewind_penalty;
+        recording.soft_drop_lock = user.soft_drop_lock;
+        let mut renderer = Renderer::new(options.previews, cell_w, Glyphs::new(options.ascii));
+        renderer.level_colors = user.level_colors;
+        renderer.drop_trail = user.drop_trail;
+        renderer.grid_lines = user.grid;
+        renderer.danger_rows = user.danger_rows;
+        renderer.flipped = user.flipped;
+        renderer.score_popups = user.score_popups;
+        renderer.ghost_style = user.ghost_style;
+        renderer.reduce_motion = user.reduce_motion;
+        renderer.goal_bar = user.goal_bar;
+        let mut keys = user.keys.clone();
+        keys.mirrored = user.mirror_controls;
+        let mut app = Self {
+            keys,
+            show_ghost,
+            confirm_quit: user.confirm_quit,
+            clear_delay,
+            lock_delay,
+            max_lock_resets,
+            hard_drop_lockout: user.hard_drop_lockout,
+            sticky_drop: user.sticky_drop,
+            entry_delay,
+            buffered: Vec::new(),
+            wall_kicks,
+            spawn_rotations: user.spawn_rotations.clone(),
+            speed_cap: user.speed_cap,
+            instant_gravity: user.instant_gravity,
+            hold_limit,
+            rewinds,
+            rewind_pieces: user.rewind_pieces,
+            rewind_penalty: user.rewind_penalty,
+            soft_drop_lock: user.soft_drop_lock,
+            same_garbage_hole,
+            das_enabled,
+            state: AppState::Menu,
+            menu: Menu::new(config, theme),
+            config,
+            seed: options.seed,
+            recording,
+            resumed: false,
+            game,
+            theme,
+            renderer,
+            das: AutoShift::new(user.das_delay, user.arr),
+            soft_drop: SoftDropRepeat::new(user.soft_drop_interval, das_enabled),
+            initial: InitialActions::new(das_enabled && user.initial_actions),
+            tick: options.tick.unwrap_or(user.tick),
+            low_power: options.low_power,
+            last_input: Instant::now(),
+            soft_drop_multiplier: options.soft_drop_multiplier.or(user.soft_drop_multiplier),
+            gravity: GravityClock::new(Instant::now()),
+            size: terminal::size()?,
+            paused_for_size: false,
+            paused_for_focus: false,
+            clock: PlayClock::new(Instant::now()),
+            next_garbage: None,
+            playback: None,
+            status: None,
+            menu_since: Instant::now(),
+            demo_moves: VecDeque::new(),
+            last_demo_step: Instant::now(),
+            banner: None,
+            reduce_motion: user.reduce_motion,
+            config_path: user.path.clone(),
+            needs_clear: true,
+            debug: options.debug.then(FrameStats::new),
+            show_debug: false,
+            sound: Sound::new(),
+            rival: None,
+            remote: None,
+        };
+        if options.demo {
+            app.set_state(AppState::Demo);
+        }
+        if options.versus {
+            let mut renderer = Renderer::new(options.previews, cell_w, Glyphs::new(options.ascii));
+            renderer.level_colors = user.level_colors;
+            renderer.drop_trail = user.drop_trail;
+            renderer.grid_lines = user.grid;
+            renderer.danger_rows = user.danger_rows;
+            renderer.flipped = user.flipped;
+            renderer.score_popups = user.score_popups;
+            renderer.ghost_style = user.ghost_style;
+            renderer.reduce_motion = user.reduce_motion;
+            renderer.goal_bar = user.goal_bar;
+            renderer.left = app.renderer.layout(&app.game.board).screen().0 + VERSUS_GAP;
+            app.rival = Some(Rival {
+                game: Game::new(),
+                renderer,
+                keys: KeyBindings::player_two(),
+                gravity: GravityClock::new(Instant::now()),
+                buffered: Vec::new(),
+            });
+            app.new_game();
+            app.set_state(AppState::Countdown {
+                since: Instant::now(),
+            });
+        }
+        if let Some((peer, seed)) = peer {
+            app.seed = Some(seed);
+            app.remote = Some(Remote {
+                peer,
+                pieces: 0,
+                lost: false,
+                disconnected: false,
+            });
+            app.new_game();
+            app.set_state(AppState::Countdown {
+                since: Instant::now(),
+            });
+        }
+        if let Some(replay) = replay {
+            app.config = replay.config;
+            let mut playback = Playback::new(replay);
+            app.game = playback.rewind();
+            app.game.show_ghost = app.show_ghost;
+            app.game.clear_delay = app.clear_delay;
+            app.game.entry_delay = app.entry_delay;
+            app.playback = Some(playback);
+            app.set_state(AppState::Countdown {
+                since: Instant::now(),
+            });
+        }
+        if let Some(game) = resume {
+            // 재시작하면 이어 하던 판과 같은 설정으로 새로 시작한다
+            app.config = GameConfig {
+                mode: game.mode,
+                start_level: game.start_level,
+                gravity: game.gravity,
+                scoring: game.scoring,
+                width: game.board.width(),
+                height: game.board.height(),
+                // 저장 파일에는 없으니 명령줄에서 정한 값을 쓴다
+                garbage: app.config.garbage,
+                randomizer: game.randomizer(),
+            };
+            app.game = game;
+            app.game.show_ghost = app.show_ghost;
+            app.game.clear_delay = app.clear_delay;
+            app.game.entry_delay = app.entry_delay;
+            app.game.lock_delay = app.lock_delay;
+            app.game.max_lock_resets = app.max_lock_resets;
+            app.game.hard_drop_lockout = app.hard_drop_lockout;
+            app.game.sticky_drop = app.sticky_drop;
+            app.game.wall_kicks = app.wall_kicks;
+            // 저장한 현재 피스는 그대로 두고 다음 스폰부터 적용한다
+            app.game.spawn_rotations = app.spawn_rotations.clone();
+            app.game.speed_cap = app.speed_cap;
+            app.game.instant_gravity = app.instant_gravity;
+            // 남은 되감기 횟수는 저장한 값을 쓴다
+            app.game.rewind_pieces = app.rewind_pieces;
+            app.game.rewind_penalty = app.rewind_penalty;
+            app.game.soft_drop_lock = app.soft_drop_lock;
+            app.game.same_garbage_hole = app.same_garbage_hole;
+            app.resumed = true;
+            app.set_state(AppState::Countdown {
+                since: Instant::now(),
+            });
+        }
+        Ok(app)
+    }
+
+    /// Quit 동작이나 Ctrl-C가 들어올 때까지 그리기 → 입력 → 갱신을 반복한다.
+    pub fn run(&mut self, out: &mut impl Write) -> Result<()> {
+        loop {
+            self.check_size();
+            self.draw(out)?;
+            // 그리기 전에 그동안 쌓인 입력을 모두 처리해 빠르게 누른 키가 한 프레임에 다 들어가게 한다
+            let mut ready = event::poll(self.poll_timeout())?;
+            while ready {
+                if !self.handle_event(event::read()?) {
+                    return Ok(());
+                }
+                // 게임이 끝났으면 남은 입력은 결과 화면으로 넘어간 뒤에 받는다
+                ready = !self.game.game_over && event::poll(Duration::ZERO)?;
+            }
+            self.update();
+            self.play_events();
+        }
+    }
+
+    /// 상태 전환은 모두 여기를 거친다. 들어가는 상태에 맞춰 게임 시계와 타이머를 맞춘다.
+    fn set_state(&mut self, next: AppState) {
+        match next {
+            AppState::Paused | AppState::ConfirmRestart | AppState::ConfirmQuit => {
+                self.game.set_paused(true);
+                if let Some(rival) = &mut self.rival {
+                    rival.game.set_paused(true);
+                }
+                self.clock.pause(Instant::now());
+                self.das.clear();
+                self.soft_drop.release();
+                self.initial.clear();
+            }
+            AppState::Playing => {
+                self.paused_for_focus = false;
+                if self.game.paused {
+                    self.game.set_paused(false);
+                }
+                self.clock.resume(Instant::now());
+                // 멈춰 있던 시간으로 바로 떨어지지 않도록 중력 타이머를 새로 시작
+                self.gravity.reset(Instant::now());
+                if let Some(rival) = &mut self.rival {
+                    if rival.game.paused {
+                        rival.game.set_paused(false);
+                    }
+                    rival.gravity.reset(Instant::now());
+                }
+            }
+            AppState::Menu => self.menu_since = Instant::now(),
+            AppState::Demo => {
+                self.new_game();
+                self.demo_moves.clear();
+            }
+            AppState::Countdown { .. } | AppState::GameOver { .. } => {}
+        }
+        self.state = next;
+        self.needs_clear = true;
+    }
+
+    /// 메뉴에서 고른 값으로 카운트다운을 시작한다.
+    fn start_from_menu(&mut self) {
+        self.config = self.menu.config;
+        self.theme = self.menu.theme;
+        self.new_game();
+        self.set_state(AppState::Countdown {
+            since: Instant::now(),
+        });
+    }
+
+    /// 재생 중이면 기록을 처음부터, 아니면 같은 설정의 새 게임을 준비한다.
+    fn new_game(&mut self) {
+        self.game = match &mut self.playback {
+            Some(playback) => playback.rewind(),
+            None => {
+                let mut game = new_game(self.seed, self.config);
+                game.wall_kicks = self.wall_kicks;
+                game.spawn_rotations = self.spawn_rotations.clone();
+                game.instant_gravity = self.instant_gravity;
+                game.holds_remaining = self.hold_limit;
+                game.rewinds_remaining = self.rewinds;
+                game.rewind_pieces = self.rewind_pieces;
+                game.rewind_penalty = self.rewind_penalty;
+                game.soft_drop_lock = self.soft_drop_lock;
+                game.respawn_current();
+                game
+            }
+        };
+        self.game.speed_cap = self.speed_cap;
+        self.game.show_ghost = self.show_ghost;
+        self.game.clear_delay = self.clear_delay;
+        self.game.entry_delay = self.entry_delay;
+        self.game.lock_delay = self.lock_delay;
+        self.game.max_lock_resets = self.max_lock_resets;
+        self.game.hard_drop_lockout = self.hard_drop_lockout;
+        self.game.sticky_drop = self.sticky_drop;
+        self.game.same_garbage_hole = self.same_garbage_hole;
+        self.recording = Replay::new(self.game.seed, self.config);
+        self.recording.wall_kicks = self.game.wall_kicks;
+        self.recording.spawn_rotations = self.game.spawn_rotations.clone();
+        self.recording.instant_gravity = self.game.instant_gravity;
+        self.recording.hold_limit = self.game.holds_remaining;
+        self.recording.rewinds = self.game.rewinds_remaining;
+        self.recording.rewind_pieces = self.game.rewind_pieces;
+        self.recording.rewind_penalty = self.game.rewind_penalty;
+        self.recording.soft_drop_lock = self.game.soft_drop_lock;
+        self.resumed = false;
+        // 연습 모드에서 켠 눈금은 다른 모드로 새로 시작하면 끈다
+        if self.renderer.ruler && !self.ruler_allowed() {
+            self.renderer.ruler = false;
+            self.needs_clear = true;
+        }
+        self.das.clear();
+        self.soft_drop.release();
+        self.initial.clear();
+        self.buffered.clear();
+        self.status = None;
+        self.banner = None;
+        if self.rival.is_some() {
+            let rival = self.rival_game();
+            if let Some(r) = &mut self.rival {
+                r.game = rival;
+                r.gravity.reset(Instant::now());
+                r.buffered.clear();
+            }
+        }
+    }
+
+    /// 대전의 2P 판: 1P와 같은 시드와 설정, 같은 게임 옵션으로 시작한다.
+    fn rival_game(&self) -> Game {
+        let mut game = Game::with_seed_and_config(self.game.seed, self.config);
+        game.wall_kicks = self.game.wall_kicks;
+        game.spawn_rotations = self.game.spawn_rotations.clone();
+        game.speed_cap = self.game.speed_cap;
+        game.instant_gravity = self.game.instant_gravity;
+        game.holds_remaining = self.game.holds_remaining;
+        game.rewinds_remaining = self.game.rewinds_remaining;
+        game.rewind_pieces = self.game.rewind_pieces;
+        game.rewind_penalty = self.game.rewind_penalty;
+        game.soft_drop_lock = self.game.soft_drop_lock;
+        game.respawn_current();
+        game.show_ghost = self.game.show_ghost;
+        game.clear_delay = self.game.clear_delay;
+        game.entry_delay = self.game.entry_delay;
+        game.lock_delay = self.game.lock_delay;
+        game.max_lock_resets = self.game.max_lock_resets;
+        game.hard_drop_lockout = self.game.hard_drop_lockout;
+        game.sticky_drop = self.game.sticky_drop;
+        game.same_garbage_hole = self.game.same_garbage_hole;
+        game
+    }
+
+    fn restart(&mut self) {
+        self.new_game();
+        self.set_state(AppState::Playing);
+        self.start_clock();
+    }
+
+    /// 플레이 시계를 0부터 다시 세고 쓰레기 줄 타이머도 맞춘다.
+    fn start_clock(&mut self) {
+        self.clock = PlayClock::new(Instant::now());
+        self.next_garbage = self
+            .game
+            .mode
+            .garbage_interval()
+            .map(|d| d.as_millis() as u64);
+    }
+
+    /// 게임에 입력을 적용하고 지금의 플레이 시각과 함께 기록한다.
+    fn apply(&mut self, input: Input) {
+        self.game.apply(input);
+        self.recording.push(self.clock.ms(Instant::now()), input);
+    }
+
+    fn save_replay(&mut self) {
+        if self.playback.is_some() {
+            return;
+        }
+        let status = if self.in_versus() {
+            "No replay in versus"
+        } else if self.resumed {
+            "No replay for resumed game"
+        } else {
+            match fs::write(REPLAY_FILE, self.recording.to_text()) {
+                Ok(()) => "Replay saved",
+                Err(_) => "Replay save failed",
+            }
+        };
+        self.status = Some(status.to_string());
+    }
+
+    /// 지금 판을 `SAVE_FILE`에 저장한다. 줄 제거 애니메이션 중이면 줄부터 지운다.
+    fn save_game(&mut self) {
+        if self.playback.is_some() || self.game.game_over {
+            return;
+        }
+        if self.in_versus() {
+            self.status = Some("No save in versus".to_string());
+            return;
+        }
+        if self.game.is_clearing() {
+            self.apply(Input::Clear);
+        }
+        if self.game.is_entering() {
+            self.apply(Input::Spawn);
+        }
+        let status = match fs::write(SAVE_FILE, self.game.save()) {
+            Ok(()) => "Game saved",
+            Err(_) => "Game save failed",
+        };
+        self.status = Some(status.to_string());
+    }
+
+    fn toggle_ghost(&mut self) {
+        self.show_ghost = !self.show_ghost;
+        self.game.show_ghost = self.show_ghost;
+        if Config::save_flag(self.config_path.as_deref(), "show_ghost", self.show_ghost).is_err() {
+            self.status = Some("Config save failed".to_string());
+        }
+    }
+
+    /// 빈칸의 격자선을 켜고 끈다. 바뀐 빈칸은 렌더러가 알아서 다시 그린다.
+    fn toggle_grid(&mut self) {
+        self.renderer.grid_lines = !self.renderer.grid_lines;
+        if Config::save_flag(
+            self.config_path.as_deref(),
+            "grid",
+            self.renderer.grid_lines,
+        )
+        .is_err()
+        {
+            self.status = Some("Config save failed".to_string());
+        }
+    }
+
+    /// 좌표 눈금을 켜거나 끈다. 평소 플레이에 끼어들지 않게 `--debug`나 연습 모드에서만,
+    /// 화면 배치가 고정된 대전에서는 쓰지 않는다.
+    fn toggle_ruler(&mut self) {
+        if !self.ruler_allowed() || self.rival.is_some() {
+            return;
+        }
+        self.renderer.ruler = !self.renderer.ruler;
+        self.needs_clear = true;
+        self.check_size();
+    }
+
+    fn ruler_allowed(&self) -> bool {
+        self.debug.is_some() || self.game.mode == GameMode::Practice
+    }
+
+    fn toggle_mute(&mut self) {
+        self.sound.muted = !self.sound.muted;
+        let status = if !self.sound.available() {
+            "No audio"
+        } else if self.sound.muted {
+            "Sound off"
+        } else {
+            "Sound on"
+        };
+        self.status = Some(status.to_string());
+    }
+
+    /// 둘이 하는 대전이거나 네트워크 대전인지
+    fn in_versus(&self) -> bool {
+        self.rival.is_some() || self.remote.is_some()
+    }
+
+    /// 게임에서 쌓인 이벤트를 꺼내 효과음으로 내고, 큰 일은 알림으로 띄운다.
+    /// 대전에서는 한쪽이 보낸 쓰레기 줄을 상대에게 넘긴다.
+    fn play_events(&mut self) {
+        // 데모는 조용히 둔다
+        if self.state == AppState::Demo {
+            self.game.take_events();
+            return;
+        }
+        let rival_events = match &mut self.rival {
+            Some(rival) => rival.game.take_events(),
+            None => Vec::new(),
+        };
+        let mut events: VecDeque<GameEvent> = self.game.take_events().into();
+        while let Some(event) = events.pop_front() {
+            if let Some(text) = banner_for(event, self.banner.is_some()) {
+                self.show_banner(text);
+            }
+            match event {
+                GameEvent::Attack(rows) => {
+                    if let Some(rival) = &mut self.rival {
+                        rival.game.receive_garbage(rows);
+                    }
+                }
+                // 그리기 전에 누르고 있는 회전/홀드를 새 피스에 넣는다(IRS/IHS)
+                GameEvent::Spawned
+                    if self.state == AppState::Playing && self.playback.is_none() =>
+                {
+                    for input in self.initial.inputs() {
+                        self.apply(input);
+                    }
+                    // 홀드로 꺼낸 피스가 나온 것은 다시 받지 않는다
+                    let more = self.game.take_events().into_iter();
+                    events.extend(more.filter(|&e| e != GameEvent::Spawned));
+                }
+                _ => {}
+            }
+            // 네트워크 대전은 보드 대신 공격, 고정, 졌다는 것만 알린다
+            if let Some(remote) = &mut self.remote {
+                match event {
+                    GameEvent::Attack(rows) => remote.send(Message::Attack(rows)),
+                    GameEvent::Locked => remote.send(Message::Lock),
+                    GameEvent::GameOver => remote.send(Message::GameOver),
+                    _ => {}
+                }
+            }
+            self.sound.play(event);
+        }
+        for event in rival_events {
+            if let GameEvent::Attack(rows) = event {
+                self.game.receive_garbage(rows);
+            }
+            self.sound.play(event);
+        }
+    }
+
+    /// 보드 가운데 알림을 띄운다. 떠 있던 알림은 글자 폭이 다를 수 있으니 보드째 다시 그린다.
+    fn show_banner(&mut self, text: &'static str) {
+        if self.reduce_motion {
+            return;
+        }
+        if self.banner.is_some() {
+            self.renderer.invalidate();
+        }
+        self.banner = Some((text, Instant::now()));
+    }
+
+    /// 화면 전체 크기를 정하는 배치(대전이면 오른쪽 끝인 2P 보드)
+    fn outer_layout(&self) -> Layout {
+        match &self.rival {
+            Some(rival) => rival.renderer.layout(&rival.game.board),
+            None => self.renderer.layout(&self.game.board),
+        }
+    }
+
+    fn check_size(&mut self) {
+        let too_small = !self.outer_layout().fits(self.size);
+        if too_small && self.state == AppState::Playing {
+            self.set_state(AppState::Paused);
+            self.paused_for_size = true;
+        } else if !too_small && self.paused_for_size {
+            self.paused_for_size = false;
+            if self.state == AppState::Paused && !self.paused_for_focus {
+                self.set_state(AppState::Playing);
+            }
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if self.needs_clear {
+            // 이전 화면의 안내 패널이나 레이아웃 잔상을 지우고 전부 다시 그린다
+            queue!(out, terminal::Clear(ClearType::All))?;
+            self.renderer.invalidate();
+            if let Some(rival) = &mut self.rival {
+                rival.renderer.invalidate();
+            }
+            self.needs_clear = false;
+        }
+        let layout = self.renderer.layout(&self.game.board);
+        if !self.outer_layout().fits(self.size) {
+            return draw_too_small(out, self.outer_layout(), self.size);
+        }
+        if self.state == AppState::Menu {
+            return draw_menu(out, layout, &self.menu, &self.keys);
+        }
+
+        // 알림이 덮었던 보드 칸은 알림이 사라질 때 다시 그린다
+        if let Some((_, since)) = self.banner
+            && since.elapsed() >= BANNER_TIME
+        {
+            self.banner = None;
+            self.renderer.invalidate();
+        }
+
+        let started = Instant::now();
+        self.renderer
+            .draw(out, &self.game, &self.keys, self.theme)?;
+        if let Some(rival) = &mut self.rival {
+            rival
+                .renderer
+                .draw(out, &rival.game, &rival.keys, self.theme)?;
+        }
+        if let Some(stats) = &mut self.debug {
+            stats.record(started, started.elapsed());
+            if self.show_debug {
+                draw_debug(
+                    out,
+                    layout,
+                    stats.fps(),
+                    stats.avg_frame_ms(),
+                    self.game.drop_interval_ms(),
+                    self.renderer.cells_drawn(),
+                )?;
+            }
+        }
+        if let Some(status) = &self.status {
+            draw_status(out, layout, status)?;
+        } else if let Some(remote) = &self.remote {
+            let text = if remote.disconnected {
+                "Opponent disconnected".to_string()
+            } else {
+                format!("Rival pieces: {}", remote.pieces)
+            };
+            draw_status(out, layout, &text)?;
+        }
+        if let Some((text, _)) = self.banner
+            && self.state == AppState::Playing
+        {
+            draw_banner(out, layout, text)?;
+        }
+        match self.state {
+            AppState::Countdown { since } => {
+                let left = COUNTDOWN.saturating_sub(since.elapsed());
+                draw_countdown(out, layout, left.as_secs() + 1)
+            }
+            AppState::Paused => draw_paused(out, layout),
+            AppState::ConfirmRestart => draw_confirm(out, layout, "Restart?"),
+            AppState::ConfirmQuit => draw_confirm(out, layout, "Quit?"),
+            // 대전은 먼저 막힌 쪽이 진다
+            AppState::GameOver { .. } if let Some(rival) = &self.rival => {
+                draw_versus_result(out, layout, !self.game.game_over, true)?;
+                let rival_layout = rival.renderer.layout(&rival.game.board);
+                draw_versus_result(out, rival_layout, !rival.game.game_over, true)
+            }
+            AppState::GameOver { .. } if self.remote.is_some() => {
+                draw_versus_result(out, layout, !self.game.game_over, false)
+            }
+            // 막힌 자리가 보이도록 결과 패널은 조금 늦게 띄운다
+            AppState::GameOver { since, .. }
+                if self.game.end_reason == Some(EndReason::TopOut)
+                    && since.elapsed() < TOP_OUT_REVEAL =>
+            {
+                Ok(())
+            }
+            AppState::GameOver { stats: true, .. } => draw_stats(out, layout, &self.game),
+            AppState::GameOver { .. } => draw_game_over(out, layout, &self.game),
+            AppState::Demo => draw_status(out, layout, "Demo - press any key"),
+            AppState::Menu | AppState::Playing => Ok(()),
+        }
+    }
+
+    /// 다음 입력을 기다릴 시간. 한가하면 길게 잡되 다음 중력 낙하는 늦추지 않는다.
+    fn poll_timeout(&self) -> Duration {
+        let moving = match self.state {
+            // 카운트다운 숫자는 시간에 맞춰 바뀌어야 하니 쉬지 않는다
+            AppState::Demo | AppState::Countdown { .. } => true,
+            AppState::Playing => {
+                self.playback.is_some()
+                    || self.rival.is_some()
+                    || self.remote.is_some()
+                    || self.banner.is_some()
+                    || self.game.is_clearing()
+                    || self.game.is_entering()
+                    || self.game.is_grounded()
+                    || self.das.is_held()
+                    || self.soft_drop.is_held()
+            }
+            _ => false,
+        };
+        let tick = if self.low_power {
+            self.tick.max(LOW_POWER_TICK)
+        } else {
+            self.tick
+        };
+        let idle = !moving && self.last_input.elapsed() >= IDLE_AFTER;
+        let timeout = if idle { tick.max(IDLE_TICK) } else { tick };
+        if self.state != AppState::Playing || self.game.is_clearing() || self.game.is_entering() {
+            return timeout;
+        }
+        let interval = Duration::from_millis(self.game.drop_interval_ms());
+        timeout.min(self.gravity.until_next(interval, Instant::now()))
+    }
+
+    /// 이벤트 하나를 처리한다. 종료해야 하면 false.
+    fn handle_event(&mut self, event: Event) -> bool {
+        if matches!(event, Event::Key(_) | Event::Mouse(_)) {
+            self.last_input = Instant::now();
+        }
+        let event = match event {
+            Event::Key(mut key) => {
+                let Some(kind) = key_kind(key.kind, self.das_enabled) else {
+                    return true;
+                };
+                key.kind = kind;
+                Event::Key(key)
+            }
+            event => event,
+        };
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) if modifiers.contains(KeyModifiers::CONTROL) => return false,
+            Event::Key(KeyEvent {
+                code,
+                kind: KeyEventKind::Press,
+                ..
+            }) => return self.handle_key(code),
+            Event::Key(KeyEvent {
+                code,
+                kind: KeyEventKind::Release,
+                ..
+            }) => match self.keys.action_for(code) {
+                Some(Action::MoveLeft) => self.das.release(-1),
+                Some(Action::MoveRight) => self.das.release(1),
+                Some(Action::SoftDrop) => self.soft_drop.release(),
+                action => {
+                    if let Some(input) = action.and_then(Action::input) {
+                        self.initial.release(input);
+                    }
+                }
+            },
+            // 자리를 비운 사이 막히지 않도록 포커스를 잃으면 멈추고, 돌아오면 이어 간다
+            Event::FocusLost if self.state == AppState::Playing => {
+                self.set_state(AppState::Paused);
+                self.paused_for_focus = true;
+                // 포커스 밖에서 뗀 키는 떼기가 오지 않으니 누르고 있던 키를 모두 놓는다
+                self.das.release(-1);
+                self.das.release(1);
+                self.soft_drop.release();
+            }
+            Event::FocusGained if self.paused_for_focus => {
+                if self.paused_for_size {
+                    self.paused_for_focus = false;
+                } else if self.state == AppState::Paused {
+                    self.set_state(AppState::Playing);
+                }
+            }
+            Event::Mouse(mouse) if self.state == AppState::Playing => self.handle_mouse(mouse),
+            // 창 크기가 바뀌면 이전 레이아웃 잔상을 지우고 다음 프레임에 새로 그린다
+            Event::Resize(cols, rows) => {
+                self.size = (cols, rows);
+                self.needs_clear = true;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        let action = self.keys.action_for(code);
+        // 디버그 오버레이는 어느 화면에서든 켜고 끈다(끄면 지운 자리를 다시 그린다)
+        if action == Some(Action::ToggleDebug) {
+            if self.debug.is_some() {
+                self.show_debug = !self.show_debug;
+                self.needs_clear = true;
+            }
+            return true;
+        }
+        match self.state {
+            // 조작 화면은 모든 키를 받는다(새 키로 쓸 수 있어야 한다)
+            AppState::Menu if self.menu.in_controls() => {
+                self.menu_since = Instant::now();
+                let path = self.config_path.as_deref();
+                let saved = match self.menu.controls_key(code, &mut self.keys) {
+                    Some(ControlsChange::Key(rebound)) => {
+                        Config::save_keys(path, rebound.config_name(), &self.keys.names(rebound))
+                    }
+                    Some(ControlsChange::Mirror) => {
+                        Config::save_flag(path, "mirror_controls", self.keys.mirrored)
+                    }
+                    None => Ok(()),
+                };
+                if let Err(err) = saved {
+                    self.menu.set_controls_message(format!("Not saved: {err}"));
+                }
+                // 시작 메뉴로 돌아가면 패널 크기가 달라진다
+                if !self.menu.in_controls() {
+                    self.needs_clear = true;
+                }
+            }
+            // 메뉴는 키 배치와 상관없이 화살표와 Enter로 조작한다
+            AppState::Menu => {
+                self.menu_since = Instant::now();
+                match code {
+                    KeyCode::Up => self.menu.up(),
+                    KeyCode::Down => self.menu.down(),
+                    KeyCode::Left => self.menu.adjust(-1),
+                    KeyCode::Right => self.menu.adjust(1),
+                    KeyCode::Enter if self.menu.select() => self.start_from_menu(),
+                    KeyCode::Enter => self.needs_clear = true,
+                    _ if action == Some(Action::Quit) => return false,
+                    _ => {}
+                }
+            }
+            AppState::Demo => self.set_state(AppState::Menu),
+            AppState::Countdown { .. } => {
+                if action == Some(Action::Quit) {
+                    return false;
+                }
+            }
+            AppState::Playing => {
+                // 대전에서는 2P 키가 먼저라 같은 키에 묶인 1P 동작은 듣지 않는다
+                if let Some(rival) = &mut self.rival
+                    && let Some(input) = rival.keys.action_for(code).and_then(Action::input)
+                {
+                    rival.press(input);
+                    return true;
+                }
+                return self.handle_play_action(action);
+            }
+            AppState::Paused => match action {
+                Some(Action::Pause) => self.set_state(AppState::Playing),
+                Some(Action::Quit) => return false,
+                Some(Action::CycleTheme) => self.theme = self.theme.next(),
+                Some(Action::ToggleGhost) => self.toggle_ghost(),
+                Some(Action::ToggleGrid) => self.toggle_grid(),
+                Some(Action::ToggleRuler) => self.toggle_ruler(),
+                Some(Action::ToggleMute) => self.toggle_mute(),
+                Some(Action::SaveReplay) => self.save_replay(),
+                Some(Action::SaveGame) => self.save_game(),
+                // 일시정지 중에는 이동/회전/드롭 입력을 무시
+                _ => {}
+            },
+            // 재시작 확인 중에는 Y/N(Esc)만 받는다
+            AppState::ConfirmRestart => match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.restart(),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.set_state(AppState::Playing);
+                }
+                _ => {}
+            },
+            // 종료 확인은 Y만 종료하고 다른 키는 모두 취소로 본다
+            AppState::ConfirmQuit => match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => return false,
+                _ => self.set_state(AppState::Playing),
+            },
+            // 게임 오버 상태에서는 재시작/종료 입력만 처리(통계 화면은 Enter/Esc/Space로 닫는다)
+            AppState::GameOver { since, stats } => {
+                if stats && matches!(code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char(' ')) {
+                    self.set_state(AppState::GameOver {
+                        since,
+                        stats: false,
+                    });
+                } else if self.remote.is_none()
+                    && (matches!(code, KeyCode::Char('r') | KeyCode::Char('R'))
+                        || action == Some(Action::Restart))
+                {
+                    self.restart();
+                } else if action == Some(Action::SaveReplay) {
+                    self.save_replay();
+                } else if action == Some(Action::Quit) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// 왼쪽 클릭은 피스를 클릭한 열 쪽으로 옮기고, 오른쪽 클릭은 시계 방향으로 돌린다.
+    /// 키로 한 것과 같은 입력을 한 칸씩 기록한다.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        // 움직이기만 한 것은 입력이 아니다
+        if !matches!(mouse.kind, MouseEventKind::Down(_)) {
+            return;
+        }
+        self.game.note_input(Instant::now());
+        if self.playback.is_some() || self.game.is_clearing() {
+            return;
+        }
+        let action = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // 보드 바깥을 누르면 아무것도 하지 않는다
+                let layout = self.renderer.layout(&self.game.board);
+                let Some(target) = layout.board_col(mouse.column, mouse.row) else {
+                    return;
+                };
+                Buffered::Column(target as i32)
+            }
+            MouseEventKind::Down(MouseButton::Right) => Buffered::Input(Input::RotateCw),
+            _ => return,
+        };
+        // 스폰 대기 중이면 키보드 입력처럼 다음 피스가 나올 때 넣는다
+        if self.game.is_entering() {
+            self.buffered.push(action);
+        } else {
+            self.apply_buffered(action);
+        }
+    }
+
+    fn apply_buffered(&mut self, action: Buffered) {
+        match action {
+            Buffered::Input(input) => self.apply(input),
+            Buffered::Column(target) => self.move_to_column(target),
+        }
+    }
+
+    /// 지금 피스가 `target` 칸에 걸칠 때까지 좌우로 옮긴다.
+    fn move_to_column(&mut self, target: i32) {
+        loop {
+            let cells = self.game.current.absolute_cells();
+            let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+            let right = cells.iter().map(|&(x, _)| x).max().unwrap_or(0);
+            let input = if target < left {
+                Input::Left
+            } else if target > right {
+                Input::Right
+            } else {
+                break;
+            };
+            let before = self.game.current.x;
+            self.apply(input);
+            // 벽이나 블록에 막히면 거기서 멈춘다
+            if self.game.current.x == before {
+                break;
+            }
+        }
+    }
+
+    fn handle_play_action(&mut self, action: Option<Action>) -> bool {
+        let Some(action) = action else {
+            return true;
+        };
+        match action {
+            Action::Pause => self.set_state(AppState::Paused),
+            Action::Quit if self.confirm_quit => self.set_state(AppState::ConfirmQuit),
+            Action::Quit => return false,
+            Action::CycleTheme => self.theme = self.theme.next(),
+            Action::ToggleGhost => self.toggle_ghost(),
+            Action::ToggleGrid => self.toggle_grid(),
+            Action::ToggleRuler => self.toggle_ruler(),
+            Action::ToggleMute => self.toggle_mute(),
+            // 네트워크 대전은 상대와 함께 다시 시작할 수 없다
+            Action::Restart if self.remote.is_some() => {}
+            Action::Restart => self.set_state(AppState::ConfirmRestart),
+            Action::SaveReplay => self.save_replay(),
+            Action::SaveGame => self.save_game(),
+            // 재생 중에는 기록된 입력만 게임을 움직인다
+            _ if self.playback.is_some() => {}
+            // 줄 제거 애니메이션 중에는 하드 드롭으로 건너뛰기만 받는다
+            _ if self.game.is_clearing() => {
+                if action == Action::HardDrop {
+                    self.apply(Input::Clear);
+                    self.gravity.reset(Instant::now());
+                }
+            }
+            _ => {
+                let Some(input) = action.input() else {
+                    return true;
+                };
+                // 스프린트 시계는 피스를 움직이는 첫 입력부터 잰다(일시정지나 테마 키로는 시작하지 않는다)
+                self.game.note_input(Instant::now());
+                // 방금 하드 드롭했으면 두 번 눌린 하드 드롭은 기록하지 않고 버린다
+                if input == Input::HardDrop && self.game.hard_drop_locked_out(Instant::now()) {
+                    return true;
+                }
+                // 끈끈한 드롭: 첫 누름은 착지 자리만 밝게 보여 주고 기록하지 않는다
+                if input == Input::HardDrop && !self.game.confirm_hard_drop(Instant::now()) {
+                    return true;
+                }
+                // 떼기를 받는 터미널이 누르고 있는 키를 눌림으로 다시 보내면 DAS에 맡긴다
+                let dir = match input {
+                    Input::Left => -1,
+                    Input::Right => 1,
+                    _ => 0,
+                };
+                if self.das_enabled && self.das.holds(dir) {
+                    return true;
+                }
+                // 누르고 있는 동안은 반복 타이머만 내린다. 떼기를 모르는 터미널의 키 반복은
+                // 눌림이 이어진다는 표시로만 쓴다.
+                if input == Input::SoftDrop && self.soft_drop.is_held() {
+                    self.soft_drop.press(Instant::now());
+                    return true;
+                }
+                // 스폰 대기 중이면 다음 피스가 나올 때 넣는다(누르고 있는 이동 키는 그동안에도 충전된다)
+                if self.game.is_entering() {
+                    self.buffered.push(Buffered::Input(input));
+                } else {
+                    self.apply(input);
+                }
+                self.initial.press(input);
+                match input {
+                    Input::Left | Input::Right if self.das_enabled => {
+                        self.das.press(dir, Instant::now());
+                    }
+                    Input::SoftDrop => {
+                        self.soft_drop.press(Instant::now());
+                        self.gravity.reset(Instant::now());
+                    }
+                    Input::SonicDrop | Input::HardDrop => {
+                        self.gravity.reset(Instant::now());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// 데모: 봇이 정한 입력을 일정한 간격으로 하나씩 넣는다. 중력과 락 딜레이는 쓰지 않고
+    /// 피스마다 하드 드롭으로 끝낸다. 끝나면 새 판을 시작한다.
+    fn update_demo(&mut self) {
+        if self.game.game_over {
+            self.set_state(AppState::Demo);
+            return;
+        }
+        if self.game.is_clearing() {
+            self.game.update_clear(Instant::now());
+            return;
+        }
+        if self.game.is_entering() {
+            self.game.update_entry(Instant::now());
+            return;
+        }
+        if self.last_demo_step.elapsed() < DEMO_STEP {
+            return;
+        }
+        self.last_demo_step = Instant::now();
+        if self.demo_moves.is_empty() {
+            self.demo_moves = plan_moves(&self.game).into();
+        }
+        if let Some(input) = self.demo_moves.pop_front() {
+            self.game.apply(input);
+        }
+    }
+
+    /// 입력과 상관없이 시간이 흐르며 일어나는 일(카운트다운, DAS, 소프트 드롭 반복, 중력, 락 딜레이)
+    fn update(&mut self) {
+        // 조작 화면에서 키를 고르는 중에는 데모로 넘어가지 않는다
+        if self.state == AppState::Menu
+            && !self.menu.in_controls()
+            && self.menu_since.elapsed() >= ATTRACT_DELAY
+        {
+            self.config = self.menu.config;
+            self.set_state(AppState::Demo);
+        }
+        if self.state == AppState::Demo {
+            self.update_demo();
+            return;
+        }
+        if let AppState::Countdown { since } = self.state
+            && since.elapsed() >= COUNTDOWN
+        {
+            self.set_state(AppState::Playing);
+            self.start_clock();
+        }
+        if self.state != AppState::Playing {
+            return;
+        }
+        // 직전 입력(하드 드롭 등)으로 게임이 끝났으면 더 움직이지 않는다
+        // (대전에서는 어느 한쪽이라도 막히면 끝나고, 통계 화면 없이 결과만 띄운다)
+        if let Some(remote) = &mut self.remote {
+            remote.receive(&mut self.game);
+        }
+        let rival_over = self.rival.as_ref().is_some_and(|r| r.game.game_over)
+            || self.remote.as_ref().is_some_and(|r| r.lost);
+        if self.game.game_over || rival_over {
+            self.set_state(AppState::GameOver {
+                since: Instant::now(),
+                stats: !self.in_versus(),
+            });
+            return;
+        }
+        if let Some(rival) = &mut self.rival {
+            rival.update();
+        }
+
+        // ── 리플레이 재생: 중력과 락도 기록된 대로만 일어난다 ──
+        if let Some(playback) = &mut self.playback {
+            for input in playback.due(self.clock.ms(Instant::now())) {
+                if !matches!(
+                    input,
+                    Input::Gravity | Input::Lock | Input::Clear | Input::Spawn | Input::Garbage(_)
+                ) {
+                    self.game.note_input(Instant::now());
+                }
+                self.game.apply(input);
+            }
+            if playback.finished() && self.status.is_none() {
+                self.status = Some("Replay finished".to_string());
+            }
+            self.game.update_timer();
+            return;
+        }
+
+        // ── 줄 제거 애니메이션: 끝날 때까지 중력과 쓰레기 줄도 멈춘다 ──
+        if self.game.is_clearing() {
+            if self.game.update_clear(Instant::now()) {
+                self.recording
+                    .push(self.clock.ms(Instant::now()), Input::Clear);
+                self.gravity.reset(Instant::now());
+            }
+            self.game.update_timer();
+            return;
+        }
+
+        // ── 스폰 대기(ARE): 다음 피스가 나오면 그동안 누른 입력을 차례로 넣는다 ──
+        if self.game.is_entering() {
+            if self.game.update_entry(Instant::now()) {
+                self.recording
+                    .push(self.clock.ms(Instant::now()), Input::Spawn);
+                self.gravity.reset(Instant::now());
+                // 아직 누르고 있는 회전/홀드는 초기 입력(IRS/IHS)으로 한 번만 넣는다
+                for action in std::mem::take(&mut self.buffered) {
+                    match action {
+                        Buffered::Input(input) if self.initial.is_held(input) => {}
+                        action => self.apply_buffered(action),
+                    }
+                }
+            }
+            self.game.update_timer();
+            return;
+        }
+
+        // ── 누르고 있는 좌우 키 자동 이동(DAS) ──
+        if let Some((dir, steps)) = self.das.take_shifts(Instant::now()) {
+            let input = if dir < 0 { Input::Left } else { Input::Right };
+            for _ in 0..steps {
+                let x = self.game.current.x;
+                self.apply(input);
+                if self.game.current.x == x {
+                    break;
+                }
+            }
+        }
+
+        // ── 누르고 있는 소프트 드롭: 칸마다 점수를 주고, 바닥에서는 락 딜레이에 맡긴다.
+        //    바로 고정하게 했으면 바닥에 닿은 다음 칸에서 고정하고, 다음 피스는 다시 눌러야 내린다 ──
+        if let Some(multiplier) = self.soft_drop_multiplier {
+            let interval = soft_drop_interval(self.game.drop_interval_ms(), multiplier);
+            self.soft_drop.set_interval(interval);
+        }
+        for _ in 0..self.soft_drop.take_drops(Instant::now()) {
+            if self.game.is_grounded() {
+                if self.game.soft_drop_lock == SoftDropLock::InstantLock {
+                    self.apply(Input::SoftDrop);
+                    self.soft_drop.release();
+                }
+                break;
+            }
+            self.apply(Input::SoftDrop);
+            self.gravity.reset(Instant::now());
+        }
+
+        // ── 치즈 모드: 일정 시간마다 쓰레기 줄이 올라온다 ──
+        if let (Some(due), Some(interval)) = (self.next_garbage, self.game.mode.garbage_interval())
+        {
+            let now_ms = self.clock.ms(Instant::now());
+            if now_ms >= due {
+                let hole = self.game.next_garbage_hole() as u8;
+                self.apply(Input::Garbage(hole));
+                self.next_garbage = Some(now_ms + interval.as_millis() as u64);
+            }
+        }
+
+        // ── 자동 낙하(중력) + 락 딜레이: 간격이 프레임보다 짧으면 밀린 칸을 한꺼번에 내린다 ──
+        let interval = Duration::from_millis(self.game.drop_interval_ms());
+        let drops = self.gravity.take_drops(interval, Instant::now());
+        if self.game.mode.has_gravity() {
+            for _ in 0..drops {
+                if self.game.is_grounded() {
+                    break;
+                }
+                self.apply(Input::Gravity);
+            }
+        }
+        // 바닥에 닿은 피스는 바로 고정하지 않고 락 딜레이가 끝날 때 고정
+        if self.game.update_lock(Instant::now()) {
+            self.recording
+                .push(self.clock.ms(Instant::now()), Input::Lock);
+        }
+        self.game.update_timer();
+    }
+}
+
+/// 시드를 고정했으면 그 시드로, 아니면 임의의 시드로 새 게임
+fn new_game(seed: Option<u64>, config: GameConfig) -> Game {
+    match seed {
+        Some(seed) => Game::with_seed_and_config(seed, config),
+        None => Game::with_config(config),
+    }
+}
+
+/// 명령줄 옵션이 설정 파일보다 먼저인 게임 설정(네트워크 대전은 접속할 때 이것을 맞춰 본다)
+pub fn game_config(options: &Options, user: &Config) -> GameConfig {
+    GameConfig {
+        width: options.width.unwrap_or(user.width),
+        height: options.height.unwrap_or(user.height),
+        randomizer: options.randomizer.unwrap_or(user.randomizer),
+        ..options.game
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{BOARD_H, BOARD_W, MAX_BOARD_H, MAX_BOARD_W, MIN_BOARD_H, MIN_BOARD_W, PieceKind};
+use std::fmt;
+use std::str::FromStr;
+
+// ── 보드 ────────────────────────────────────────────────────────────
+
+/// 보드에 쌓인 블록 한 칸. 색은 렌더러가 정한다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Block {
+    /// 고정된 피스의 일부(어떤 피스였는지 기억한다)
+    Piece(PieceKind),
+    /// 아래에서 올라온 쓰레기 줄
+    Garbage,
+}
+
+/// 보드 한 칸(`None`이면 빈칸)
+pub type Cell = Option<Block>;
+
+// 저장 파일에서 빈칸과 쓰레기 칸을 나타내는 글자(피스 칸은 피스 글자)
+const EMPTY_CHAR: char = '.';
+const GARBAGE_CHAR: char = '#';
+
+/// 쌓인 블록. `grid[y][x]`로 읽고, 크기는 만들 때 정한다(기본 `BOARD_W`×`BOARD_H`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    pub grid: Vec<Vec<Cell>>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Self::with_size(BOARD_W, BOARD_H)
+    }
+
+    /// 가로 `width`칸, 세로 `height`줄짜리 빈 보드
+    pub fn with_size(width: usize, height: usize) -> Self {
+        Self {
+            grid: vec![vec![None; width]; height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+
+    /// 해당 좌표가 비어 있고 보드 범위를 만족하는지 확인
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        if x < 0 || x >= self.width() as i32 {
+            return false;
+        }
+        if y >= self.height() as i32 {
+            return false;
+        }
+        // 보드 위쪽(y < 0)은 스폰/회전을 위해 허용
+        if y < 0 {
+            return true;
+        }
+        self.grid[y as usize][x as usize].is_none()
+    }
+
+    /// 모든 좌표가 유효한 위치인지 확인
+    pub fn fits(&self, cells: &[(i32, i32)]) -> bool {
+        cells.iter().all(|&(x, y)| self.is_free(x, y))
+    }
+
+    /// 피스를 보드에 고정
+    pub fn lock(&mut self, cells: &[(i32, i32)], kind: PieceKind) {
+        for &(x, y) in cells {
+            if y >= 0 && y < self.height() as i32 && x >= 0 && x < self.width() as i32 {
+                self.grid[y as usize][x as usize] = Some(Block::Piece(kind));
+            }
+        }
+    }
+
+    /// 모든 줄을 한 칸씩 올리고 맨 아래에 `hole_x`부터 `hole_w`칸만 빈 쓰레기 줄을 넣는다.
+    /// 맨 윗줄에 블록이 있어 보드 밖으로 밀려나면 true(막힘).
+    pub fn add_garbage_row(&mut self, hole_x: usize, hole_w: usize) -> bool {
+        let overflow = self.grid[0].iter().any(|c| c.is_some());
+        // 맨 윗줄을 맨 아래로 돌린 뒤 쓰레기 줄로 덮어쓴다
+        self.grid.rotate_left(1);
+        let width = self.width();
+        let bottom = self.grid.last_mut().expect("board has rows");
+        bottom.fill(Some(Block::Garbage));
+        let hole_x = hole_x.min(width - hole_w);
+        bottom[hole_x..hole_x + hole_w].fill(None);
+        overflow
+    }
+
+    /// 맨 위 `rows`줄을 비운다(그 아래 블록은 움직이지 않는다).
+    pub fn clear_top_rows(&mut self, rows: usize) {
+        for row in self.grid.iter_mut().take(rows) {
+            row.fill(None);
+        }
+    }
+
+    /// `rows`에 든 줄을 빼고 모든 칸이 비었는지
+    pub fn is_empty_without(&self, rows: &[usize]) -> bool {
+        self.grid
+            .iter()
+            .enumerate()
+            .all(|(y, row)| rows.contains(&y) || row.iter().all(|c| c.is_none()))
+    }
+
+    /// 가득 찬 줄의 y좌표들(위에서부터). 지우지는 않는다.
+    pub fn full_rows(&self) -> Vec<usize> {
+        (0..self.height())
+            .filter(|&y| is_full(&self.grid[y]))
+            .collect()
+    }
+
+    /// 가득 찬 줄을 제거하고 제거한 줄 수를 반환.
+    pub fn clear_lines(&mut self) -> u32 {
+        let cleared = self.grid.iter().filter(|row| is_full(row)).count();
+        // 지울 줄이 없으면(고정할 때 대부분) 보드를 건드리지 않는다
+        if cleared == 0 {
+            return 0;
+        }
+        // 아래에서부터 남길 줄을 내려 담고(순서 유지), 위로 올라온 찬 줄은 비운다
+        let mut write = self.height();
+        for y in (0..self.height()).rev() {
+            if !is_full(&self.grid[y]) {
+                write -= 1;
+                self.grid.swap(write, y);
+            }
+        }
+        for row in &mut self.grid[..write] {
+            row.fill(None);
+        }
+        cleared as u32
+    }
+
+    // ── 봇과 테스트용 지표 ──
+
+    /// 열마다 가장 높은 블록의 높이(바닥에서 센 줄 수, 빈 열은 0).
+    /// 보드 폭이 정해져 있지 않아 배열 대신 `Vec`으로 돌려준다.
+    pub fn column_heights(&self) -> Vec<usize> {
+        (0..self.width())
+            .map(|x| {
+                let top = (0..self.height()).find(|&y| self.grid[y][x].is_some());
+                top.map_or(0, |y| self.height() - y)
+            })
+            .collect()
+    }
+
+    /// 위에 블록이 있는 빈칸 수
+    pub fn holes(&self) -> usize {
+        let heights = self.column_heights();
+        (0..self.width())
+            .map(|x| {
+                let top = self.height() - heights[x];
+                (top..self.height())
+                    .filter(|&y| self.grid[y][x].is_none())
+                    .count()
+            })
+            .sum()
+    }
+
+    /// 이웃한 열끼리 높이 차이의 합
+    pub fn bumpiness(&self) -> usize {
+        self.column_heights()
+            .windows(2)
+            .map(|pair| pair[0].abs_diff(pair[1]))
+            .sum()
+    }
+
+    /// 모든 열 높이의 합
+    pub fn aggregate_height(&self) -> usize {
+        self.column_heights().iter().sum()
+    }
+}
+
+/// 한 줄에 한 행씩, 빈칸은 `.`, 쓰레기 칸은 `#`, 피스 칸은 피스 글자로 쓴다.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.grid {
+            let line: String = row
+                .iter()
+                .map(|cell| match cell {
+                    None => EMPTY_CHAR,
+                    Some(Block::Garbage) => GARBAGE_CHAR,
+                    Some(Block::Piece(kind)) => kind.letter(),
+                })
+                .collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Board {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = s
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| match c {
+                        EMPTY_CHAR => Ok(None),
+                        GARBAGE_CHAR => Ok(Some(Block::Garbage)),
+                        _ => PieceKind::from_letter(c)
+                            .map(|kind| Some(Block::Piece(kind)))
+                            .ok_or(format!("unknown cell '{c}'")),
+                    })
+                    .collect::<Result<Vec<Cell>, String>>()
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let board = Board { grid };
+        let (width, height) = (board.width(), board.height());
+        if board.grid.iter().any(|row| row.len() != width) {
+            return Err("board rows have different widths".to_string());
+        }
+        if !(MIN_BOARD_W..=MAX_BOARD_W).contains(&width)
+            || !(MIN_BOARD_H..=MAX_BOARD_H).contains(&height)
+        {
+            return Err(format!("unsupported board size {width}x{height}"));
+        }
+        Ok(board)
+    }
+}
+
+fn is_full(row: &[Cell]) -> bool {
+    row.iter().all(|c| c.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_single_full_row() {
+        // 한 줄이 가득 찼을 때 정확히 1줄 제거되는지 확인
+        let mut board = Board::new();
+        let y = BOARD_H - 1;
+        for x in 0..BOARD_W {
+            board.grid[y][x] = Some(Block::Piece(PieceKind::Z));
+        }
+
+        let cleared = board.clear_lines();
+        assert_eq!(cleared, 1);
+        assert!(board.grid[y].iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn clears_multiple_rows_and_compacts() {
+        // 여러 줄 제거 후 아래로 내려오는지 확인
+        let mut board = Board::new();
+        let bottom = BOARD_H - 1;
+        let above = BOARD_H - 2;
+        for x in 0..BOARD_W {
+            board.grid[bottom][x] = Some(Block::Piece(PieceKind::J));
+            board.grid[above][x] = Some(Block::Piece(PieceKind::J));
+        }
+        board.grid[BOARD_H - 3][0] = Some(Block::Piece(PieceKind::S));
+
+        assert_eq!(board.full_rows(), vec![above, bottom]);
+        let cleared = board.clear_lines();
+        assert_eq!(cleared, 2);
+        assert!(board.grid[bottom][0].is_some());
+        assert!(board.grid[above].iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn garbage_row_pushes_stack_up() {
+        let mut board = Board::new();
+        board.grid[BOARD_H - 1][0] = Some(Block::Piece(PieceKind::T));
+        assert!(!board.add_garbage_row(3, 1));
+
+        assert_eq!(board.grid[BOARD_H - 2][0], Some(Block::Piece(PieceKind::T)));
+        let bottom = &board.grid[BOARD_H - 1];
+        assert_eq!(bottom[3], None);
+        assert_eq!(bottom.iter().filter(|c| c.is_some()).count(), BOARD_W - 1);
+
+        board.grid[0][5] = Some(Block::Garbage);
+        assert!(board.add_garbage_row(0, 1));
+    }
+
+    #[test]
+    fn metrics_count_heights_holes_and_bumps() {
+        // 6x4 보드. 맨 아래 두 줄에 구멍 두 개.
+        let board: Board = "\
+            ......\n\
+            .T....\n\
+            #T.#..\n\
+            #.#.#.\n"
+            .parse()
+            .unwrap();
+        assert_eq!(board.column_heights(), vec![2, 3, 1, 2, 1, 0]);
+        assert_eq!(board.holes(), 2);
+        assert_eq!(board.bumpiness(), 1 + 2 + 1 + 1 + 1);
+        assert_eq!(board.aggregate_height(), 9);
+
+        let empty = Board::new();
+        assert_eq!(empty.column_heights(), vec![0; BOARD_W]);
+        assert_eq!((empty.holes(), empty.bumpiness()), (0, 0));
+    }
+
+    #[test]
+    fn clearing_nothing_leaves_the_grid_untouched() {
+        let mut board = Board::new();
+        for x in 0..BOARD_W - 1 {
+            board.grid[BOARD_H - 1][x] = Some(Block::Piece(PieceKind::T));
+        }
+        board.grid[BOARD_H - 2][3] = Some(Block::Garbage);
+        let before = board.grid.clone();
+        assert_eq!(board.clear_lines(), 0);
+        assert_eq!(board.grid, before);
+    }
+
+    #[test]
+    fn small_board_clears_a_line() {
+        let mut board = Board::with_size(6, 12);
+        assert_eq!((board.width(), board.height()), (6, 12));
+        for x in 0..6 {
+            board.grid[11][x] = Some(Block::Garbage);
+        }
+        board.grid[10][2] = Some(Block::Piece(PieceKind::L));
+        assert!(!board.is_free(6, 0) && !board.is_free(0, 12));
+
+        assert_eq!(board.clear_lines(), 1);
+        assert_eq!(board.grid[11][2], Some(Block::Piece(PieceKind::L)));
+        assert!(board.grid[10].iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut board = Board::with_size(5, 4);
+        board.grid[3][0] = Some(Block::Garbage);
+        board.grid[3][2] = Some(Block::Piece(PieceKind::L));
+        let text = board.to_string();
+        assert_eq!(text, ".....\n.....\n.....\n#.L..\n");
+        assert_eq!(text.parse::<Board>(), Ok(board));
+
+        assert!("....\n...\n....\n....\n".parse::<Board>().is_err());
+        assert!("....\n....\n....\n...x\n".parse::<Board>().is_err());
+        assert!("....\n".parse::<Board>().is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{Game, Input};
+use std::time::Duration;
+
+// 놓은 뒤의 보드를 평가하는 가중치. 지운 줄은 보상하고 높이, 구멍, 울퉁불퉁함은 벌점이다.
+const LINE_WEIGHT: f64 = 0.76;
+const HEIGHT_WEIGHT: f64 = -0.51;
+const HOLE_WEIGHT: f64 = -0.36;
+const BUMPINESS_WEIGHT: f64 = -0.18;
+
+/// 지금 피스를 놓을 자리를 골라 그 자리로 가는 입력을 돌려준다(마지막은 하드 드롭).
+///
+/// 회전 수와 좌우 이동 수의 조합마다 게임을 복제해 실제로 입력을 넣어 보고, 놓인 뒤의
+/// 보드 지표로 가장 좋은 조합을 고른다. 모든 수가 막히면 그냥 하드 드롭한다.
+pub fn plan_moves(game: &Game) -> Vec<Input> {
+    let rotations = if !game.current.kind.def().rotates() {
+        0
+    } else {
+        3
+    };
+    let width = game.board.width() as i32;
+    let mut best: Option<(f64, Vec<Input>)> = None;
+    for turns in 0..=rotations {
+        for shift in -width..=width {
+            let Some((score, inputs)) = try_placement(game, turns, shift) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|(top, _)| score > *top) {
+                best = Some((score, inputs));
+            }
+        }
+    }
+    best.map_or_else(|| vec![Input::HardDrop], |(_, inputs)| inputs)
+}
+
+/// 시계 방향으로 `turns`번 돌리고 `shift`칸 옮긴 뒤 하드 드롭해 본다.
+/// 회전이나 이동이 막혀 다른 조합과 같은 자리가 되면 `None`.
+fn try_placement(game: &Game, turns: u8, shift: i32) -> Option<(f64, Vec<Input>)> {
+    let mut trial = game.clone();
+    // 찬 줄이 바로 지워져야 놓은 뒤의 보드를 평가할 수 있다
+    trial.clear_delay = Duration::ZERO;
+    let mut inputs = Vec::new();
+    for _ in 0..turns {
+        if !trial.try_rotate() {
+            return None;
+        }
+        inputs.push(Input::RotateCw);
+    }
+    let step = if shift < 0 { Input::Left } else { Input::Right };
+    for _ in 0..shift.abs() {
+        let x = trial.current.x;
+        trial.apply(step);
+        if trial.current.x == x {
+            return None;
+        }
+        inputs.push(step);
+    }
+    trial.apply(Input::HardDrop);
+    inputs.push(Input::HardDrop);
+    if trial.game_over {
+        return Some((f64::MIN, inputs));
+    }
+    let board = &trial.board;
+    let score = LINE_WEIGHT * f64::from(trial.lines - game.lines)
+        + HEIGHT_WEIGHT * board.aggregate_height() as f64
+        + HOLE_WEIGHT * board.holes() as f64
+        + BUMPINESS_WEIGHT * board.bumpiness() as f64;
+    Some((score, inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BOARD_H, BOARD_W, Block, Piece, PieceKind};
+
+    #[test]
+    fn fills_the_gap_that_clears_a_line() {
+        // 바닥 줄 오른쪽 끝 한 칸만 비어 있으면 세운 I로 그 줄을 지운다
+        let mut game = Game::with_seed(3);
+        for row in BOARD_H - 4..BOARD_H {
+            for x in 0..BOARD_W - 1 {
+                game.board.grid[row][x] = Some(Block::Garbage);
+            }
+        }
+        game.current = Piece::new(PieceKind::I);
+        for input in plan_moves(&game) {
+            game.apply(input);
+        }
+        assert_eq!(game.lines, 4);
+        assert!(game.board.grid.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn plays_a_full_game_through_the_normal_inputs() {
+        let mut game = Game::with_seed(8);
+        for _ in 0..100 {
+            for input in plan_moves(&game) {
+                game.apply(input);
+            }
+        }
+        assert!(!game.game_over);
+        assert!(game.lines > 20);
+        assert_eq!(game.pieces_placed(), 100);
+    }
+}
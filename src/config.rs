@@ -0,0 +1,858 @@
+use crate::error::{Error, Result};
+use crate::input::{ARR, DAS_DELAY, SOFT_DROP_INTERVAL};
+use crate::keys::KeyBindings;
+use crate::theme::{GhostStyle, Theme};
+use constris::{
+    BOARD_H, BOARD_W, HARD_DROP_LOCKOUT, LOCK_DELAY, MAX_BOARD_H, MAX_BOARD_W, MAX_LOCK_RESETS,
+    MAX_SPEED_LEVEL, MIN_BOARD_H, MIN_BOARD_W, PieceKind, REWIND_PENALTY, REWIND_PIECES,
+    RandomizerKind, SoftDropLock, piece_set,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+// 위험 표시를 켜는 기본 줄 수(맨 위에서부터)
+const DANGER_ROWS: usize = 4;
+
+// 프레임 사이에 입력을 기다리는 기본 최대 시간
+pub const TICK: Duration = Duration::from_millis(50);
+
+// 고정한 뒤 다음 피스가 나오기까지 기다리는 기본 시간(ARE)
+pub const ENTRY_DELAY: Duration = Duration::from_millis(100);
+
+// 밀리초로 적는 시간 항목들이 받는 범위(범위 밖이면 가까운 끝으로 맞춘다)
+const DELAY_MS: RangeInclusive<u64> = 0..=5000;
+const INTERVAL_MS: RangeInclusive<u64> = 1..=1000;
+// 횟수와 레벨로 적는 항목들이 받는 범위
+const LEVELS: RangeInclusive<u32> = 1..=99;
+const COUNTS: RangeInclusive<u32> = 0..=999;
+const LOCK_RESETS: RangeInclusive<u32> = 0..=100;
+// 되감을 때마다 그만큼 지난 보드를 기억해 둔다
+const REWIND_PIECE_COUNTS: RangeInclusive<usize> = 1..=50;
+
+/// 설정 파일이 없을 때 처음 한 번 만들어 두는 파일. 모든 항목이 기본값이다.
+const DEFAULT_FILE: &str = r#"# constris settings. Remove a line to go back to its default.
+show_ghost = true
+ghost_style = "grey"
+theme = "classic"
+grid = false
+confirm_quit = true
+narrow = false
+level_colors = true
+width = 10
+height = 20
+mirror_controls = false
+das_ms = 170
+arr_ms = 50
+soft_drop_ms = 30
+# soft_drop_multiplier = 20
+soft_drop_lock = "delay"
+lock_delay_ms = 500
+lock_resets = 15
+hard_drop_lockout_ms = 100
+sticky_drop_ms = 0
+entry_delay_ms = 100
+initial_actions = true
+# speed_cap_level = 15
+# instant_gravity_level = 20
+# hold_limit = 5
+rewinds = 0
+rewind_pieces = 5
+rewind_penalty = 1000
+randomizer = "bag"
+drop_trail = true
+wall_kicks = true
+same_garbage_hole = false
+danger_rows = 4
+flipped = false
+score_popups = true
+reduce_motion = false
+goal_bar = true
+tick_ms = 50
+
+# Keys for an action: one name or a list, e.g. rotate_cw = ["Up", "x"]
+[keys]
+"#;
+
+/// 설정 파일에 적힌 그대로의 모양. 빠진 항목은 기본값을 쓴다.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ConfigFile {
+    keys: HashMap<String, KeyNames>,
+    mirror_controls: Option<bool>,
+    spawn_rotation: HashMap<String, u8>,
+    show_ghost: Option<bool>,
+    ghost_style: Option<String>,
+    theme: Option<String>,
+    confirm_quit: Option<bool>,
+    narrow: Option<bool>,
+    level_colors: Option<bool>,
+    width: Option<usize>,
+    height: Option<usize>,
+    das_ms: Option<u64>,
+    arr_ms: Option<u64>,
+    soft_drop_ms: Option<u64>,
+    soft_drop_multiplier: Option<u32>,
+    soft_drop_lock: Option<String>,
+    lock_delay_ms: Option<u64>,
+    lock_resets: Option<u32>,
+    hard_drop_lockout_ms: Option<u64>,
+    sticky_drop_ms: Option<u64>,
+    entry_delay_ms: Option<u64>,
+    initial_actions: Option<bool>,
+    speed_cap_level: Option<u32>,
+    instant_gravity_level: Option<u32>,
+    hold_limit: Option<u32>,
+    rewinds: Option<u32>,
+    rewind_pieces: Option<usize>,
+    rewind_penalty: Option<u32>,
+    randomizer: Option<String>,
+    drop_trail: Option<bool>,
+    wall_kicks: Option<bool>,
+    same_garbage_hole: Option<bool>,
+    grid: Option<bool>,
+    danger: Option<bool>,
+    danger_rows: Option<usize>,
+    flipped: Option<bool>,
+    score_popups: Option<bool>,
+    reduce_motion: Option<bool>,
+    goal_bar: Option<bool>,
+    tick_ms: Option<u64>,
+}
+
+/// 키 하나(`"z"`) 또는 여러 개(`["Up", "z"]`)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// 실행 중에 쓰는 사용자 설정
+pub struct Config {
+    pub keys: KeyBindings,
+    // 좌우 이동 키가 서로 반대쪽으로 민다(바꾼 키에도 그대로 적용된다)
+    pub mirror_controls: bool,
+    pub show_ghost: bool,
+    // 고스트를 회색으로 칠할지 지금 피스 색을 어둡게 해서 칠할지
+    pub ghost_style: GhostStyle,
+    // 처음 고른 블록 테마(--theme이 있으면 그쪽)
+    pub theme: Theme,
+    // 플레이 중 종료 키를 누르면 Y로 한 번 더 확인받는다
+    pub confirm_quit: bool,
+    // 칸을 가로 2문자로 그려 좁은 터미널에서도 보드가 들어가게 한다
+    pub narrow: bool,
+    // 레벨이 오를 때마다 보드 테두리 색을 바꾼다
+    pub level_colors: bool,
+    // 처음 고른 보드 크기(--width, --height가 있으면 그쪽)
+    pub width: usize,
+    pub height: usize,
+    // 좌우 키를 누르고 있으면 자동 이동을 시작하기까지의 시간(DAS)과 그 뒤 한 칸씩 미는 간격(ARR)
+    pub das_delay: Duration,
+    pub arr: Duration,
+    // 소프트 드롭 키를 누르고 있을 때 한 칸씩 내리는 간격
+    pub soft_drop_interval: Duration,
+    // 있으면 소프트 드롭을 고정 간격 대신 지금 중력의 이 배수 빠르기로 내린다
+    pub soft_drop_multiplier: Option<u32>,
+    // 소프트 드롭으로 바닥에 닿은 피스를 바로 고정할지, 락 딜레이를 기다릴지
+    pub soft_drop_lock: SoftDropLock,
+    // 바닥에 닿은 피스가 고정되기까지 기다리는 시간과, 이동/회전으로 그 시간을 되돌릴 수 있는 횟수
+    pub lock_delay: Duration,
+    pub max_lock_resets: u32,
+    // 하드 드롭 직후 이 시간 동안은 하드 드롭 키를 무시한다(0이면 끈다)
+    pub hard_drop_lockout: Duration,
+    // 0이 아니면 하드 드롭을 이 시간 안에 두 번 눌러야 떨어뜨린다(첫 번째는 착지 자리만 보여 준다)
+    pub sticky_drop: Duration,
+    // 고정(과 줄 제거) 뒤 다음 피스가 나오기까지 기다리는 시간(0이면 바로 나온다)
+    pub entry_delay: Duration,
+    // 새 피스가 나올 때 누르고 있는 회전/홀드 키를 바로 넣는다(IRS/IHS)
+    pub initial_actions: bool,
+    // 있으면 이 레벨부터는 낙하 속도가 더 빨라지지 않는다
+    pub speed_cap: Option<u32>,
+    // 있으면 이 레벨부터 피스가 바닥에서 나온다(20G)
+    pub instant_gravity: Option<u32>,
+    // 있으면 한 판에 이 횟수만큼만 홀드할 수 있다
+    pub hold_limit: Option<u32>,
+    // 막혔을 때 끝내는 대신 `rewind_pieces`개 전으로 되감는 횟수(0이면 끈다)와 되감을 때 깎는 점수
+    pub rewinds: u32,
+    pub rewind_pieces: usize,
+    pub rewind_penalty: u32,
+    // 다음 피스를 고르는 방법(7-bag, 큰 가방, 완전 랜덤, TGM식)
+    pub randomizer: RandomizerKind,
+    // 하드 드롭한 피스가 지나온 자리를 잠깐 흐리게 그린다
+    pub drop_trail: bool,
+    // 끄면 회전할 때 킥 없이 제자리만 시도한다
+    pub wall_kicks: bool,
+    // 치즈 모드의 쓰레기 줄 구멍을 모두 같은 열에 낸다
+    pub same_garbage_hole: bool,
+    // 빈칸에 격자선을 그린다. 실행 중에 바꾸면 설정 파일에 저장한다.
+    pub grid: bool,
+    // 쌓인 블록이 맨 위에서 이 줄 수 안에 들어오면 테두리로 경고한다(0이면 끈다)
+    pub danger_rows: usize,
+    // 보드를 위아래로 뒤집어 그린다(바닥이 화면 위쪽에 온다)
+    pub flipped: bool,
+    // 줄을 지워 얻은 점수를 지운 줄 자리에 잠깐 띄운다
+    pub score_popups: bool,
+    // 줄 제거 애니메이션, 하드 드롭 번쩍임과 궤적, 점수 알림, 보드 가운데 알림을 모두 끈다
+    pub reduce_motion: bool,
+    // 마라톤과 스프린트에서 목표 줄 수까지의 진행 막대를 패널에 그린다
+    pub goal_bar: bool,
+    // 프레임 사이에 입력을 기다리는 최대 시간(짧을수록 자주 그린다)
+    pub tick: Duration,
+    // 종류별 스폰 회전 상태(`PieceKind::index` 순서)
+    pub spawn_rotations: Vec<u8>,
+    // 읽은 설정 파일. 실행 중에 바꾼 값도 여기에 적는다(설정 디렉터리가 없으면 없다).
+    pub path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            keys: KeyBindings::default(),
+            mirror_controls: false,
+            show_ghost: true,
+            ghost_style: GhostStyle::default(),
+            theme: Theme::default(),
+            confirm_quit: true,
+            narrow: false,
+            level_colors: true,
+            width: BOARD_W,
+            height: BOARD_H,
+            das_delay: DAS_DELAY,
+            arr: ARR,
+            soft_drop_interval: SOFT_DROP_INTERVAL,
+            soft_drop_multiplier: None,
+            soft_drop_lock: SoftDropLock::default(),
+            lock_delay: LOCK_DELAY,
+            max_lock_resets: MAX_LOCK_RESETS,
+            hard_drop_lockout: HARD_DROP_LOCKOUT,
+            sticky_drop: Duration::ZERO,
+            entry_delay: ENTRY_DELAY,
+            initial_actions: true,
+            speed_cap: None,
+            instant_gravity: None,
+            hold_limit: None,
+            rewinds: 0,
+            rewind_pieces: REWIND_PIECES,
+            rewind_penalty: REWIND_PENALTY,
+            randomizer: RandomizerKind::default(),
+            drop_trail: true,
+            wall_kicks: true,
+            same_garbage_hole: false,
+            grid: false,
+            danger_rows: DANGER_ROWS,
+            flipped: false,
+            score_popups: true,
+            reduce_motion: false,
+            goal_bar: true,
+            tick: TICK,
+            spawn_rotations: vec![0; piece_set().len()],
+            path: None,
+        }
+    }
+}
+
+impl Config {
+    /// `path`(없으면 설정 디렉터리의 `config.toml`)를 읽는다. 파일이 없으면 기본값으로
+    /// 채운 파일을 만들어 두고, 읽을 수 없거나 잘못된 항목은 경고와 함께 기본값으로 대신한다.
+    pub fn load(path: Option<PathBuf>) -> (Self, Vec<String>) {
+        let Some(path) = path.or_else(config_path) else {
+            return (Self::default(), Vec::new());
+        };
+        let (config, mut warnings) = match std::fs::read_to_string(&path) {
+            Ok(text) => Self::from_toml(&text),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let warnings = match write_default(&path) {
+                    Ok(()) => Vec::new(),
+                    Err(err) => vec![format!("could not write the default config: {err}")],
+                };
+                (Self::default(), warnings)
+            }
+            Err(err) => (Self::default(), vec![format!("{err}, using defaults")]),
+        };
+        for warning in &mut warnings {
+            *warning = format!("{}: {warning}", path.display());
+        }
+        let config = Self {
+            path: Some(path),
+            ..config
+        };
+        (config, warnings)
+    }
+
+    fn from_toml(text: &str) -> (Self, Vec<String>) {
+        let file: ConfigFile = match toml::from_str(text) {
+            Ok(file) => file,
+            Err(err) => {
+                return (
+                    Self::default(),
+                    vec![format!("{}, using defaults", err.message())],
+                );
+            }
+        };
+        let names = file
+            .keys
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys = match keys {
+                    KeyNames::One(key) => vec![key],
+                    KeyNames::Many(keys) => keys,
+                };
+                (action, keys)
+            })
+            .collect();
+        let (keys, mut warnings) = KeyBindings::from_names(&names);
+        let mut spawn_rotations = vec![0; piece_set().len()];
+        for (name, state) in &file.spawn_rotation {
+            let mut letters = name.chars().map(|c| c.to_ascii_uppercase());
+            match (
+                letters.next().and_then(PieceKind::from_letter),
+                letters.next(),
+            ) {
+                (Some(kind), None) if *state < 4 => spawn_rotations[kind.index()] = *state,
+                (Some(_), None) => {
+                    warnings.push(format!("spawn_rotation for {name} must be 0 to 3, using 0"))
+                }
+                _ => warnings.push(format!("unknown piece '{name}' in spawn_rotation")),
+            }
+        }
+        let ghost_style = match file.ghost_style.as_deref().map(str::parse) {
+            None => GhostStyle::default(),
+            Some(Ok(style)) => style,
+            Some(Err(err)) => {
+                warnings.push(format!("{err}, using grey"));
+                GhostStyle::default()
+            }
+        };
+        let soft_drop_lock = match file.soft_drop_lock.as_deref().map(str::parse) {
+            None => SoftDropLock::default(),
+            Some(Ok(lock)) => lock,
+            Some(Err(err)) => {
+                warnings.push(format!("{err}, using delay"));
+                SoftDropLock::default()
+            }
+        };
+        let theme = match file.theme.as_deref().map(str::parse) {
+            None => Theme::default(),
+            Some(Ok(theme)) => theme,
+            Some(Err(err)) => {
+                warnings.push(format!("{err}, using classic"));
+                Theme::default()
+            }
+        };
+        let randomizer = match file.randomizer.as_deref().map(str::parse) {
+            None => RandomizerKind::default(),
+            Some(Ok(randomizer)) => randomizer,
+            Some(Err(err)) => {
+                warnings.push(format!("{err}, using bag"));
+                RandomizerKind::default()
+            }
+        };
+        if file.soft_drop_multiplier == Some(0) {
+            warnings.push("soft_drop_multiplier must be at least 1, ignoring it".to_string());
+        }
+        let config = Self {
+            keys,
+            mirror_controls: file.mirror_controls.unwrap_or(false),
+            show_ghost: file.show_ghost.unwrap_or(true),
+            ghost_style,
+            theme,
+            confirm_quit: file.confirm_quit.unwrap_or(true),
+            narrow: file.narrow.unwrap_or(false),
+            level_colors: file.level_colors.unwrap_or(true),
+            width: clamped(
+                &mut warnings,
+                "width",
+                file.width,
+                MIN_BOARD_W..=MAX_BOARD_W,
+            )
+            .unwrap_or(BOARD_W),
+            height: clamped(
+                &mut warnings,
+                "height",
+                file.height,
+                MIN_BOARD_H..=MAX_BOARD_H,
+            )
+            .unwrap_or(BOARD_H),
+            das_delay: clamped(&mut warnings, "das_ms", file.das_ms, DELAY_MS)
+                .map_or(DAS_DELAY, Duration::from_millis),
+            arr: clamped(&mut warnings, "arr_ms", file.arr_ms, INTERVAL_MS)
+                .map_or(ARR, Duration::from_millis),
+            soft_drop_interval: clamped(
+                &mut warnings,
+                "soft_drop_ms",
+                file.soft_drop_ms,
+                INTERVAL_MS,
+            )
+            .map_or(SOFT_DROP_INTERVAL, Duration::from_millis),
+            soft_drop_multiplier: file.soft_drop_multiplier.filter(|&m| m > 0),
+            soft_drop_lock,
+            lock_delay: clamped(&mut warnings, "lock_delay_ms", file.lock_delay_ms, DELAY_MS)
+                .map_or(LOCK_DELAY, Duration::from_millis),
+            max_lock_resets: clamped(&mut warnings, "lock_resets", file.lock_resets, LOCK_RESETS)
+                .unwrap_or(MAX_LOCK_RESETS),
+            hard_drop_lockout: clamped(
+                &mut warnings,
+                "hard_drop_lockout_ms",
+                file.hard_drop_lockout_ms,
+                DELAY_MS,
+            )
+            .map_or(HARD_DROP_LOCKOUT, Duration::from_millis),
+            sticky_drop: clamped(
+                &mut warnings,
+                "sticky_drop_ms",
+                file.sticky_drop_ms,
+                DELAY_MS,
+            )
+            .map_or(Duration::ZERO, Duration::from_millis),
+            entry_delay: clamped(
+                &mut warnings,
+                "entry_delay_ms",
+                file.entry_delay_ms,
+                DELAY_MS,
+            )
+            .map_or(ENTRY_DELAY, Duration::from_millis),
+            initial_actions: file.initial_actions.unwrap_or(true),
+            // 가장 빨라지는 레벨보다 높게 막는 것은 막지 않는 것과 같다
+            speed_cap: clamped(
+                &mut warnings,
+                "speed_cap_level",
+                file.speed_cap_level,
+                1..=MAX_SPEED_LEVEL,
+            ),
+            instant_gravity: clamped(
+                &mut warnings,
+                "instant_gravity_level",
+                file.instant_gravity_level,
+                LEVELS,
+            ),
+            hold_limit: clamped(&mut warnings, "hold_limit", file.hold_limit, COUNTS),
+            rewinds: clamped(&mut warnings, "rewinds", file.rewinds, COUNTS).unwrap_or(0),
+            rewind_pieces: clamped(
+                &mut warnings,
+                "rewind_pieces",
+                file.rewind_pieces,
+                REWIND_PIECE_COUNTS,
+            )
+            .unwrap_or(REWIND_PIECES),
+            rewind_penalty: file.rewind_penalty.unwrap_or(REWIND_PENALTY),
+            randomizer,
+            drop_trail: file.drop_trail.unwrap_or(true),
+            wall_kicks: file.wall_kicks.unwrap_or(true),
+            same_garbage_hole: file.same_garbage_hole.unwrap_or(false),
+            grid: file.grid.unwrap_or(false),
+            danger_rows: match file.danger {
+                Some(false) => 0,
+                _ => clamped(
+                    &mut warnings,
+                    "danger_rows",
+                    file.danger_rows,
+                    0..=MAX_BOARD_H,
+                )
+                .unwrap_or(DANGER_ROWS),
+            },
+            flipped: file.flipped.unwrap_or(false),
+            score_popups: file.score_popups.unwrap_or(true),
+            reduce_motion: file.reduce_motion.unwrap_or(false),
+            goal_bar: file.goal_bar.unwrap_or(true),
+            tick: clamped(&mut warnings, "tick_ms", file.tick_ms, INTERVAL_MS)
+                .map_or(TICK, Duration::from_millis),
+            spawn_rotations,
+            path: None,
+        };
+        (config, warnings)
+    }
+
+    /// 실행 중에 바꾼 켜고 끄는 값(고스트, 격자선)을 설정 파일 `path`에 적어 둔다.
+    pub fn save_flag(path: Option<&Path>, key: &str, on: bool) -> Result<()> {
+        update_file(path, |text| set_value(text, key, on.into()))
+    }
+
+    /// 조작 화면에서 바꾼 동작의 키들을 설정 파일 `path`의 `[keys]` 표에 적어 둔다.
+    pub fn save_keys(path: Option<&Path>, action: &str, keys: &[String]) -> Result<()> {
+        update_file(path, |text| set_keys(text, action, keys))
+    }
+}
+
+/// 값이 `range` 밖이면 가까운 끝으로 맞추고 경고를 남긴다.
+fn clamped<T: PartialOrd + Copy + Display>(
+    warnings: &mut Vec<String>,
+    name: &str,
+    value: Option<T>,
+    range: RangeInclusive<T>,
+) -> Option<T> {
+    let value = value?;
+    let (min, max) = (*range.start(), *range.end());
+    if range.contains(&value) {
+        return Some(value);
+    }
+    let fixed = if value < min { min } else { max };
+    warnings.push(format!("{name} must be {min} to {max}, using {fixed}"));
+    Some(fixed)
+}
+
+/// 처음 실행할 때 `path`에 기본 설정 파일을 만든다.
+fn write_default(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    Ok(std::fs::write(path, DEFAULT_FILE)?)
+}
+
+/// 설정 파일을 읽어 `edit`로 고친 텍스트를 쓴다. 파일이 없으면 빈 파일에서 시작한다.
+fn update_file(path: Option<&Path>, edit: impl FnOnce(&str) -> Result<String>) -> Result<()> {
+    let path = path.ok_or(Error::Config("no config directory".to_string()))?;
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let text = edit(&text)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    Ok(std::fs::write(path, text)?)
+}
+
+/// 설정 파일 텍스트에서 최상위 값 하나만 바꾼다. 주석과 다른 항목, 모르는 항목도
+/// 그대로 두고, 읽을 수 없는 파일은 덮어쓰지 않는다.
+fn set_value(text: &str, key: &str, value: toml_edit::Value) -> Result<String> {
+    let mut doc: DocumentMut = text.parse()?;
+    put(doc.as_table_mut(), key, value);
+    Ok(doc.to_string())
+}
+
+/// `[keys]` 표에서 `action`의 키만 바꾼다. 키가 하나면 문자열, 여럿이면 배열로 쓴다.
+fn set_keys(text: &str, action: &str, keys: &[String]) -> Result<String> {
+    let mut doc: DocumentMut = text.parse()?;
+    let section = doc
+        .entry("keys")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or(Error::Config("'keys' is not a table".to_string()))?;
+    let value = match keys {
+        [key] => key.as_str().into(),
+        _ => toml_edit::Array::from_iter(keys).into(),
+    };
+    put(section, action, value);
+    Ok(doc.to_string())
+}
+
+/// `table`의 `key`에 `value`를 넣는다. 있던 값을 바꿀 때는 그 뒤에 붙은 주석을 남긴다.
+fn put(table: &mut dyn TableLike, key: &str, mut value: toml_edit::Value) {
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(old) => {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        }
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+/// 설정 파일 위치: `$XDG_CONFIG_HOME/constris/config.toml`
+/// (없으면 `~/.config`, Windows는 `%APPDATA%`).
+pub fn config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("constris").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::Action;
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn reads_single_and_list_key_bindings() {
+        let (config, warnings) = Config::from_toml(
+            r#"
+            [keys]
+            hard_drop = "Enter"
+            rotate_cw = ["w", "Up"]
+            "#,
+        );
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert!(config.confirm_quit);
+        assert_eq!(config.ghost_style, GhostStyle::Grey);
+        assert!(!config.narrow);
+        assert!(config.level_colors);
+        assert_eq!(config.soft_drop_interval, SOFT_DROP_INTERVAL);
+        assert_eq!(config.soft_drop_multiplier, None);
+        assert_eq!(config.soft_drop_lock, SoftDropLock::LockDelay);
+        assert_eq!(config.randomizer, RandomizerKind::Bag7);
+        assert_eq!(config.lock_delay, LOCK_DELAY);
+        assert_eq!(config.max_lock_resets, MAX_LOCK_RESETS);
+        assert_eq!(config.hard_drop_lockout, HARD_DROP_LOCKOUT);
+        assert_eq!(config.sticky_drop, Duration::ZERO);
+        assert_eq!(config.entry_delay, ENTRY_DELAY);
+        assert!(config.initial_actions);
+        assert_eq!(config.speed_cap, None);
+        assert_eq!(config.hold_limit, None);
+        assert_eq!(config.rewinds, 0);
+        assert_eq!(config.rewind_pieces, REWIND_PIECES);
+        assert!(config.drop_trail);
+        assert!(config.wall_kicks);
+        assert!(!config.same_garbage_hole);
+        assert!(!config.grid);
+        assert_eq!(config.danger_rows, DANGER_ROWS);
+        assert!(!config.flipped);
+        assert!(config.score_popups);
+        assert!(!config.reduce_motion);
+        assert!(config.goal_bar);
+        assert_eq!(config.tick, TICK);
+        assert_eq!(config.theme, Theme::Classic);
+        assert_eq!((config.width, config.height), (BOARD_W, BOARD_H));
+        assert_eq!((config.das_delay, config.arr), (DAS_DELAY, ARR));
+        assert_eq!(config.path, None);
+        assert!(!config.mirror_controls);
+        assert_eq!(
+            config.keys.action_for(KeyCode::Enter),
+            Some(Action::HardDrop)
+        );
+        assert_eq!(
+            config.keys.action_for(KeyCode::Char('w')),
+            Some(Action::RotateCw)
+        );
+    }
+
+    #[test]
+    fn reads_spawn_rotations_per_piece() {
+        let (config, warnings) = Config::from_toml(
+            r#"
+            [spawn_rotation]
+            T = 2
+            s = 1
+            Z = 5
+            X = 1
+            "#,
+        );
+
+        assert_eq!(config.spawn_rotations[PieceKind::T.index()], 2);
+        assert_eq!(config.spawn_rotations[PieceKind::S.index()], 1);
+        assert_eq!(config.spawn_rotations[PieceKind::Z.index()], 0);
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+    }
+
+    #[test]
+    fn reads_ghost_style_and_warns_on_unknown_names() {
+        let (config, warnings) = Config::from_toml("ghost_style = \"tint\"\n");
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.ghost_style, GhostStyle::Tint);
+        let (config, warnings) = Config::from_toml("ghost_style = \"blue\"\n");
+        assert_eq!(config.ghost_style, GhostStyle::Grey);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn reads_the_soft_drop_landing_behaviour() {
+        let (config, warnings) = Config::from_toml("soft_drop_lock = \"instant\"\n");
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.soft_drop_lock, SoftDropLock::InstantLock);
+        let (config, warnings) = Config::from_toml("soft_drop_lock = \"now\"\n");
+        assert_eq!(config.soft_drop_lock, SoftDropLock::LockDelay);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn reads_the_randomizer_and_warns_on_unknown_names() {
+        let (config, warnings) = Config::from_toml("randomizer = \"bag2\"\n");
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.randomizer, RandomizerKind::BagN(2));
+        let (config, warnings) = Config::from_toml("randomizer = \"shuffle\"\n");
+        assert_eq!(config.randomizer, RandomizerKind::Bag7);
+        assert!(warnings[0].contains("using bag"));
+    }
+
+    #[test]
+    fn partial_file_keeps_the_defaults_for_everything_else() {
+        let (config, warnings) = Config::from_toml(
+            r#"
+            theme = "contrast"
+            width = 12
+            das_ms = 120
+            mirror_controls = true
+            "#,
+        );
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert!(config.mirror_controls);
+        assert_eq!(config.theme, Theme::HighContrast);
+        assert_eq!((config.width, config.height), (12, BOARD_H));
+        assert_eq!(config.das_delay, Duration::from_millis(120));
+        assert_eq!(config.arr, ARR);
+        assert_eq!(config.lock_delay, LOCK_DELAY);
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped_with_a_warning() {
+        let (config, warnings) = Config::from_toml(
+            r#"
+            width = 2
+            height = 99
+            arr_ms = 0
+            lock_delay_ms = 60000
+            danger_rows = 50
+            tick_ms = 0
+            theme = "neon"
+            "#,
+        );
+        assert_eq!((config.width, config.height), (MIN_BOARD_W, MAX_BOARD_H));
+        assert_eq!(config.arr, Duration::from_millis(1));
+        assert_eq!(config.lock_delay, Duration::from_secs(5));
+        assert_eq!(config.danger_rows, MAX_BOARD_H);
+        assert_eq!(config.tick, Duration::from_millis(1));
+        assert_eq!(config.theme, Theme::Classic);
+        assert_eq!(warnings.len(), 7, "{warnings:?}");
+        assert!(warnings.contains(&"width must be 4 to 30, using 4".to_string()));
+        assert!(warnings.contains(&"lock_delay_ms must be 0 to 5000, using 5000".to_string()));
+
+        // 횟수와 레벨도 범위 밖이면 알려 준다(0레벨부터 20G가 되는 일은 없다)
+        let (config, warnings) = Config::from_toml(
+            r#"
+            instant_gravity_level = 0
+            lock_resets = 1000
+            hold_limit = 5000
+            rewinds = 5000
+            rewind_pieces = 0
+            speed_cap_level = 0
+            "#,
+        );
+        assert_eq!(config.instant_gravity, Some(1));
+        assert_eq!(config.max_lock_resets, 100);
+        assert_eq!((config.hold_limit, config.rewinds), (Some(999), 999));
+        assert_eq!(config.rewind_pieces, 1);
+        assert_eq!(config.speed_cap, Some(1));
+        assert_eq!(warnings.len(), 6, "{warnings:?}");
+        assert!(warnings.contains(&"instant_gravity_level must be 1 to 99, using 1".to_string()));
+        let (config, warnings) = Config::from_toml("hold_limit = 0\nrewind_pieces = 50\n");
+        assert_eq!((config.hold_limit, config.rewind_pieces), (Some(0), 50));
+        assert!(warnings.is_empty(), "{warnings:?}");
+        let (config, warnings) = Config::from_toml("speed_cap_level = 31\n");
+        assert_eq!(config.speed_cap, Some(MAX_SPEED_LEVEL));
+        assert_eq!(warnings, ["speed_cap_level must be 1 to 30, using 30"]);
+        let (config, warnings) = Config::from_toml("speed_cap_level = 15\n");
+        assert_eq!(config.speed_cap, Some(15));
+        assert!(warnings.is_empty(), "{warnings:?}");
+
+        // 값의 종류가 틀리면 파일 전체를 읽을 수 없어 기본값을 쓴다
+        let (config, warnings) = Config::from_toml("width = \"wide\"\n");
+        assert_eq!(config.width, BOARD_W);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn default_file_reads_back_as_the_defaults() {
+        let (config, warnings) = Config::from_toml(DEFAULT_FILE);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        let defaults = Config::default();
+        assert_eq!(config.theme, defaults.theme);
+        assert_eq!((config.width, config.height), (BOARD_W, BOARD_H));
+        assert_eq!((config.das_delay, config.arr), (DAS_DELAY, ARR));
+        assert_eq!(config.soft_drop_interval, defaults.soft_drop_interval);
+        assert_eq!(config.lock_delay, defaults.lock_delay);
+        assert_eq!(config.max_lock_resets, defaults.max_lock_resets);
+        assert_eq!(config.entry_delay, defaults.entry_delay);
+        assert_eq!(config.danger_rows, defaults.danger_rows);
+        assert_eq!(config.tick, defaults.tick);
+        assert_eq!(
+            config.keys.action_for(KeyCode::Left),
+            Some(Action::MoveLeft)
+        );
+    }
+
+    #[test]
+    fn first_load_writes_the_default_file() {
+        let dir = std::env::temp_dir().join(format!("constris-config-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        let (config, warnings) = Config::load(Some(path.clone()));
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.path.as_deref(), Some(path.as_path()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DEFAULT_FILE);
+        // 두 번째부터는 만든 파일을 읽는다
+        std::fs::write(&path, "width = 8\n").unwrap();
+        let (config, warnings) = Config::load(Some(path.clone()));
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.width, 8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn broken_file_falls_back_to_defaults() {
+        let (config, warnings) = Config::from_toml("[keys\nmove_left = ");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            config.keys.action_for(KeyCode::Left),
+            Some(Action::MoveLeft)
+        );
+    }
+
+    #[test]
+    fn saving_a_value_keeps_other_settings() {
+        let text = set_value(
+            "show_ghost = true\nconfirm_quit = false\n[keys]\nhard_drop = \"Enter\"\n",
+            "show_ghost",
+            false.into(),
+        )
+        .unwrap();
+        let (config, warnings) = Config::from_toml(&text);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert!(!config.show_ghost);
+        assert!(!config.confirm_quit);
+        assert_eq!(
+            config.keys.action_for(KeyCode::Enter),
+            Some(Action::HardDrop)
+        );
+        assert!(set_value("[broken", "show_ghost", true.into()).is_err());
+    }
+
+    #[test]
+    fn saving_keeps_comments_and_unknown_keys() {
+        let text = "# 내 설정\nshow_ghost = true # 고스트\nfuture_option = 3\n\n[keys]\n# 드롭\nhard_drop = \"Enter\"\n";
+        let text = set_value(text, "show_ghost", false.into()).unwrap();
+        let text = set_keys(&text, "hard_drop", &["Space".to_string()]).unwrap();
+        assert_eq!(
+            text,
+            "# 내 설정\nshow_ghost = false # 고스트\nfuture_option = 3\n\n[keys]\n# 드롭\nhard_drop = \"Space\"\n"
+        );
+        // 없던 최상위 값은 표 안이 아니라 맨 위에 들어간다
+        let text = set_value("[keys]\nhold = \"c\"\n", "grid", true.into()).unwrap();
+        assert!(Config::from_toml(&text).0.grid);
+    }
+
+    #[test]
+    fn saving_a_key_keeps_the_other_bindings() {
+        let text = set_keys(
+            "grid = true\n[keys]\nhard_drop = \"Enter\"\n",
+            "hold",
+            &["v".to_string()],
+        )
+        .unwrap();
+        let (config, warnings) = Config::from_toml(&text);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert!(config.grid);
+        assert_eq!(
+            config.keys.action_for(KeyCode::Enter),
+            Some(Action::HardDrop)
+        );
+        assert_eq!(
+            config.keys.action_for(KeyCode::Char('v')),
+            Some(Action::Hold)
+        );
+        // 표가 없던 파일에도 만들어 넣고, `keys`가 표가 아니면 건드리지 않는다
+        assert!(
+            set_keys("", "hold", &["v".to_string()])
+                .unwrap()
+                .contains("[keys]")
+        );
+        assert!(set_keys("keys = 1\n", "hold", &["v".to_string()]).is_err());
+    }
+}
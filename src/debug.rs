@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// FPS와 평균 프레임 시간을 이 기간 동안의 프레임으로 잰다
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// 디버그 오버레이용 프레임 통계: 최근 1초 동안 그린 프레임의 시각과 걸린 시간
+pub struct FrameStats {
+    frames: VecDeque<(Instant, Duration)>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// `at`에 시작한 프레임을 그리는 데 `took`만큼 걸렸다.
+    pub fn record(&mut self, at: Instant, took: Duration) {
+        self.frames.push_back((at, took));
+        while let Some(&(first, _)) = self.frames.front()
+            && at.saturating_duration_since(first) >= WINDOW
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    /// 최근 1초 동안 그린 프레임 수
+    pub fn fps(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 최근 프레임들을 그리는 데 걸린 평균 시간(ms)
+    pub fn avg_frame_ms(&self) -> f64 {
+        if self.frames.is_empty() {
+            return 0.0;
+        }
+        let total: Duration = self.frames.iter().map(|&(_, took)| took).sum();
+        total.as_secs_f64() * 1000.0 / self.frames.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_last_second() {
+        let t0 = Instant::now();
+        let mut stats = FrameStats::new();
+        for i in 0..30 {
+            let took = Duration::from_millis(if i % 2 == 0 { 1 } else { 3 });
+            stats.record(t0 + Duration::from_millis(i * 50), took);
+        }
+        // 마지막 프레임(1.45초)까지 1초 안에 든 0.5초 ~ 1.45초의 20프레임만 남는다
+        assert_eq!(stats.fps(), 20);
+        assert!((stats.avg_frame_ms() - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// 프론트엔드에서 생길 수 있는 오류. `main`이 메시지와 종료 코드로 바꾼다.
+#[derive(Debug)]
+pub enum Error {
+    /// 터미널이나 파일 입출력 실패
+    Io(io::Error),
+    /// 명령줄 인자가 잘못됐거나 --help/--version을 요청함(clap이 출력한다)
+    Usage(clap::Error),
+    /// 설정 파일을 해석하거나 쓸 수 없음
+    Config(String),
+    /// 리플레이 파일을 읽을 수 없음
+    Replay { path: PathBuf, msg: String },
+    /// 이어 할 저장 파일을 읽을 수 없음
+    Save { path: PathBuf, msg: String },
+    /// 피스 세트 파일을 읽을 수 없거나 정의가 잘못됨
+    Pieces { path: PathBuf, msg: String },
+    /// 네트워크 대전 상대와 연결할 수 없음
+    Net { addr: String, msg: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// 프로세스 종료 코드: 사용법 오류는 2(--help는 0), 나머지는 1
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(err) => err.exit_code(),
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Usage(err) => write!(f, "{err}"),
+            Error::Config(msg) => write!(f, "config: {msg}"),
+            Error::Replay { path, msg }
+            | Error::Save { path, msg }
+            | Error::Pieces { path, msg } => {
+                write!(f, "{}: {msg}", path.display())
+            }
+            Error::Net { addr, msg } => write!(f, "{addr}: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Config(err.message().to_string())
+    }
+}
+
+impl From<toml_edit::TomlError> for Error {
+    fn from(err: toml_edit::TomlError) -> Self {
+        Error::Config(err.message().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_and_reports_exit_codes() {
+        let err: Error = io::Error::other("disk gone").into();
+        assert_eq!(err.to_string(), "disk gone");
+        assert_eq!(err.exit_code(), 1);
+
+        let err: Error = toml::from_str::<toml::Table>("[broken").unwrap_err().into();
+        assert!(matches!(err, Error::Config(_)));
+        let usage = clap::Error::new(clap::error::ErrorKind::InvalidValue);
+        assert_eq!(Error::Usage(usage).exit_code(), 2);
+    }
+}
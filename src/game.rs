@@ -0,0 +1,2680 @@
+use crate::piece::srs_kicks;
+use crate::randomizer::PieceSource;
+use crate::{
+    BOARD_H, BOARD_W, Board, GameMode, GravityCurve, MAX_BOARD_H, MAX_BOARD_W, MIN_BOARD_H,
+    MIN_BOARD_W, Piece, PieceKind, RandomizerKind, ScoringMode, piece_set,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+// 바닥에 닿은 뒤 고정까지 기다리는 기본 시간과, 그 사이 이동/회전으로 타이머를 되돌릴 수 있는 기본 횟수
+pub const LOCK_DELAY: Duration = Duration::from_millis(500);
+pub const MAX_LOCK_RESETS: u32 = 15;
+
+// 인비저블 모드: 고정한 블록이 보이는 시간, 엿보기 시간과 감점
+const INVISIBLE_DELAY: Duration = Duration::from_millis(1000);
+const PEEK_TIME: Duration = Duration::from_millis(1000);
+pub const PEEK_PENALTY: u32 = 500;
+
+// 하드 드롭으로 고정한 칸이 하얗게 번쩍였다가 제 색으로 돌아오는 시간
+const LOCK_FLASH: Duration = Duration::from_millis(150);
+
+// 하드 드롭 직후 다음 하드 드롭을 무시하는 기본 시간(두 번 눌려 새 피스까지 떨어뜨리지 않게)
+pub const HARD_DROP_LOCKOUT: Duration = Duration::from_millis(100);
+
+// 하드 드롭 궤적이 흐려지며 사라지는 시간
+const DROP_TRAIL: Duration = Duration::from_millis(120);
+
+// 줄을 지워 얻은 점수가 보드 위에 떠 있다가 사라지는 시간
+const SCORE_POPUP: Duration = Duration::from_millis(800);
+
+// 젠/연습 모드에서 스폰이 막히면 비우는 위쪽 줄 수
+const ZEN_CLEAR_ROWS: usize = 4;
+
+// 연습 모드에서 되돌릴 수 있는 고정 수(오래된 것부터 버린다)
+pub const MAX_UNDO: usize = 50;
+
+// 막혔을 때 되감는 기본 피스 수와 되감을 때마다 깎는 기본 점수
+pub const REWIND_PIECES: usize = 5;
+pub const REWIND_PENALTY: u32 = 1000;
+
+// 쓰레기 줄 구멍용 난수원의 시드를 피스 순서용 시드와 다르게 만드는 값
+const GARBAGE_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// 미리 뽑아 두는 다음 피스 수(미리보기로 보여 줄 수 있는 최대 개수)
+pub const MAX_PREVIEW: usize = 5;
+
+// 고를 수 있는 시작 레벨 범위는 1..=MAX_START_LEVEL
+pub const MAX_START_LEVEL: u32 = 15;
+
+/// 3-코너 규칙으로 판정한 T-스핀 종류
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TSpin {
+    Mini,
+    Full,
+}
+
+/// 하드 드롭으로 피스가 지나온 자리(화면 효과용). 열과 줄 범위는 양 끝을 포함한다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DropTrail {
+    pub left: i32,
+    pub right: i32,
+    pub top: i32,
+    pub bottom: i32,
+}
+
+impl DropTrail {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.left..=self.right).contains(&x) && (self.top..=self.bottom).contains(&y)
+    }
+}
+
+/// 줄을 지워 얻은 점수를 지운 줄 근처에 잠깐 띄우는 알림(화면 효과용)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScorePopup {
+    /// 그 고정으로 얻은 점수(드롭 점수는 빼고 콤보, 퍼펙트 클리어 보너스는 더한다)
+    pub points: u32,
+    /// 지운 줄 중 가장 위 줄
+    pub row: usize,
+    pub cleared: u32,
+    pub t_spin: bool,
+    pub perfect: bool,
+}
+
+/// 게임이 끝난 이유
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndReason {
+    /// 스폰 자리가 막혀서 졌다
+    TopOut,
+    /// 모드의 목표 줄 수를 채웠다
+    GoalReached,
+    /// 제한 시간이 끝났다
+    TimeUp,
+}
+
+/// 소프트 드롭으로 바닥에 닿은 피스를 다시 내리려 할 때 하는 일
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SoftDropLock {
+    /// 락 딜레이가 끝날 때까지 기다린다(누르고 있는 동안 회전, 이동 가능)
+    #[default]
+    LockDelay,
+    /// 옛 게임처럼 바로 고정한다
+    InstantLock,
+}
+
+impl fmt::Display for SoftDropLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SoftDropLock::LockDelay => "delay",
+            SoftDropLock::InstantLock => "instant",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for SoftDropLock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "delay" | "lock-delay" => Ok(SoftDropLock::LockDelay),
+            "instant" | "instant-lock" => Ok(SoftDropLock::InstantLock),
+            _ => Err(format!(
+                "unknown soft drop lock '{s}' (expected delay or instant)"
+            )),
+        }
+    }
+}
+
+/// 프론트엔드가 효과음 등으로 알려 줄 만한 일. `Game::take_events`로 꺼낸다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// 피스를 고정했다
+    Locked,
+    /// 줄을 지웠다(지운 줄 수, 4면 테트리스)
+    Cleared(u32),
+    /// 줄을 지워 보드가 완전히 비었다(퍼펙트 클리어)
+    PerfectClear,
+    /// 레벨이 올랐다
+    LevelUp,
+    /// 게임이 끝났다(이유는 `end_reason`)
+    GameOver,
+    /// 대전: 받을 쓰레기 줄을 상쇄하고 남은 만큼 상대에게 보낸다(줄 수)
+    Attack(u32),
+    /// 새 피스가 나왔다(IRS/IHS는 이때 누르고 있는 키를 넣는다)
+    Spawned,
+    /// 막혔지만 끝나지 않고 몇 피스 전으로 되감았다
+    Rewound,
+}
+
+// ── 게임 상태 ───────────────────────────────────────────────────────
+
+/// 고정하기 직전의 상태(연습 모드 되돌리기와 막혔을 때 되감기에 쓴다).
+/// 되돌리면 이 피스를 다시 스폰 위치에 놓는다.
+#[derive(Clone)]
+struct Snapshot {
+    board: Board,
+    kind: PieceKind,
+    queue: VecDeque<PieceKind>,
+    held: Option<PieceKind>,
+    hold_used: bool,
+    holds_remaining: Option<u32>,
+    source: PieceSource,
+    script: VecDeque<PieceKind>,
+    score: u32,
+    lines: u32,
+    level: u32,
+    combo: i32,
+    back_to_back: bool,
+    piece_counts: Vec<u32>,
+    clear_counts: [u32; 4],
+    t_spins: u32,
+    max_combo: u32,
+}
+
+/// 복제한 게임은 원본과 같은 피스 순서로 이어진다(봇이 수를 미리 둬 볼 때 쓴다).
+#[derive(Clone)]
+pub struct Game {
+    pub mode: GameMode,
+    pub board: Board,
+    pub current: Piece,
+    // 다음에 나올 피스들. 앞에서 꺼내고 `source`에서 뽑아 뒤를 채워 늘 MAX_PREVIEW개다.
+    pub queue: VecDeque<PieceKind>,
+    // 홀드해 둔 피스. 한 피스를 고정하기 전까지 홀드는 한 번만 할 수 있다.
+    pub held: Option<PieceKind>,
+    pub(crate) hold_used: bool,
+    // 있으면 이 판에서 남은 홀드 횟수(0이 되면 홀드 키가 아무것도 하지 않는다). 없으면 무제한.
+    pub holds_remaining: Option<u32>,
+    // 막혔을 때 끝내는 대신 되감을 수 있는 남은 횟수(0이면 그대로 끝난다),
+    // 몇 피스 전으로 되감는지, 되감을 때마다 깎는 점수
+    pub rewinds_remaining: u32,
+    pub rewind_pieces: usize,
+    pub rewind_penalty: u32,
+    // 다음 피스를 고르는 랜덤 방법과 그 난수원
+    pub(crate) source: PieceSource,
+    // `from_script`로 정해 둔 남은 피스. 다 쓰기 전에는 가방에서 뽑지 않는다.
+    script: VecDeque<PieceKind>,
+    // 가방을 섞는 시드
+    pub seed: u64,
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    // 골라서 시작한 레벨. 줄 수로 계산한 레벨이 이보다 낮아지지 않는다.
+    pub start_level: u32,
+    // 레벨에 따른 낙하 속도 곡선
+    pub gravity: GravityCurve,
+    // 줄 제거 점수와 레벨 상승 규칙
+    pub scoring: ScoringMode,
+    // 연속으로 줄을 지운 횟수. 줄을 못 지우면 -1로 돌아간다.
+    pub combo: i32,
+    // 직전 줄 제거가 어려운 제거(테트리스/T-스핀)였는지. 이어서 또 하면 1.5배.
+    pub back_to_back: bool,
+    // 종류별로 고정한 피스 수(`PieceKind::index` 순서)
+    pub piece_counts: Vec<u32>,
+    // 한 번에 지운 줄 수별 횟수(싱글, 더블, 트리플, 테트리스), T-스핀 횟수, 가장 긴 콤보
+    pub clear_counts: [u32; 4],
+    pub t_spins: u32,
+    pub max_combo: u32,
+    // 소프트 드롭(소닉 드롭 포함)과 하드 드롭으로 내린 칸 수. 실제로 누른 만큼이라
+    // 되돌리기나 되감기로 줄지 않는다.
+    pub soft_drop_cells: u32,
+    pub hard_drop_cells: u32,
+    pub game_over: bool,
+    // 게임이 끝난 이유(game_over와 함께 채워진다)
+    pub end_reason: Option<EndReason>,
+    pub paused: bool,
+    // 인비저블 모드: 마지막으로 고정한 칸과 그 시각, 엿보기가 끝나는 시각
+    last_locked: Vec<(i32, i32)>,
+    last_locked_at: Option<Instant>,
+    peek_until: Option<Instant>,
+    // 마지막 고정이 하드 드롭이었으면 그 시각(고정한 칸을 번쩍이게 한다)
+    hard_dropped_at: Option<Instant>,
+    // 마지막 하드 드롭 시각과, 그 뒤로 하드 드롭을 받지 않는 시간(이동/회전은 막지 않는다)
+    last_hard_drop: Option<Instant>,
+    pub hard_drop_lockout: Duration,
+    // 끈끈한 드롭: 0이 아니면 하드 드롭을 이 시간 안에 두 번 눌러야 떨어뜨린다.
+    // 첫 번째로 누른 시각(이동/회전이나 고정하면 취소된다)
+    pub sticky_drop: Duration,
+    pub(crate) pending_drop: Option<Instant>,
+    // 마지막 하드 드롭으로 지나온 자리와 그 시각
+    drop_trail: Option<(DropTrail, Instant)>,
+    // 마지막 줄 제거로 얻은 점수와 그 시각
+    score_popup: Option<(ScorePopup, Instant)>,
+    // 줄 제거 애니메이션: 0이 아니면 찬 줄을 이 시간 동안 남겨 둔 뒤 지운다
+    pub clear_delay: Duration,
+    // 애니메이션 중인 찬 줄들과 시작 시각(비어 있으면 애니메이션 중이 아님)
+    pub clearing: Vec<usize>,
+    clearing_since: Option<Instant>,
+    // 스폰 대기(ARE): 0이 아니면 고정(과 줄 제거) 뒤 이 시간이 지나야 다음 피스가 나온다
+    pub entry_delay: Duration,
+    entry_since: Option<Instant>,
+    // 아직 프론트엔드가 꺼내 가지 않은 이벤트
+    events: Vec<GameEvent>,
+    // 고스트 피스를 보여 줄지(렌더링에서만 쓴다)
+    pub show_ghost: bool,
+    // 끄면 회전이 제자리에서 들어맞을 때만 성공한다(킥 후보를 시도하지 않는다)
+    pub wall_kicks: bool,
+    // 소프트 드롭이 바닥에 닿은 피스를 바로 고정하는지, 락 딜레이에 맡기는지
+    pub soft_drop_lock: SoftDropLock,
+    // 종류별 스폰 회전 상태(`PieceKind::index` 순서, 0이면 기본 모양)
+    pub spawn_rotations: Vec<u8>,
+    // 있으면 이 레벨부터는 더 빨라지지 않는다(표시하는 레벨은 계속 오른다)
+    pub speed_cap: Option<u32>,
+    // 있으면 이 레벨부터 20G: 피스가 바닥에서 나오고 중력 한 번에 바닥까지 떨어진다
+    pub instant_gravity: Option<u32>,
+    // 켜면 쓰레기 줄이 모두 직전 줄과 같은 열에 구멍을 낸다(깨끗한 치즈)
+    pub same_garbage_hole: bool,
+    last_garbage_hole: Option<usize>,
+    // 쓰레기 줄 구멍을 뽑는 난수원과 지금까지 뽑은 횟수. 같은 시드의 게임은 같은 구멍을 낸다.
+    garbage_rng: StdRng,
+    garbage_draws: u32,
+    // 대전: 상대가 보내 온, 다음에 줄을 못 지우고 고정하면 올라올 쓰레기 줄 수
+    pub incoming_garbage: u32,
+    // 플레이 시간: 첫 입력 때 시작해 게임이 끝나면 멈춘다(일시정지 시간은 뺀다)
+    pub start_time: Option<Instant>,
+    end_time: Option<Instant>,
+    paused_at: Option<Instant>,
+    // 락 딜레이 길이와 타이머를 되돌릴 수 있는 횟수(다 쓰면 바로 고정한다)
+    pub lock_delay: Duration,
+    pub max_lock_resets: u32,
+    // 락 딜레이: 바닥에 닿은 시각과 지금까지 타이머를 되돌린 횟수
+    lock_started: Option<Instant>,
+    lock_resets: u32,
+    // T-스핀 판정용: 마지막으로 성공한 동작이 회전이었는지, 그때 쓴 킥 오프셋
+    last_action_was_rotation: bool,
+    last_kick: (i32, i32),
+    // 연습 모드: 고정할 때마다 쌓는 되돌리기 기록(최대 `MAX_UNDO`개)과
+    // 지금 피스를 내리며 얻은 드롭 점수(되돌리면 이것도 뺀다)
+    undo_stack: Vec<Snapshot>,
+    drop_points: u32,
+    // 되감기용: 최근 `rewind_pieces`개 고정 직전 상태(앞이 가장 오래된 것)
+    rewind_history: VecDeque<Snapshot>,
+}
+
+/// 새 게임을 시작할 때 고르는 값(모드, 시작 레벨, 낙하 속도 곡선, 점수 규칙, 보드 크기,
+/// 미리 깔아 둘 쓰레기 줄 수, 피스 랜덤 방법)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameConfig {
+    pub mode: GameMode,
+    pub start_level: u32,
+    pub gravity: GravityCurve,
+    pub scoring: ScoringMode,
+    pub width: usize,
+    pub height: usize,
+    /// 핸디캡: 시작할 때 바닥에 깔아 두는 쓰레기 줄 수(보드 높이보다 한 줄 적게까지)
+    pub garbage: usize,
+    pub randomizer: RandomizerKind,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::default(),
+            start_level: 1,
+            gravity: GravityCurve::default(),
+            scoring: ScoringMode::default(),
+            width: BOARD_W,
+            height: BOARD_H,
+            garbage: 0,
+            randomizer: RandomizerKind::default(),
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    /// 임의의 시드로 새 게임을 시작한다.
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// 주어진 시드로 새 게임을 시작한다. 피스 순서가 시드로 결정된다.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_script(Vec::new(), seed)
+    }
+
+    /// 처음 나올 피스들을 정해 두고 시작한다(테스트에서 보드 상황을 만들 때 쓴다).
+    ///
+    /// `pieces[0]`이 `current`, 그다음 `MAX_PREVIEW`개가 `queue`가 되고, 나머지는
+    /// 피스가 스폰될 때마다 하나씩 `queue` 뒤에 붙는다. 다 쓰고 나면 `seed`로 섞은
+    /// 7-bag에서 처음부터 뽑으므로, 그 뒤 순서는 같은 시드의 `with_seed` 게임의 첫 피스부터와 같다.
+    pub fn from_script(pieces: Vec<PieceKind>, seed: u64) -> Self {
+        let mut source = PieceSource::new(seed, RandomizerKind::default());
+        let mut script = VecDeque::from(pieces);
+        let mut draw = || script.pop_front().unwrap_or_else(|| source.pull());
+        let kind = draw();
+        let queue = (0..MAX_PREVIEW).map(|_| draw()).collect();
+        Self {
+            mode: GameMode::default(),
+            board: Board::new(),
+            current: Piece::new(kind),
+            queue,
+            held: None,
+            hold_used: false,
+            holds_remaining: None,
+            rewinds_remaining: 0,
+            rewind_pieces: REWIND_PIECES,
+            rewind_penalty: REWIND_PENALTY,
+            source,
+            script,
+            seed,
+            score: 0,
+            lines: 0,
+            level: 1,
+            start_level: 1,
+            gravity: GravityCurve::default(),
+            scoring: ScoringMode::default(),
+            combo: -1,
+            back_to_back: false,
+            piece_counts: vec![0; piece_set().len()],
+            clear_counts: [0; 4],
+            t_spins: 0,
+            max_combo: 0,
+            soft_drop_cells: 0,
+            hard_drop_cells: 0,
+            game_over: false,
+            end_reason: None,
+            paused: false,
+            last_locked: Vec::new(),
+            last_locked_at: None,
+            peek_until: None,
+            hard_dropped_at: None,
+            last_hard_drop: None,
+            hard_drop_lockout: HARD_DROP_LOCKOUT,
+            sticky_drop: Duration::ZERO,
+            pending_drop: None,
+            drop_trail: None,
+            score_popup: None,
+            clear_delay: Duration::ZERO,
+            clearing: Vec::new(),
+            clearing_since: None,
+            entry_delay: Duration::ZERO,
+            entry_since: None,
+            events: Vec::new(),
+            show_ghost: true,
+            wall_kicks: true,
+            soft_drop_lock: SoftDropLock::default(),
+            spawn_rotations: vec![0; piece_set().len()],
+            speed_cap: None,
+            instant_gravity: None,
+            same_garbage_hole: false,
+            last_garbage_hole: None,
+            garbage_rng: StdRng::seed_from_u64(seed ^ GARBAGE_SEED_SALT),
+            garbage_draws: 0,
+            incoming_garbage: 0,
+            start_time: None,
+            end_time: None,
+            paused_at: None,
+            lock_delay: LOCK_DELAY,
+            max_lock_resets: MAX_LOCK_RESETS,
+            lock_started: None,
+            lock_resets: 0,
+            last_action_was_rotation: false,
+            last_kick: (0, 0),
+            undo_stack: Vec::new(),
+            drop_points: 0,
+            rewind_history: VecDeque::new(),
+        }
+    }
+
+    /// 메뉴나 명령줄에서 고른 설정으로 새 게임을 시작한다.
+    pub fn with_config(config: GameConfig) -> Self {
+        Self::with_seed_and_config(rand::random(), config)
+    }
+
+    /// 주어진 시드와 설정으로 새 게임을 시작한다.
+    pub fn with_seed_and_config(seed: u64, config: GameConfig) -> Self {
+        let mut game = Self::with_seed(seed);
+        game.configure(config);
+        game
+    }
+
+    /// 시작 전 게임에 설정을 적용한다.
+    fn configure(&mut self, config: GameConfig) {
+        self.mode = config.mode;
+        self.gravity = config.gravity;
+        self.scoring = config.scoring;
+        self.set_start_level(config.start_level);
+        // 기본 7-bag이 아니면 그 방법으로 처음부터 다시 뽑는다(크기를 바꾸며 다시 스폰한다)
+        if config.randomizer != self.source.kind {
+            self.source = PieceSource::new(self.seed, config.randomizer);
+            self.current = Piece::new(self.source.pull());
+            self.queue = (0..MAX_PREVIEW).map(|_| self.source.pull()).collect();
+        }
+        self.set_board_size(config.width, config.height);
+        // 구멍은 쓰레기 줄 난수원에서 뽑으므로 같은 시드면 같은 보드로 시작한다
+        let scale = self.mode.cell_scale() as usize;
+        for _ in 0..config.garbage.min(self.board.height() / scale - 1) {
+            let hole = self.next_garbage_hole();
+            self.raise_garbage(hole);
+        }
+    }
+
+    /// 빈 보드를 주어진 크기(허용 범위로 제한)로 바꾸고 현재 피스를 다시 스폰한다.
+    pub fn set_board_size(&mut self, width: usize, height: usize) {
+        // 가장 큰 피스가 들어갈 폭보다 좁게는 만들지 않는다
+        let widest = piece_set().max_size() * self.mode.cell_scale() as usize;
+        let width = width.clamp(MIN_BOARD_W.max(widest), MAX_BOARD_W);
+        let height = height.clamp(MIN_BOARD_H, MAX_BOARD_H);
+        // 빅 모드는 두 칸씩 움직이니 홀수 크기면 마지막 열과 맨 아래 줄에 닿지 못한다
+        let scale = self.mode.cell_scale() as usize;
+        let (width, height) = (width - width % scale, height - height % scale);
+        self.board = Board::with_size(width, height);
+        self.respawn_current();
+    }
+
+    /// 스폰 회전 상태나 20G를 바꾼 뒤 아직 움직이지 않은 현재 피스를 새 모양으로 다시 놓는다.
+    pub fn respawn_current(&mut self) {
+        self.current = self.spawn_piece(self.current.kind);
+        self.settle_instantly();
+    }
+
+    /// 20G 레벨인지
+    pub fn is_instant_gravity(&self) -> bool {
+        self.instant_gravity
+            .is_some_and(|level| self.level >= level)
+    }
+
+    /// 20G면 현재 피스를 고정하지 않고 바닥까지 내린다(점수 없음).
+    fn settle_instantly(&mut self) {
+        if self.is_instant_gravity() {
+            while self.try_move(0, 1) {}
+        }
+    }
+
+    fn spawn_next(&mut self) {
+        let kind = self.queue.pop_front().expect("queue is never empty");
+        let refill = self
+            .script
+            .pop_front()
+            .unwrap_or_else(|| self.source.pull());
+        self.queue.push_back(refill);
+        self.spawn(kind);
+    }
+
+    /// `kind`를 스폰 위치에 놓는다.
+    fn spawn(&mut self, kind: PieceKind) {
+        self.entry_since = None;
+        // 스폰 대기 중이나 되돌리기 전에 누른 하드 드롭은 이 피스를 떨어뜨리라는 뜻이 아니다
+        self.pending_drop = None;
+        self.drop_points = 0;
+        self.lock_started = None;
+        self.lock_resets = 0;
+        self.last_action_was_rotation = false;
+        self.current = self.spawn_piece(kind);
+        // 스폰 위치가 막혀 있으면 게임 오버. 젠/연습 모드는 위쪽을 비우고 계속하고,
+        // 되감기가 남았으면 몇 피스 전으로 돌아간다.
+        if !self.board.fits(&self.current.absolute_cells()) {
+            if matches!(self.mode, GameMode::Zen | GameMode::Practice) {
+                self.board.clear_top_rows(ZEN_CLEAR_ROWS);
+            } else {
+                if !self.rewind() {
+                    self.finish(EndReason::TopOut);
+                }
+                return;
+            }
+        }
+        self.settle_instantly();
+        self.events.push(GameEvent::Spawned);
+    }
+
+    /// 스폰 위치에 놓인 `kind`. 빅 모드는 폭이 절반인 보드에 스폰한 자리를 두 배로 키운다.
+    fn spawn_piece(&self, kind: PieceKind) -> Piece {
+        let scale = self.mode.cell_scale();
+        let width = self.board.width() / scale as usize;
+        Piece::spawn_rotated(kind, width, self.spawn_rotations[kind.index()]).scaled(scale)
+    }
+
+    /// 지금 피스를 홀드한다. 홀드가 비어 있으면 다음 피스를 꺼내고, 아니면 홀드한 피스와 바꾼다.
+    /// 다음 고정 전까지 다시 홀드할 수 없고, 남은 홀드 횟수가 0이거나 못 했으면 false.
+    pub fn hold(&mut self) -> bool {
+        if !self.can_hold() || self.game_over || self.is_clearing() || self.is_entering() {
+            return false;
+        }
+        if let Some(left) = &mut self.holds_remaining {
+            *left -= 1;
+        }
+        match self.held.replace(self.current.kind) {
+            None => self.spawn_next(),
+            Some(kind) => self.spawn(kind),
+        }
+        self.hold_used = true;
+        true
+    }
+
+    /// 연습 모드: 지금 피스를 세트 순서의 다음 종류로 바꿔 스폰 위치에 다시 놓는다.
+    pub fn cycle_piece(&mut self) -> bool {
+        if self.mode != GameMode::Practice
+            || self.game_over
+            || self.is_clearing()
+            || self.is_entering()
+        {
+            return false;
+        }
+        let next = (self.current.kind.index() + 1) % piece_set().len();
+        self.spawn(
+            piece_set()
+                .kinds()
+                .nth(next)
+                .expect("index is inside the set"),
+        );
+        true
+    }
+
+    /// 연습 모드: 마지막 고정을 되돌려 그 전의 보드, 점수, 피스 순서로 돌아간다.
+    /// 되돌릴 고정이 없으면 false.
+    pub fn undo(&mut self) -> bool {
+        if self.is_clearing() {
+            return false;
+        }
+        let Some(snapshot) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.restore(snapshot);
+        true
+    }
+
+    /// 막혔을 때: 되감기가 남았으면 `rewind_pieces`개 전 고정 직전으로 돌아가
+    /// `rewind_penalty`만큼 점수를 깎고 계속한다. 되감을 기록이 없으면 false.
+    fn rewind(&mut self) -> bool {
+        if self.rewinds_remaining == 0 {
+            return false;
+        }
+        let Some(snapshot) = self.rewind_history.pop_front() else {
+            return false;
+        };
+        // 남은 기록은 막히기 바로 전 상태들이라 다음 되감기에 쓰지 않는다
+        self.rewind_history.clear();
+        self.rewinds_remaining -= 1;
+        self.events.push(GameEvent::Rewound);
+        self.restore(snapshot);
+        self.score = self.score.saturating_sub(self.rewind_penalty);
+        true
+    }
+
+    /// `snapshot`의 보드, 점수, 피스 순서로 돌아가 그 피스를 다시 스폰한다.
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.board = snapshot.board;
+        self.queue = snapshot.queue;
+        self.held = snapshot.held;
+        self.hold_used = snapshot.hold_used;
+        self.holds_remaining = snapshot.holds_remaining;
+        self.source = snapshot.source;
+        self.script = snapshot.script;
+        self.score = snapshot.score;
+        self.lines = snapshot.lines;
+        self.level = snapshot.level;
+        self.combo = snapshot.combo;
+        self.back_to_back = snapshot.back_to_back;
+        self.piece_counts = snapshot.piece_counts;
+        self.clear_counts = snapshot.clear_counts;
+        self.t_spins = snapshot.t_spins;
+        self.max_combo = snapshot.max_combo;
+        self.last_locked.clear();
+        self.hard_dropped_at = None;
+        self.drop_trail = None;
+        self.spawn(snapshot.kind);
+    }
+
+    /// 되돌릴 수 있는 고정 수
+    pub fn undo_depth(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    fn push_undo(&mut self) {
+        if self.undo_stack.len() == MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+        let snapshot = self.snapshot();
+        self.undo_stack.push(snapshot);
+    }
+
+    fn push_rewind(&mut self) {
+        if self.rewind_history.len() >= self.rewind_pieces {
+            self.rewind_history.pop_front();
+        }
+        let snapshot = self.snapshot();
+        self.rewind_history.push_back(snapshot);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            board: self.board.clone(),
+            kind: self.current.kind,
+            queue: self.queue.clone(),
+            held: self.held,
+            hold_used: self.hold_used,
+            holds_remaining: self.holds_remaining,
+            source: self.source.clone(),
+            script: self.script.clone(),
+            score: self.score.saturating_sub(self.drop_points),
+            lines: self.lines,
+            level: self.level,
+            combo: self.combo,
+            back_to_back: self.back_to_back,
+            piece_counts: self.piece_counts.clone(),
+            clear_counts: self.clear_counts,
+            t_spins: self.t_spins,
+            max_combo: self.max_combo,
+        }
+    }
+
+    /// 지금 홀드할 수 있는지(사이드 패널에서 홀드 칸을 흐리게 보여 줄 때 쓴다)
+    pub fn can_hold(&self) -> bool {
+        !self.hold_used && self.holds_remaining != Some(0)
+    }
+
+    /// 게임을 끝내고 시계를 멈춘다.
+    fn finish(&mut self, reason: EndReason) {
+        self.game_over = true;
+        self.end_reason = Some(reason);
+        self.end_time = Some(Instant::now());
+        self.events.push(GameEvent::GameOver);
+    }
+
+    /// 지난번 이후 일어난 이벤트를 순서대로 꺼낸다.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 목표를 달성해 끝났는지
+    pub fn won(&self) -> bool {
+        self.end_reason == Some(EndReason::GoalReached)
+    }
+
+    /// 제한 시간 모드에서 남은 시간.
+    pub fn remaining(&self) -> Option<Duration> {
+        let limit = self.mode.time_limit()?;
+        Some(limit.saturating_sub(self.elapsed()))
+    }
+
+    /// 매 프레임 호출: 제한 시간이 다 되면 보드 상태와 상관없이 끝낸다.
+    pub fn update_timer(&mut self) {
+        if !self.game_over && self.remaining() == Some(Duration::ZERO) {
+            self.finish(EndReason::TimeUp);
+        }
+    }
+
+    /// 시작 레벨을 정한다(1..=MAX_START_LEVEL로 제한). 낙하 속도에 바로 반영된다.
+    pub fn set_start_level(&mut self, level: u32) {
+        self.start_level = level.clamp(1, MAX_START_LEVEL);
+        self.level = self.level.max(self.start_level);
+    }
+
+    /// 플레이어 입력이 들어왔음을 알린다. 처음 한 번 시계를 시작한다.
+    pub fn note_input(&mut self, now: Instant) {
+        if self.start_time.is_none() {
+            self.start_time = Some(now);
+        }
+    }
+
+    /// 첫 입력부터 지금(끝났으면 끝난 순간)까지의 플레이 시간.
+    pub fn elapsed(&self) -> Duration {
+        let Some(start) = self.start_time else {
+            return Duration::ZERO;
+        };
+        let until = self
+            .end_time
+            .or(self.paused_at)
+            .unwrap_or_else(Instant::now);
+        until.saturating_duration_since(start)
+    }
+
+    /// 쌓인 블록 한 칸이 지금 화면에 보여야 하는지. 인비저블 모드에서는 방금 놓은
+    /// 피스와 엿보기 중일 때만 보인다(충돌 판정은 언제나 실제 보드로 한다).
+    pub fn cell_visible(&self, x: i32, y: i32, now: Instant) -> bool {
+        if self.mode != GameMode::Invisible || self.game_over {
+            return true;
+        }
+        if self.peek_until.is_some_and(|until| now < until) {
+            return true;
+        }
+        self.last_locked.contains(&(x, y))
+            && self
+                .last_locked_at
+                .is_some_and(|at| now.saturating_duration_since(at) < INVISIBLE_DELAY)
+    }
+
+    /// 인비저블 모드에서 잠깐 쌓인 블록 전체를 본다(점수 감점).
+    pub fn peek(&mut self, now: Instant) {
+        if self.mode != GameMode::Invisible {
+            return;
+        }
+        self.score = self.score.saturating_sub(PEEK_PENALTY);
+        self.peek_until = Some(now + PEEK_TIME);
+    }
+
+    /// 다음 쓰레기 줄의 구멍 열. 게임 시드로 정해지는 난수원에서 뽑으므로 같은 시드의
+    /// 두 게임은 같은 순서로 구멍을 낸다. 같은 열로 정했으면 첫 줄만 뽑는다.
+    pub fn next_garbage_hole(&mut self) -> usize {
+        match self.last_garbage_hole {
+            Some(hole) if self.same_garbage_hole => hole,
+            _ => {
+                self.garbage_draws += 1;
+                self.garbage_rng.gen_range(0..self.garbage_columns())
+            }
+        }
+    }
+
+    /// 다음 피스를 고르는 랜덤 방법
+    pub fn randomizer(&self) -> RandomizerKind {
+        self.source.kind
+    }
+
+    /// 쓰레기 줄 구멍을 지금까지 뽑은 횟수. 시드와 이 값이면 난수원 상태를 다시 만들 수 있다.
+    pub fn garbage_draws(&self) -> u32 {
+        self.garbage_draws
+    }
+
+    /// 난수원을 시드에서 `draws`번 뽑은 상태로 맞춘다(저장한 게임이나 상대 게임과 맞출 때 쓴다).
+    pub fn sync_garbage(&mut self, draws: u32) {
+        self.garbage_rng = StdRng::seed_from_u64(self.seed ^ GARBAGE_SEED_SALT);
+        for _ in 0..draws {
+            self.garbage_rng.gen_range(0..self.garbage_columns());
+        }
+        self.garbage_draws = draws;
+    }
+
+    /// 쓰레기 줄 구멍을 낼 수 있는 열 수. 빅 모드는 피스 칸 하나 폭(두 열)을 한 열로 센다.
+    fn garbage_columns(&self) -> usize {
+        self.board.width() / self.mode.cell_scale() as usize
+    }
+
+    /// 구멍이 `hole_x`열(`garbage_columns` 기준)인 쓰레기 줄 하나를 보드에 밀어 올린다.
+    /// 빅 모드는 구멍 폭과 줄 높이가 두 칸이라 빅 피스로 메우고 두 줄씩 지울 수 있다.
+    /// 보드 밖으로 밀려난 블록이 있으면 true.
+    fn raise_garbage(&mut self, hole_x: usize) -> bool {
+        let scale = self.mode.cell_scale() as usize;
+        let mut overflow = false;
+        for _ in 0..scale {
+            overflow |= self.board.add_garbage_row(hole_x * scale, scale);
+        }
+        overflow
+    }
+
+    /// 구멍이 `hole_x`인 쓰레기 줄을 아래에서 밀어 올린다. 쌓인 블록이 보드 위로
+    /// 밀려나면 막힌 것으로 끝난다.
+    pub fn add_garbage(&mut self, hole_x: usize) {
+        if self.game_over {
+            return;
+        }
+        self.last_garbage_hole = Some(hole_x);
+        if self.raise_garbage(hole_x) {
+            if !self.rewind() {
+                self.finish(EndReason::TopOut);
+            }
+            return;
+        }
+        // 모든 블록이 같이 올라갔으니 겹친 피스도 그만큼 올리면 원래 관계가 된다
+        if !self.board.fits(&self.current.absolute_cells()) {
+            self.current.y -= self.current.scale;
+        }
+    }
+
+    /// 대전 상대가 보낸 쓰레기 줄을 받아 둔다. 다음에 줄을 지우면 그만큼 상쇄하고,
+    /// 못 지우고 고정하면 한꺼번에 올라온다.
+    pub fn receive_garbage(&mut self, rows: u32) {
+        self.incoming_garbage = self.incoming_garbage.saturating_add(rows);
+    }
+
+    /// 대전에서 한 번에 지운 줄 수로 보내는 쓰레기 줄 수(싱글은 보내지 않는다)
+    pub fn attack_lines(cleared: u32) -> u32 {
+        match cleared {
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            _ => 0,
+        }
+    }
+
+    /// 지금까지 고정한 피스 수
+    pub fn pieces_placed(&self) -> u32 {
+        self.piece_counts.iter().sum()
+    }
+
+    /// 플레이 시간 기준 초당 고정한 피스 수(시계가 돌기 전에는 0)
+    pub fn pieces_per_second(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs > 0.0 {
+            self.pieces_placed() as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// 이동 시도(성공 시 true). 빅 모드는 `dx`, `dy`의 두 배만큼 움직인다.
+    pub fn try_move(&mut self, dx: i32, dy: i32) -> bool {
+        let mut moved = self.current.clone();
+        moved.x += dx * moved.scale;
+        moved.y += dy * moved.scale;
+        if self.board.fits(&moved.absolute_cells()) {
+            self.current = moved;
+            self.last_action_was_rotation = false;
+            self.reset_lock_delay();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 시계 방향 회전. SRS 킥 표의 후보를 순서대로 시도한다.
+    pub fn try_rotate(&mut self) -> bool {
+        let rotated_cells = self.current.rotated_cw();
+        self.rotate_with_kicks(rotated_cells, (self.current.rotation + 1) % 4)
+    }
+
+    /// 반시계 방향 회전. 킥 처리는 시계 방향과 같다.
+    pub fn try_rotate_ccw(&mut self) -> bool {
+        let rotated_cells = self.current.rotated_ccw();
+        self.rotate_with_kicks(rotated_cells, (self.current.rotation + 3) % 4)
+    }
+
+    /// 180도 회전. 전용 킥 목록을 쓴다.
+    pub fn try_rotate_180(&mut self) -> bool {
+        let rotated_cells = self.current.rotated_180();
+        self.rotate_with_kicks(rotated_cells, (self.current.rotation + 2) % 4)
+    }
+
+    /// 회전된 셀을 `to` 상태로 놓아 본다. 킥 후보 중 처음 들어맞는 위치를 쓴다.
+    /// 킥을 껐으면 첫 후보인 제자리 `(0, 0)`만 시도한다.
+    fn rotate_with_kicks(&mut self, rotated_cells: Vec<(i32, i32)>, to: u8) -> bool {
+        if !self.current.kind.def().rotates() {
+            return true;
+        }
+        let from = self.current.rotation;
+        let mut kicks = srs_kicks(self.current.kind, from, to);
+        if !self.wall_kicks {
+            kicks.truncate(1);
+        }
+
+        // 빅 모드는 킥도 두 배로 옮긴다
+        let scale = self.current.scale;
+        for (kx, ky) in kicks {
+            let mut rotated = self.current.clone();
+            rotated.cells = rotated_cells.clone();
+            rotated.x += kx * scale;
+            rotated.y += ky * scale;
+            if self.board.fits(&rotated.absolute_cells()) {
+                rotated.rotation = to;
+                self.current = rotated;
+                self.last_action_was_rotation = true;
+                self.last_kick = (kx, ky);
+                self.reset_lock_delay();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 일시정지/재개. 재개할 때는 정지 동안 흐른 시간이 플레이 시간과 락 딜레이,
+    /// 줄 제거 애니메이션, 스폰 대기에 포함되지 않게 한다.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.set_paused_at(paused, Instant::now());
+    }
+
+    fn set_paused_at(&mut self, paused: bool, now: Instant) {
+        self.paused = paused;
+        if paused {
+            self.paused_at = Some(now);
+            return;
+        }
+        let Some(at) = self.paused_at.take() else {
+            return;
+        };
+        // 정지했던 만큼 시계들을 뒤로 미뤄 멈춘 곳부터 이어 간다. 락 딜레이를 새로
+        // 시작하지 않아야 일시정지로 고정을 끝없이 미룰 수 없다.
+        let paused_for = now.saturating_duration_since(at);
+        for since in [
+            &mut self.start_time,
+            &mut self.lock_started,
+            &mut self.clearing_since,
+            &mut self.entry_since,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *since += paused_for;
+        }
+    }
+
+    /// 현재 피스가 더 내려갈 수 없는지 확인
+    pub fn is_grounded(&self) -> bool {
+        let below: Vec<(i32, i32)> = self
+            .current
+            .absolute_cells()
+            .iter()
+            .map(|&(x, y)| (x, y + self.current.scale))
+            .collect();
+        !self.board.fits(&below)
+    }
+
+    /// 바닥에 닿은 상태에서 이동/회전에 성공하면 락 타이머를 다시 시작한다(횟수 제한).
+    fn reset_lock_delay(&mut self) {
+        if self.lock_started.is_some() && self.lock_resets < self.max_lock_resets {
+            self.lock_resets += 1;
+            self.lock_started = Some(Instant::now());
+        }
+    }
+
+    /// 매 프레임 호출: 바닥에 닿으면 락 타이머를 시작하고, 시간이 다 되거나
+    /// 리셋 횟수를 모두 쓰면 고정한다. 공중에 뜨면 타이머를 멈춘다. 고정했으면 true.
+    pub fn update_lock(&mut self, now: Instant) -> bool {
+        // 방금 고정한 피스가 아직 current로 남아 있으니 다시 고정하지 않는다
+        if self.is_clearing() || self.is_entering() {
+            return false;
+        }
+        if !self.is_grounded() {
+            self.lock_started = None;
+            return false;
+        }
+        match self.lock_started {
+            None => self.lock_started = Some(now),
+            Some(started) => {
+                if now.duration_since(started) >= self.lock_delay
+                    || self.lock_resets >= self.max_lock_resets
+                {
+                    self.lock_and_advance();
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// 소프트 드롭: 한 칸 내리고 1점. 내려갈 수 없으면 `SoftDropLock::InstantLock`일 때만
+    /// 바로 고정하고, 아니면 락 딜레이에 맡긴다.
+    pub fn soft_drop(&mut self) {
+        if self.try_move(0, 1) {
+            self.add_drop_points(1);
+            self.soft_drop_cells += 1;
+        } else if self.soft_drop_lock == SoftDropLock::InstantLock {
+            self.lock_and_advance();
+        }
+    }
+
+    /// 소닉 드롭: 바닥까지 내리되 고정은 락 딜레이에 맡긴다(칸당 1점)
+    pub fn sonic_drop(&mut self) {
+        while self.try_move(0, 1) {
+            self.add_drop_points(1);
+            self.soft_drop_cells += 1;
+        }
+    }
+
+    /// 드롭 점수. 연습 모드에서 고정을 되돌릴 때 같이 돌려놓으려고 피스마다 따로 센다.
+    fn add_drop_points(&mut self, points: u32) {
+        self.score += points;
+        self.drop_points += points;
+    }
+
+    /// 하드 드롭: 내려갈 수 있을 때까지 이동 후 고정(칸당 2점)
+    pub fn hard_drop(&mut self) {
+        let cells = self.current.absolute_cells();
+        let xs = cells.iter().map(|&(x, _)| x);
+        let (left, right) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+        let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let scale = self.current.scale;
+        let mut rows = 0;
+        while self.try_move(0, 1) {
+            rows += 1;
+        }
+        self.add_drop_points(2 * rows);
+        self.hard_drop_cells += rows;
+        self.lock_and_advance();
+        self.hard_dropped_at = self.last_locked_at;
+        self.last_hard_drop = self.last_locked_at;
+        // 궤적은 처음 자리의 윗줄부터 내려앉은 피스의 윗줄 바로 위까지
+        if rows > 0 {
+            let bottom = top + rows as i32 * scale - 1;
+            let trail = DropTrail {
+                left,
+                right,
+                top,
+                bottom,
+            };
+            self.drop_trail = Some((trail, Instant::now()));
+        }
+    }
+
+    /// 직전 하드 드롭 뒤로 아직 `hard_drop_lockout`이 지나지 않았는지.
+    /// 프론트엔드가 하드 드롭 키를 받기 전에 확인한다.
+    pub fn hard_drop_locked_out(&self, now: Instant) -> bool {
+        self.last_hard_drop
+            .is_some_and(|at| now.saturating_duration_since(at) < self.hard_drop_lockout)
+    }
+
+    /// 끈끈한 드롭을 켰으면 하드 드롭 키를 한 번 더 누르기를 기다린다. 이번에 떨어뜨려야 하면
+    /// true(꺼져 있거나, 기다리던 드롭을 `sticky_drop` 안에 다시 눌렀을 때)이고, 아니면 지금
+    /// 시각부터 확인을 기다린다. 프론트엔드가 하드 드롭 입력을 넣기 전에 부른다.
+    pub fn confirm_hard_drop(&mut self, now: Instant) -> bool {
+        if self.sticky_drop.is_zero() {
+            return true;
+        }
+        if self.drop_pending(now) {
+            self.pending_drop = None;
+            return true;
+        }
+        self.pending_drop = Some(now);
+        false
+    }
+
+    /// 한 번 누른 하드 드롭이 확인을 기다리는 중인지(착지 자리를 밝게 그린다)
+    pub fn drop_pending(&self, now: Instant) -> bool {
+        self.pending_drop
+            .is_some_and(|at| now.saturating_duration_since(at) < self.sticky_drop)
+    }
+
+    /// 방금 하드 드롭으로 지나온 자리와 흐려진 정도(0.0~1.0). 사라졌으면 None.
+    pub fn drop_trail(&self, now: Instant) -> Option<(DropTrail, f32)> {
+        let (trail, at) = self.drop_trail?;
+        let elapsed = now.saturating_duration_since(at);
+        (elapsed < DROP_TRAIL).then(|| (trail, elapsed.as_secs_f32() / DROP_TRAIL.as_secs_f32()))
+    }
+
+    /// 마지막 줄 제거로 얻은 점수와 사라지는 진행도(0.0~1.0). 떠 있지 않으면 None.
+    pub fn score_popup(&self, now: Instant) -> Option<(ScorePopup, f32)> {
+        let (popup, at) = self.score_popup?;
+        let elapsed = now.saturating_duration_since(at);
+        (elapsed < SCORE_POPUP).then(|| (popup, elapsed.as_secs_f32() / SCORE_POPUP.as_secs_f32()))
+    }
+
+    /// 방금 하드 드롭으로 고정한 칸들과 번쩍임 진행도(0.0~1.0). 번쩍이는 중이 아니면 None.
+    /// 줄을 지운 고정은 제거 애니메이션이 대신하므로 칸이 비어 있다.
+    pub fn lock_flash(&self, now: Instant) -> Option<(&[(i32, i32)], f32)> {
+        let elapsed = now.saturating_duration_since(self.hard_dropped_at?);
+        (elapsed < LOCK_FLASH).then(|| {
+            let progress = elapsed.as_secs_f32() / LOCK_FLASH.as_secs_f32();
+            (&self.last_locked[..], progress)
+        })
+    }
+
+    /// 현재 T피스가 놓인 자리를 3-코너 규칙으로 판정한다.
+    ///
+    /// 마지막 동작이 회전이고 중심 대각선 네 칸 중 셋 이상이 막혀 있으면 T-스핀이다.
+    /// 튀어나온 쪽(앞쪽) 두 코너가 모두 막혀 있거나 SRS 마지막 킥(1, 2칸)으로
+    /// 들어왔으면 정식, 아니면 미니로 본다.
+    pub fn t_spin(&self) -> Option<TSpin> {
+        // 직접 정의한 세트에는 T피스가 없다
+        let is_t = piece_set().is_standard() && self.current.kind == PieceKind::T;
+        if !is_t || !self.last_action_was_rotation {
+            return None;
+        }
+        // T는 3x3 그리드의 (1, 1)을 중심으로 회전한다. 빅 모드는 각 칸의 왼쪽 위 칸을 본다.
+        let s = self.current.scale;
+        let (cx, cy) = (self.current.x + s, self.current.y + s);
+        let blocked = |dx: i32, dy: i32| !self.board.is_free(cx + dx * s, cy + dy * s);
+        let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+        if corners.iter().filter(|&&(dx, dy)| blocked(dx, dy)).count() < 3 {
+            return None;
+        }
+
+        // 중심 상하좌우 중 비어 있는 쪽이 뒤, 그 반대가 앞(튀어나온 칸)
+        let back = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .find(|&(dx, dy)| !self.current.cells.contains(&(1 + dx, 1 + dy)))?;
+        let (fx, fy) = (-back.0, -back.1);
+        let front_blocked = if fx == 0 {
+            blocked(-1, fy) && blocked(1, fy)
+        } else {
+            blocked(fx, -1) && blocked(fx, 1)
+        };
+        if front_blocked || (self.last_kick.0.abs() == 1 && self.last_kick.1.abs() == 2) {
+            Some(TSpin::Full)
+        } else {
+            Some(TSpin::Mini)
+        }
+    }
+
+    /// 줄 수와 T-스핀 여부에 따른 기본 점수(레벨 곱하기 전).
+    fn clear_points(cleared: u32, t_spin: Option<TSpin>) -> u32 {
+        match (t_spin, cleared) {
+            (None, 1) => 100,
+            (None, 2) => 300,
+            (None, 3) => 500,
+            (None, 4) => 800,
+            (Some(TSpin::Mini), 0) => 100,
+            (Some(TSpin::Mini), 1) => 200,
+            (Some(TSpin::Mini), 2) => 400,
+            (Some(TSpin::Full), 0) => 400,
+            (Some(TSpin::Full), 1) => 800,
+            (Some(TSpin::Full), 2) => 1200,
+            (Some(TSpin::Full), 3) => 1600,
+            _ => 0,
+        }
+    }
+
+    /// 퍼펙트 클리어 보너스(레벨을 곱하기 전). 지운 줄 수가 많을수록 크다.
+    fn perfect_clear_points(cleared: u32) -> u32 {
+        match cleared {
+            1 => 800,
+            2 => 1200,
+            3 => 1800,
+            4 => 3500,
+            _ => 0,
+        }
+    }
+
+    /// 현재 피스를 고정하고 점수/레벨 갱신 후 다음 피스 스폰
+    pub fn lock_and_advance(&mut self) {
+        if self.mode == GameMode::Practice {
+            self.push_undo();
+        }
+        if self.rewinds_remaining > 0 && self.rewind_pieces > 0 {
+            self.push_rewind();
+        }
+        // 코너 판정은 고정 전에 해야 줄 제거로 보드가 바뀌기 전 상태를 본다
+        let t_spin = self.t_spin();
+        let score_before = self.score;
+        let cells = self.current.absolute_cells();
+        self.board.lock(&cells, self.current.kind);
+        self.piece_counts[self.current.kind.index()] += 1;
+        self.hold_used = false;
+        self.events.push(GameEvent::Locked);
+
+        let full_rows = self.board.full_rows();
+        let cleared = full_rows.len() as u32;
+        // 줄이 지워지면 좌표가 밀리므로 방금 놓은 칸을 따로 보여 주지 않는다
+        self.last_locked = if cleared == 0 { cells } else { Vec::new() };
+        self.last_locked_at = Some(Instant::now());
+        self.hard_dropped_at = None;
+        self.drop_trail = None;
+        self.pending_drop = None;
+        // NES 규칙에는 T-스핀, 백투백, 콤보, 퍼펙트 클리어 보너스가 없다
+        let guideline = self.scoring == ScoringMode::Guideline;
+        let mut points = if guideline {
+            Self::clear_points(cleared, t_spin) * self.level
+        } else {
+            ScoringMode::nes_line_points(cleared, self.level)
+        };
+        if cleared > 0 && guideline {
+            let difficult = cleared == 4 || t_spin.is_some();
+            if difficult && self.back_to_back {
+                points = points * 3 / 2;
+            }
+            self.back_to_back = difficult;
+        }
+        self.score += points;
+        if t_spin.is_some() {
+            self.t_spins += 1;
+        }
+        if cleared > 0 {
+            self.combo += 1;
+            if self.combo >= 1 && guideline {
+                self.score += 50 * self.combo as u32 * self.level;
+            }
+            self.max_combo = self.max_combo.max(self.combo as u32);
+            self.clear_counts[cleared as usize - 1] += 1;
+            self.lines += cleared;
+            self.events.push(GameEvent::Cleared(cleared));
+            // 애니메이션 중에는 찬 줄이 남아 있으니 나머지 줄이 모두 비었는지로 판단한다
+            if self.board.is_empty_without(&full_rows) {
+                if guideline {
+                    self.score += Self::perfect_clear_points(cleared) * self.level;
+                }
+                self.events.push(GameEvent::PerfectClear);
+            }
+            self.score_popup = Some((
+                ScorePopup {
+                    points: self.score - score_before,
+                    row: full_rows.iter().copied().min().unwrap_or(0),
+                    cleared,
+                    t_spin: t_spin.is_some(),
+                    perfect: self.events.last() == Some(&GameEvent::PerfectClear),
+                },
+                Instant::now(),
+            ));
+            let level = self.scoring.level(self.start_level, self.lines);
+            if level > self.level {
+                self.events.push(GameEvent::LevelUp);
+            }
+            self.level = level;
+
+            let attack = Self::attack_lines(cleared);
+            let cancelled = attack.min(self.incoming_garbage);
+            self.incoming_garbage -= cancelled;
+            if attack > cancelled {
+                self.events.push(GameEvent::Attack(attack - cancelled));
+            }
+        } else {
+            self.combo = -1;
+            // 받아 둔 쓰레기 줄은 다음 피스가 나오기 전에 올라온다. 올리다가 막혀서
+            // 끝났거나 되감았으면 남은 줄은 버리고, 되감은 피스를 그대로 둔다.
+            let rewinds = self.rewinds_remaining;
+            let stopped = |game: &Self| game.game_over || game.rewinds_remaining < rewinds;
+            for _ in 0..std::mem::take(&mut self.incoming_garbage) {
+                let hole = self.next_garbage_hole();
+                self.add_garbage(hole);
+                if stopped(self) {
+                    break;
+                }
+            }
+            if stopped(self) {
+                return;
+            }
+        }
+
+        // 애니메이션을 켰으면 점수만 먼저 올리고, 찬 줄은 finish_clear에서 지운다
+        if cleared > 0 && !self.clear_delay.is_zero() {
+            self.clearing = full_rows;
+            self.clearing_since = Some(Instant::now());
+            return;
+        }
+        self.board.clear_lines();
+        self.advance();
+    }
+
+    /// 줄 제거 애니메이션 중인지(그동안 다음 피스는 나오지 않는다)
+    pub fn is_clearing(&self) -> bool {
+        !self.clearing.is_empty()
+    }
+
+    /// 애니메이션 중인 찬 줄을 지우고 다음 피스를 꺼낸다. 애니메이션 중이 아니면 아무것도 안 한다.
+    pub fn finish_clear(&mut self) {
+        if !self.is_clearing() {
+            return;
+        }
+        self.clearing.clear();
+        self.clearing_since = None;
+        self.board.clear_lines();
+        self.advance();
+    }
+
+    /// 애니메이션 시간이 다 됐으면 줄을 지운다. 지웠으면 true(리플레이에 기록할 때가 됐다).
+    pub fn update_clear(&mut self, now: Instant) -> bool {
+        match self.clearing_since {
+            Some(since) if now.saturating_duration_since(since) >= self.clear_delay => {
+                self.finish_clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 애니메이션 진행도(0.0~1.0). 애니메이션 중이 아니면 None.
+    pub fn clear_progress(&self, now: Instant) -> Option<f32> {
+        let since = self.clearing_since?;
+        let elapsed = now.saturating_duration_since(since).as_secs_f32();
+        Some((elapsed / self.clear_delay.as_secs_f32()).min(1.0))
+    }
+
+    /// 목표를 채웠으면 끝내고, 아니면 다음 피스를 꺼낸다(스폰 대기가 있으면 그 뒤에).
+    fn advance(&mut self) {
+        if let Some(goal) = self.mode.line_goal()
+            && self.lines >= goal
+        {
+            self.finish(EndReason::GoalReached);
+            return;
+        }
+        if !self.entry_delay.is_zero() {
+            self.entry_since = Some(Instant::now());
+            return;
+        }
+        self.spawn_next();
+    }
+
+    /// 다음 피스가 나오기를 기다리는 중인지(그동안 current는 방금 고정한 피스다)
+    pub fn is_entering(&self) -> bool {
+        self.entry_since.is_some()
+    }
+
+    /// 스폰 대기를 끝내고 다음 피스를 꺼낸다. 기다리는 중이 아니면 아무것도 안 한다.
+    pub fn finish_entry(&mut self) {
+        if self.is_entering() {
+            self.spawn_next();
+        }
+    }
+
+    /// 스폰 대기 시간이 다 됐으면 다음 피스를 꺼낸다. 꺼냈으면 true(리플레이에 기록할 때가 됐다).
+    pub fn update_entry(&mut self, now: Instant) -> bool {
+        match self.entry_since {
+            Some(since) if now.saturating_duration_since(since) >= self.entry_delay => {
+                self.finish_entry();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 지금 레벨과 낙하 속도 곡선에 따른 낙하 간격(ms). 속도 상한 레벨을 넘으면 그 레벨 빠르기.
+    pub fn drop_interval_ms(&self) -> u64 {
+        let level = self.speed_cap.map_or(self.level, |cap| self.level.min(cap));
+        self.gravity.interval_ms(level)
+    }
+
+    /// 중력으로 한 칸(20G면 바닥까지) 내린다.
+    pub(crate) fn gravity_step(&mut self) {
+        if self.is_instant_gravity() {
+            self.settle_instantly();
+        } else {
+            self.try_move(0, 1);
+        }
+    }
+
+    /// 고스트 피스(하드 드롭 그림자) 위치 계산.
+    pub fn ghost_cells(&self) -> Vec<(i32, i32)> {
+        let mut ghost = self.current.clone();
+        loop {
+            let mut next = ghost.clone();
+            next.y += ghost.scale;
+            if self.board.fits(&next.absolute_cells()) {
+                ghost = next;
+            } else {
+                break;
+            }
+        }
+        ghost.absolute_cells()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ALL_PIECES, BOARD_H, BOARD_W, Block, Input};
+
+    #[test]
+    fn bag_yields_each_piece_once_per_seven() {
+        // 처음 두 피스를 포함해 7개마다 모든 종류가 한 번씩 나오는지 확인
+        let mut game = Game::new();
+        for _ in 0..3 {
+            let mut seen = vec![game.current.kind];
+            for _ in 0..6 {
+                game.spawn_next();
+                seen.push(game.current.kind);
+            }
+            for kind in ALL_PIECES {
+                assert_eq!(seen.iter().filter(|&&k| k == kind).count(), 1);
+            }
+            game.spawn_next();
+        }
+    }
+
+    #[test]
+    fn spawn_takes_the_front_of_the_queue_and_refills_behind() {
+        let mut game = Game::with_seed(5);
+        let upcoming: Vec<PieceKind> = game.queue.iter().copied().collect();
+        game.spawn_next();
+        assert_eq!(game.current.kind, upcoming[0]);
+        assert_eq!(game.queue.len(), MAX_PREVIEW);
+        assert!(game.queue.iter().take(MAX_PREVIEW - 1).eq(&upcoming[1..]));
+    }
+
+    #[test]
+    fn same_seed_gives_same_pieces() {
+        let mut a = Game::with_seed(42);
+        let mut b = Game::with_seed(42);
+        for _ in 0..10 {
+            assert_eq!(a.current.kind, b.current.kind);
+            a.spawn_next();
+            b.spawn_next();
+        }
+    }
+
+    #[test]
+    fn drops_award_points_per_cell() {
+        // 빈 보드에서 O피스는 y=-1에서 바닥(y=18)까지 19칸 떨어진다
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::O);
+
+        game.soft_drop();
+        assert_eq!(game.score, 1);
+
+        game.hard_drop();
+        assert_eq!(game.score, 1 + 2 * 18);
+    }
+
+    #[test]
+    fn soft_drop_on_the_floor_locks_only_when_asked_to() {
+        let mut game = Game::with_seed(3);
+        game.sonic_drop();
+        let kind = game.current.kind;
+        game.soft_drop();
+        assert_eq!((game.current.kind, game.pieces_placed()), (kind, 0));
+        assert!(game.is_grounded());
+
+        game.soft_drop_lock = SoftDropLock::InstantLock;
+        game.soft_drop();
+        assert_eq!(game.pieces_placed(), 1);
+    }
+
+    #[test]
+    fn soft_drop_lock_names_round_trip() {
+        for lock in [SoftDropLock::LockDelay, SoftDropLock::InstantLock] {
+            assert_eq!(lock.to_string().parse(), Ok(lock));
+        }
+        assert_eq!("Instant".parse(), Ok(SoftDropLock::InstantLock));
+        assert_eq!("lock-delay".parse(), Ok(SoftDropLock::LockDelay));
+        assert!("sometimes".parse::<SoftDropLock>().is_err());
+    }
+
+    #[test]
+    fn moves_stop_at_walls() {
+        // I피스를 왼쪽 끝까지 밀면 x=0에서 멈춘다
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        assert_eq!(game.current.x, 0);
+        assert!(!game.try_move(-1, 0));
+    }
+
+    #[test]
+    fn rotates_with_wall_kick() {
+        // 세로 I피스를 오른쪽 벽에 붙이고 회전하면 킥으로 안쪽으로 밀려난다
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::I);
+        game.current.y = 5;
+        assert!(game.try_rotate());
+        while game.try_move(1, 0) {}
+        assert!(game.try_rotate());
+        assert!(
+            game.current
+                .absolute_cells()
+                .iter()
+                .all(|&(x, _)| (0..BOARD_W as i32).contains(&x))
+        );
+    }
+
+    #[test]
+    fn grounded_piece_waits_for_lock_delay() {
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::O);
+        while game.try_move(0, 1) {}
+
+        let landed = Instant::now();
+        game.update_lock(landed);
+        game.update_lock(landed + LOCK_DELAY / 2);
+        assert_eq!(game.current.y, 18, "still sliding before the delay");
+
+        // 바닥에서 옆으로 밀면 타이머가 다시 시작된다
+        assert!(game.try_move(-1, 0));
+        game.update_lock(landed + LOCK_DELAY);
+        assert!(game.board.grid[BOARD_H - 1].iter().all(|c| c.is_none()));
+
+        game.update_lock(Instant::now() + LOCK_DELAY);
+        assert!(game.board.grid[BOARD_H - 1].iter().any(|c| c.is_some()));
+    }
+
+    #[test]
+    fn pausing_does_not_restart_the_lock_delay() {
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::O);
+        while game.try_move(0, 1) {}
+
+        let landed = Instant::now();
+        game.update_lock(landed);
+        game.set_paused_at(true, landed + LOCK_DELAY / 2);
+        let resumed = landed + Duration::from_secs(10);
+        game.set_paused_at(false, resumed);
+
+        // 멈추기 전에 남았던 절반만 더 기다리면 고정된다
+        assert!(!game.update_lock(resumed + LOCK_DELAY / 2 - Duration::from_millis(1)));
+        assert!(game.update_lock(resumed + LOCK_DELAY / 2));
+    }
+
+    #[test]
+    fn lock_resets_are_capped() {
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::O);
+        while game.try_move(0, 1) {}
+        game.update_lock(Instant::now());
+
+        for i in 0..MAX_LOCK_RESETS {
+            let dx = if i % 2 == 0 { -1 } else { 1 };
+            assert!(game.try_move(dx, 0));
+        }
+        // 리셋을 모두 쓰면 타이머와 상관없이 바로 고정
+        game.update_lock(Instant::now());
+        assert!(game.board.grid[BOARD_H - 1].iter().any(|c| c.is_some()));
+    }
+
+    #[test]
+    fn entry_delay_holds_the_next_piece_back() {
+        let mut game = Game::with_seed(5);
+        game.entry_delay = Duration::from_millis(100);
+        let next = game.queue[0];
+        game.hard_drop();
+        assert!(game.is_entering());
+        assert_eq!(game.queue[0], next);
+        // 기다리는 동안에는 락 딜레이도 홀드도 방금 고정한 피스에 걸리지 않는다
+        assert!(!game.update_lock(Instant::now() + Duration::from_secs(1)));
+        assert!(!game.hold());
+        assert!(!game.update_entry(Instant::now()));
+
+        assert!(game.update_entry(Instant::now() + Duration::from_millis(100)));
+        assert!(!game.is_entering());
+        assert_eq!(game.current.kind, next);
+
+        // 대기 중에 들어온 입력은 다음 피스를 먼저 꺼내고 적용된다
+        game.hard_drop();
+        let next = game.queue[0];
+        game.apply(Input::Left);
+        assert_eq!(game.current.kind, next);
+        assert!(!game.is_entering());
+    }
+
+    #[test]
+    fn pausing_during_the_entry_delay_keeps_the_rest_of_the_wait() {
+        let mut game = Game::with_seed(5);
+        game.entry_delay = Duration::from_millis(100);
+        game.hard_drop();
+        let since = game.entry_since.expect("entering");
+        game.set_paused_at(true, since + Duration::from_millis(40));
+        let resumed = since + Duration::from_secs(10);
+        game.set_paused_at(false, resumed);
+
+        assert!(!game.update_entry(resumed + Duration::from_millis(59)));
+        assert!(game.is_entering());
+        assert!(game.update_entry(resumed + Duration::from_millis(60)));
+    }
+
+    #[test]
+    fn pausing_during_a_line_clear_continues_the_animation() {
+        let mut game = Game::new();
+        game.clear_delay = Duration::from_millis(200);
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        let since = game.clearing_since.expect("clearing");
+        game.set_paused_at(true, since + Duration::from_millis(150));
+        let resumed = since + Duration::from_secs(10);
+        game.set_paused_at(false, resumed);
+
+        assert_eq!(game.clear_progress(resumed), Some(0.75));
+        assert!(!game.update_clear(resumed + Duration::from_millis(49)));
+        assert!(game.update_clear(resumed + Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn hard_drop_is_locked_out_briefly_after_a_hard_drop() {
+        let mut game = Game::with_seed(1);
+        assert!(!game.hard_drop_locked_out(Instant::now()));
+        game.apply(Input::HardDrop);
+        let dropped = Instant::now();
+        assert!(game.hard_drop_locked_out(dropped));
+        assert!(!game.hard_drop_locked_out(dropped + HARD_DROP_LOCKOUT));
+
+        // 이동과 회전은 막지 않는다
+        assert!(game.try_move(-1, 0));
+        assert!(game.try_rotate());
+
+        game.hard_drop_lockout = Duration::ZERO;
+        assert!(!game.hard_drop_locked_out(dropped));
+    }
+
+    #[test]
+    fn sticky_drop_needs_a_second_press_and_movement_cancels_it() {
+        let mut game = Game::with_seed(1);
+        let start = Instant::now();
+        assert!(game.confirm_hard_drop(start));
+
+        game.sticky_drop = Duration::from_millis(300);
+        assert!(!game.confirm_hard_drop(start));
+        assert!(game.drop_pending(start));
+        assert!(game.confirm_hard_drop(start + Duration::from_millis(200)));
+        assert!(!game.drop_pending(start + Duration::from_millis(200)));
+
+        // 너무 늦게 누르면 처음부터 다시 기다린다
+        assert!(!game.confirm_hard_drop(start));
+        assert!(!game.drop_pending(start + Duration::from_millis(300)));
+        assert!(!game.confirm_hard_drop(start + Duration::from_millis(300)));
+
+        // 이동이나 회전은 기다리던 드롭을 취소하고, 중력은 취소하지 않는다
+        game.apply(Input::Gravity);
+        assert!(game.drop_pending(start + Duration::from_millis(300)));
+        game.apply(Input::Left);
+        assert!(!game.drop_pending(start + Duration::from_millis(300)));
+        assert!(!game.confirm_hard_drop(start));
+        game.apply(Input::RotateCw);
+        assert!(!game.confirm_hard_drop(start));
+        // 고정해도 취소된다
+        game.apply(Input::HardDrop);
+        assert!(!game.drop_pending(start));
+    }
+
+    #[test]
+    fn sticky_drop_pressed_during_entry_delay_waits_again_for_the_new_piece() {
+        let mut game = Game::with_seed(1);
+        game.sticky_drop = Duration::from_millis(300);
+        game.entry_delay = Duration::from_millis(100);
+        game.hard_drop();
+        assert!(game.is_entering());
+        let now = Instant::now();
+        assert!(!game.confirm_hard_drop(now));
+        game.finish_entry();
+        assert!(!game.drop_pending(now));
+        assert!(!game.confirm_hard_drop(now));
+    }
+
+    #[test]
+    fn undo_and_cycle_cancel_a_pending_sticky_drop() {
+        let mut game = Game::with_seed(1);
+        game.mode = GameMode::Practice;
+        game.sticky_drop = Duration::from_millis(300);
+        game.hard_drop();
+        let now = Instant::now();
+        assert!(!game.confirm_hard_drop(now));
+        assert!(game.undo());
+        assert!(!game.drop_pending(now));
+
+        assert!(!game.confirm_hard_drop(now));
+        assert!(game.cycle_piece());
+        assert!(!game.drop_pending(now));
+    }
+
+    #[test]
+    fn lock_settings_come_from_the_game() {
+        let mut game = Game::new();
+        game.lock_delay = Duration::from_millis(20);
+        game.max_lock_resets = 3;
+        game.current = Piece::new(PieceKind::O);
+        while game.try_move(0, 1) {}
+        let landed = Instant::now();
+        game.update_lock(landed);
+
+        // 짧은 딜레이 안에서 계속 밀어도 세 번째 리셋에서 고정된다
+        for dx in [-1, 1] {
+            assert!(game.try_move(dx, 0));
+            assert!(!game.update_lock(landed));
+        }
+        assert!(game.try_move(-1, 0));
+        assert!(game.update_lock(landed));
+        assert!(game.board.grid[BOARD_H - 1].iter().any(|c| c.is_some()));
+
+        // 다음 피스는 기본값이 아닌 짧은 딜레이가 지나면 고정된다
+        while game.try_move(0, 1) {}
+        let landed = Instant::now();
+        game.update_lock(landed);
+        assert!(!game.update_lock(landed + Duration::from_millis(10)));
+        assert!(game.update_lock(landed + Duration::from_millis(20)));
+    }
+
+    /// 바닥 두 줄에 T-스핀 더블 자리를 만든다(왼쪽 끝, 위는 0번 열이 덮음).
+    fn t_spin_double_board() -> Board {
+        let mut board = Board::new();
+        for x in 0..BOARD_W {
+            if x != 1 {
+                board.grid[BOARD_H - 1][x] = Some(Block::Piece(PieceKind::O));
+            }
+            if x > 2 {
+                board.grid[BOARD_H - 2][x] = Some(Block::Piece(PieceKind::O));
+            }
+        }
+        board.grid[BOARD_H - 3][0] = Some(Block::Piece(PieceKind::O));
+        board
+    }
+
+    #[test]
+    fn scores_t_spin_double() {
+        let mut game = Game::new();
+        game.board = t_spin_double_board();
+        // 오른쪽을 향한 T를 1번 열로 내린 뒤 회전해 슬롯에 끼운다
+        game.current = Piece::new(PieceKind::T);
+        game.current.cells = game.current.rotated_cw();
+        game.current.rotation = 1;
+        game.current.x = 0;
+        while game.try_move(0, 1) {}
+        assert!(game.try_rotate());
+        assert_eq!(game.t_spin(), Some(TSpin::Full));
+
+        game.hard_drop();
+        assert_eq!(game.lines, 2);
+        assert_eq!(game.score, 1200);
+    }
+
+    #[test]
+    fn moving_after_rotation_is_not_a_t_spin() {
+        let mut game = Game::new();
+        game.board = t_spin_double_board();
+        game.current = Piece::new(PieceKind::T);
+        game.current.cells = game.current.rotated_cw();
+        game.current.rotation = 1;
+        game.current.x = 0;
+        game.current.y = 10;
+        assert!(game.try_rotate());
+        assert!(game.try_move(0, 1));
+        assert_eq!(game.t_spin(), None);
+    }
+
+    #[test]
+    fn consecutive_clears_build_a_combo() {
+        // 아래 세 줄의 왼쪽 네 칸만 비워 두고 I피스로 한 줄씩 지운다
+        let mut game = Game::new();
+        for y in BOARD_H - 3..BOARD_H {
+            for x in 4..BOARD_W {
+                game.board.grid[y][x] = Some(Block::Piece(PieceKind::O));
+            }
+        }
+        let mut expected = 0;
+        for combo in 0..3 {
+            game.current = Piece::new(PieceKind::I);
+            while game.try_move(-1, 0) {}
+            while game.try_move(0, 1) {}
+            game.lock_and_advance();
+
+            expected += 100 + 50 * combo;
+            if combo == 2 {
+                expected += 800;
+            }
+            assert_eq!(game.combo, combo as i32);
+            assert_eq!(game.score, expected);
+        }
+
+        // 줄을 못 지우면 콤보가 끊기지만 최고 기록은 남는다
+        game.current = Piece::new(PieceKind::O);
+        game.hard_drop();
+        assert_eq!(game.combo, -1);
+        assert_eq!(game.max_combo, 2);
+        assert_eq!(game.clear_counts, [3, 0, 0, 0]);
+    }
+
+    #[test]
+    fn back_to_back_tetrises_get_a_bonus() {
+        // 아래 여덟 줄의 0번 열만 비워 두고 세로 I피스로 테트리스 두 번
+        let mut game = Game::new();
+        for y in BOARD_H - 8..BOARD_H {
+            for x in 1..BOARD_W {
+                game.board.grid[y][x] = Some(Block::Piece(PieceKind::O));
+            }
+        }
+        for _ in 0..2 {
+            game.current = Piece::new(PieceKind::I);
+            game.current.y = 2;
+            assert!(game.try_rotate());
+            while game.try_move(-1, 0) {}
+            while game.try_move(0, 1) {}
+            game.lock_and_advance();
+        }
+
+        // 800 + (800 x 1.5 + 콤보 1 보너스 50 + 퍼펙트 클리어 3500)
+        assert_eq!(game.lines, 8);
+        assert_eq!(game.clear_counts, [0, 0, 0, 2]);
+        assert!(game.back_to_back);
+        assert_eq!(game.score, 800 + 1200 + 50 + 3500);
+    }
+
+    #[test]
+    fn t_piece_kicks_off_left_wall() {
+        // R 상태 T를 왼쪽 벽에 붙이고 R→2 회전: SRS 두 번째 후보 (+1, 0)
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::T);
+        game.current.y = 5;
+        assert!(game.try_rotate());
+        while game.try_move(-1, 0) {}
+        assert_eq!(game.current.x, -1);
+
+        assert!(game.try_rotate());
+        assert_eq!(game.current.rotation, 2);
+        assert_eq!(game.last_kick, (1, 0));
+        assert_eq!(game.current.x, 0);
+    }
+
+    #[test]
+    fn rotation_without_kicks_fails_unless_it_fits_in_place() {
+        // 왼쪽 벽에 붙은 R 상태 T는 제자리에서 R→2로 돌 수 없다
+        let blocked = |wall_kicks| {
+            let mut game = Game::new();
+            game.wall_kicks = wall_kicks;
+            game.current = Piece::new(PieceKind::T);
+            game.current.y = 5;
+            assert!(game.try_rotate());
+            while game.try_move(-1, 0) {}
+            (game.try_rotate(), game.current.rotation)
+        };
+        assert_eq!(blocked(true), (true, 2));
+        assert_eq!(blocked(false), (false, 1));
+
+        // 제자리에서 들어맞으면 킥 없이도 돈다
+        let mut game = Game::new();
+        game.wall_kicks = false;
+        game.current = Piece::new(PieceKind::T);
+        game.current.y = 5;
+        assert!(game.try_rotate_ccw());
+        assert!(game.try_rotate_180());
+        assert_eq!(game.last_kick, (0, 0));
+    }
+
+    #[test]
+    fn i_piece_kicks_two_columns_off_left_wall() {
+        // 세로 I를 왼쪽 벽에 붙이고 R→2 회전: 세 번째 후보 (+2, 0)
+        let mut game = Game::new();
+        game.current = Piece::new(PieceKind::I);
+        game.current.y = 5;
+        assert!(game.try_rotate());
+        while game.try_move(-1, 0) {}
+        assert_eq!(game.current.x, -2);
+
+        assert!(game.try_rotate());
+        assert_eq!(game.last_kick, (2, 0));
+        let cols: Vec<i32> = game.current.absolute_cells().iter().map(|c| c.0).collect();
+        assert_eq!(cols.iter().min(), Some(&0));
+    }
+
+    #[test]
+    fn marathon_ends_as_a_win_at_line_goal() {
+        let mut game = Game::new();
+        game.mode = GameMode::Marathon;
+        game.lines = crate::MARATHON_LINES - 1;
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+
+        assert!(game.game_over);
+        assert!(game.won());
+    }
+
+    #[test]
+    fn sprint_clock_runs_from_first_input_until_goal() {
+        let mut game = Game::new();
+        game.mode = GameMode::Sprint;
+        assert_eq!(game.elapsed(), Duration::ZERO);
+
+        let start = Instant::now() - Duration::from_secs(5);
+        game.note_input(start);
+        game.note_input(Instant::now());
+        assert!(game.elapsed() >= Duration::from_secs(5));
+
+        game.lines = crate::SPRINT_LINES - 1;
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        assert!(game.won());
+
+        // 끝난 뒤에는 시간이 더 흐르지 않는다
+        let final_time = game.elapsed();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(game.elapsed(), final_time);
+    }
+
+    #[test]
+    fn ultra_ends_when_time_runs_out() {
+        let mut game = Game::new();
+        game.mode = GameMode::Ultra;
+        game.update_timer();
+        assert!(!game.game_over, "clock has not started yet");
+
+        game.note_input(Instant::now() - crate::ULTRA_TIME);
+        game.update_timer();
+        assert!(game.game_over);
+        assert_eq!(game.end_reason, Some(EndReason::TimeUp));
+        assert_eq!(game.remaining(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn starting_level_sets_speed_and_floor() {
+        let mut game = Game::new();
+        let level_one = game.drop_interval_ms();
+        game.set_start_level(5);
+        assert_eq!(game.level, 5);
+        assert_eq!(game.drop_interval_ms(), 1000 - 4 * 80);
+        assert!(game.drop_interval_ms() < level_one);
+
+        // 한 줄을 지워도 시작 레벨 아래로 내려가지 않는다
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        assert_eq!(game.level, 5);
+    }
+
+    #[test]
+    fn speed_cap_stops_the_interval_but_not_the_level() {
+        let mut game = Game::new();
+        game.speed_cap = Some(5);
+        game.set_start_level(9);
+        assert_eq!(game.level, 9);
+        assert_eq!(game.drop_interval_ms(), 1000 - 4 * 80);
+        game.speed_cap = None;
+        assert_eq!(game.drop_interval_ms(), 1000 - 8 * 80);
+    }
+
+    #[test]
+    fn instant_gravity_spawns_pieces_on_the_floor() {
+        let mut game = Game::with_seed(2);
+        game.instant_gravity = Some(3);
+        game.respawn_current();
+        // 아직 그 레벨이 아니면 위에서 나오고 중력은 한 칸씩이다
+        assert!(!game.is_grounded());
+        let y = game.current.y;
+        game.apply(Input::Gravity);
+        assert_eq!(game.current.y, y + 1);
+
+        game.set_start_level(3);
+        game.hard_drop();
+        assert!(game.is_grounded());
+        assert!(game.take_events().contains(&GameEvent::Spawned));
+        // 바닥에서 옮긴 피스는 다음 중력 한 번에 다시 바닥까지 떨어진다
+        let score = game.score;
+        game.current.y -= 3;
+        game.apply(Input::Gravity);
+        assert!(game.is_grounded());
+        assert_eq!(game.score, score);
+    }
+
+    #[test]
+    fn lock_and_advance_clears_and_scores() {
+        // 바닥 줄에 I피스 자리만 비워 두고 하드 드롭으로 채운다
+        let mut game = Game::new();
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        while game.try_move(0, 1) {}
+        game.lock_and_advance();
+
+        // 보드가 비었으니 퍼펙트 클리어 보너스 800이 붙는다
+        assert_eq!(game.lines, 1);
+        assert_eq!(game.score, 100 + 800);
+        assert!(game.board.grid[bottom].iter().all(|c| c.is_none()));
+
+        // 얻은 점수가 지운 줄 자리에 잠깐 뜬다
+        let now = Instant::now();
+        let (popup, progress) = game.score_popup(now).expect("popup");
+        assert_eq!((popup.points, popup.row, popup.cleared), (900, bottom, 1));
+        assert!(!popup.t_spin && popup.perfect);
+        assert!(progress < 1.0);
+        assert!(game.score_popup(now + SCORE_POPUP).is_none());
+    }
+
+    #[test]
+    fn nes_scoring_pays_only_for_lines() {
+        // 한 줄 제거를 두 번 이어서 해도 콤보 보너스 없이 40 x 레벨씩이다
+        let mut game = Game::new();
+        game.scoring = ScoringMode::Nes;
+        game.set_start_level(3);
+        let bottom = BOARD_H - 1;
+        for _ in 0..2 {
+            for x in 4..BOARD_W {
+                game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+            }
+            game.board.grid[bottom - 1][BOARD_W - 1] = Some(Block::Garbage);
+            game.current = Piece::new(PieceKind::I);
+            while game.try_move(-1, 0) {}
+            while game.try_move(0, 1) {}
+            game.lock_and_advance();
+            game.board.grid[bottom][BOARD_W - 1] = None;
+        }
+        assert_eq!(game.lines, 2);
+        assert_eq!(game.score, 2 * 40 * 3);
+        assert_eq!(game.level, 3);
+    }
+
+    #[test]
+    fn animated_clear_keeps_full_rows_until_the_delay_ends() {
+        let mut game = Game::with_seed(2);
+        game.clear_delay = Duration::from_millis(200);
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+
+        // 점수는 바로 오르지만 줄은 남아 있고 다음 피스도 아직 나오지 않는다
+        assert_eq!(game.lines, 1);
+        assert_eq!(game.clearing, vec![bottom]);
+        assert!(game.board.grid[bottom].iter().all(|c| c.is_some()));
+        assert!(!game.update_lock(Instant::now() + LOCK_DELAY));
+        assert_eq!(game.pieces_placed(), 1);
+
+        let now = Instant::now();
+        assert!(!game.update_clear(now));
+        assert!(game.update_clear(now + game.clear_delay));
+        assert!(!game.is_clearing());
+        assert!(game.board.grid[bottom].iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn reports_lock_clear_level_up_and_game_over() {
+        let mut game = Game::with_seed(8);
+        game.take_events();
+        game.lines = 9;
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        assert_eq!(
+            game.take_events(),
+            vec![
+                GameEvent::Locked,
+                GameEvent::Cleared(1),
+                GameEvent::PerfectClear,
+                GameEvent::LevelUp,
+                GameEvent::Spawned
+            ]
+        );
+        assert!(game.take_events().is_empty());
+
+        game.board.grid[0][4] = Some(Block::Garbage);
+        game.hard_drop();
+        assert_eq!(game.take_events().last(), Some(&GameEvent::GameOver));
+    }
+
+    #[test]
+    fn counts_placed_pieces_by_kind() {
+        let mut game = Game::with_seed(3);
+        for kind in [PieceKind::I, PieceKind::I, PieceKind::T, PieceKind::I] {
+            game.board = Board::new();
+            game.current = Piece::new(kind);
+            game.hard_drop();
+        }
+
+        assert_eq!(game.piece_counts[PieceKind::I.index()], 3);
+        assert_eq!(game.piece_counts[PieceKind::T.index()], 1);
+        assert_eq!(game.pieces_placed(), 4);
+    }
+
+    #[test]
+    fn sonic_drop_lands_without_locking() {
+        let mut game = Game::with_seed(5);
+        game.current = Piece::new(PieceKind::O);
+        game.sonic_drop();
+
+        // O는 그리드 0~1행을 차지하므로 y=-1에서 바닥(y=BOARD_H-2)까지 BOARD_H-1칸
+        assert_eq!(game.score, BOARD_H as u32 - 1);
+        assert!(game.is_grounded());
+        assert_eq!(game.current.kind, PieceKind::O);
+        assert!(game.board.grid.iter().flatten().all(|c| c.is_none()));
+    }
+
+    #[test]
+    fn big_mode_pieces_fill_two_by_two_cells_and_clear_rows_in_pairs() {
+        let mut game = Game::from_script(vec![PieceKind::O; 3], 1);
+        game.mode = GameMode::Big;
+        game.respawn_current();
+        assert_eq!(game.current.absolute_cells().len(), 16);
+        let x = game.current.x;
+        assert!(game.try_move(1, 0));
+        assert_eq!(game.current.x, x + 2);
+
+        // 바닥 두 줄을 O가 떨어질 네 열만 남기고 채우면 빅 O 하나로 두 줄이 지워진다
+        let left = game.current.absolute_cells().iter().map(|&(x, _)| x).min();
+        let columns = left.unwrap()..left.unwrap() + 4;
+        for row in &mut game.board.grid[BOARD_H - 2..] {
+            for (col, cell) in row.iter_mut().enumerate() {
+                if !columns.contains(&(col as i32)) {
+                    *cell = Some(Block::Garbage);
+                }
+            }
+        }
+        game.hard_drop();
+        assert_eq!(game.lines, 2);
+        let filled = game.board.grid.iter().flatten().filter(|c| c.is_some());
+        assert_eq!(filled.count(), 8);
+        assert!(game.board.grid[BOARD_H - 1][columns.start as usize].is_some());
+    }
+
+    #[test]
+    fn big_mode_boards_have_even_sizes_so_every_edge_is_reachable() {
+        let config = GameConfig {
+            mode: GameMode::Big,
+            width: 11,
+            height: 21,
+            garbage: 3,
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_seed_and_config(4, config);
+        assert_eq!((game.board.width(), game.board.height()), (10, 20));
+        // 핸디캡 쓰레기 줄도 두 줄씩 같은 모양으로 깔린다
+        let rows = &game.board.grid[20 - 6..];
+        for pair in rows.chunks(2) {
+            assert_eq!(pair[0], pair[1]);
+            assert!(pair[0].iter().any(Option::is_some));
+        }
+        assert!(game.board.grid[20 - 7].iter().all(Option::is_none));
+
+        while game.try_move(1, 0) {}
+        let right = game.current.absolute_cells().iter().map(|&(x, _)| x).max();
+        assert_eq!(right, Some(9));
+    }
+
+    #[test]
+    fn big_mode_garbage_has_a_two_wide_hole_a_big_piece_can_fill() {
+        let mut game = Game::from_script(vec![PieceKind::I; 3], 1);
+        game.mode = GameMode::Big;
+        game.respawn_current();
+        assert!(game.try_rotate());
+        let left = game.current.absolute_cells().iter().map(|&(x, _)| x).min();
+        let left = left.unwrap() as usize;
+        game.add_garbage(left / 2);
+        for row in &game.board.grid[BOARD_H - 2..] {
+            let holes: Vec<usize> = (0..BOARD_W).filter(|&x| row[x].is_none()).collect();
+            assert_eq!(holes, [left, left + 1]);
+        }
+        assert!(game.board.grid[BOARD_H - 3].iter().all(Option::is_none));
+
+        // 세운 빅 I가 구멍을 메우면 두 줄이 함께 지워져 줄 짝이 어긋나지 않는다
+        game.hard_drop();
+        assert_eq!(game.lines, 2);
+        let filled = game.board.grid.iter().flatten().filter(|c| c.is_some());
+        assert_eq!(filled.count(), 2 * 6);
+    }
+
+    #[test]
+    fn counts_cells_soft_and_hard_dropped() {
+        let mut game = Game::from_script(vec![PieceKind::O; 3], 5);
+        game.soft_drop();
+        game.soft_drop();
+        assert_eq!((game.soft_drop_cells, game.hard_drop_cells), (2, 0));
+        // 첫 O는 두 칸 내려온 뒤 바닥(y=BOARD_H-2)까지, 다음 O들은 차례로 그 위까지
+        game.hard_drop();
+        game.hard_drop();
+        let first = BOARD_H as u32 - 1 - 2;
+        assert_eq!(game.hard_drop_cells, first + (BOARD_H as u32 - 3));
+        game.sonic_drop();
+        assert_eq!(game.soft_drop_cells, 2 + (BOARD_H as u32 - 5));
+    }
+
+    #[test]
+    fn with_config_applies_mode_level_gravity_and_size() {
+        let game = Game::with_config(GameConfig {
+            mode: GameMode::Sprint,
+            start_level: 7,
+            gravity: GravityCurve::Guideline,
+            scoring: ScoringMode::Nes,
+            width: 6,
+            height: 12,
+            garbage: 0,
+            randomizer: RandomizerKind::Tgm,
+        });
+        assert_eq!((game.board.width(), game.board.height()), (6, 12));
+        assert_eq!(game.randomizer(), RandomizerKind::Tgm);
+        // 좁은 보드에서도 가운데에 스폰된다
+        assert!(game.board.fits(&game.current.absolute_cells()));
+        assert_eq!(game.mode, GameMode::Sprint);
+        assert_eq!(game.level, 7);
+        assert_eq!(game.start_level, 7);
+        assert_eq!(game.scoring, ScoringMode::Nes);
+        assert_eq!(
+            game.drop_interval_ms(),
+            GravityCurve::Guideline.interval_ms(7)
+        );
+    }
+
+    #[test]
+    fn invisible_mode_hides_the_stack_until_peek() {
+        let mut game = Game::with_seed(9);
+        game.mode = GameMode::Invisible;
+        game.current = Piece::new(PieceKind::O);
+        game.hard_drop();
+        game.score = 1000;
+        let bottom = BOARD_H as i32 - 1;
+
+        // 방금 놓은 피스는 잠깐 보이고, 시간이 지나면 사라진다
+        let now = Instant::now();
+        assert!(game.cell_visible(4, bottom, now));
+        let later = now + INVISIBLE_DELAY;
+        assert!(!game.cell_visible(4, bottom, later));
+        assert!(game.board.grid[bottom as usize][4].is_some());
+
+        game.peek(later);
+        assert!(game.cell_visible(4, bottom, later));
+        assert_eq!(game.score, 1000 - PEEK_PENALTY);
+    }
+
+    #[test]
+    fn garbage_lifts_the_falling_piece_and_can_top_out() {
+        let mut game = Game::with_seed(4);
+        game.current = Piece::new(PieceKind::O);
+        game.sonic_drop();
+        let y = game.current.y;
+        game.add_garbage(0);
+        assert_eq!(game.current.y, y - 1);
+        assert!(game.board.fits(&game.current.absolute_cells()));
+
+        game.board.grid[0][9] = Some(Block::Piece(PieceKind::I));
+        game.add_garbage(0);
+        assert_eq!(game.end_reason, Some(EndReason::TopOut));
+    }
+
+    #[test]
+    fn seeded_games_get_the_same_garbage() {
+        let play = |attacks: &[usize]| {
+            let mut game = Game::with_seed(11);
+            for &rows in attacks {
+                for _ in 0..rows {
+                    let hole = game.next_garbage_hole();
+                    game.add_garbage(hole);
+                }
+                game.apply(Input::HardDrop);
+            }
+            game
+        };
+        let attacks = [1, 2, 0, 4, 1];
+        let (a, b) = (play(&attacks), play(&attacks));
+        assert_eq!(a.board, b.board);
+        assert_eq!(a.garbage_draws(), 8);
+
+        // 뽑은 횟수만 맞추면 다른 게임의 난수원도 같은 다음 구멍을 낸다
+        let mut synced = Game::with_seed(11);
+        synced.sync_garbage(a.garbage_draws());
+        let mut a = a;
+        assert_eq!(synced.next_garbage_hole(), a.next_garbage_hole());
+    }
+
+    #[test]
+    fn versus_garbage_is_cancelled_by_clears_and_rises_on_a_plain_lock() {
+        let fill = |game: &mut Game, rows: usize, from: usize| {
+            for y in BOARD_H - rows..BOARD_H {
+                for x in from..BOARD_W {
+                    game.board.grid[y][x] = Some(Block::Piece(PieceKind::O));
+                }
+            }
+        };
+        let attacks = |game: &mut Game| -> Vec<u32> {
+            let events = game.take_events().into_iter();
+            events
+                .filter_map(|e| match e {
+                    GameEvent::Attack(rows) => Some(rows),
+                    _ => None,
+                })
+                .collect()
+        };
+        let mut game = Game::with_seed(3);
+        game.receive_garbage(3);
+
+        // 더블로 보낼 1줄은 받을 3줄 중 1줄을 상쇄하는 데 쓴다
+        fill(&mut game, 2, 2);
+        game.current = Piece::new(PieceKind::O);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        assert_eq!(game.incoming_garbage, 2);
+        assert!(attacks(&mut game).is_empty());
+
+        // 테트리스의 4줄 중 2줄로 나머지를 상쇄하고 2줄을 보낸다
+        fill(&mut game, 4, 1);
+        game.current = Piece::new(PieceKind::I);
+        game.try_rotate();
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        assert_eq!(game.incoming_garbage, 0);
+        assert_eq!(attacks(&mut game), vec![2]);
+
+        // 줄을 못 지우고 고정하면 받아 둔 줄이 다음 피스 전에 올라온다
+        game.receive_garbage(2);
+        game.hard_drop();
+        assert_eq!(game.incoming_garbage, 0);
+        assert_eq!(game.garbage_draws(), 2);
+        for y in [BOARD_H - 1, BOARD_H - 2] {
+            let garbage = game.board.grid[y]
+                .iter()
+                .filter(|c| **c == Some(Block::Garbage))
+                .count();
+            assert_eq!(garbage, BOARD_W - 1);
+        }
+    }
+
+    #[test]
+    fn endless_incoming_garbage_tops_out_without_overflowing() {
+        let mut game = Game::with_seed(3);
+        game.receive_garbage(u32::MAX);
+        game.receive_garbage(u32::MAX);
+        assert_eq!(game.incoming_garbage, u32::MAX);
+        game.hard_drop();
+        assert!(game.game_over);
+        assert!(game.garbage_draws() <= BOARD_H as u32);
+    }
+
+    #[test]
+    fn rewinding_under_garbage_drops_the_rest_of_the_attack() {
+        let mut game = Game::with_seed(3);
+        game.rewinds_remaining = 1;
+        game.rewind_pieces = 1;
+        game.hard_drop();
+        let (board, kind) = (game.board.clone(), game.current.kind);
+        game.receive_garbage(2 * BOARD_H as u32);
+        game.hard_drop();
+        assert!(game.take_events().contains(&GameEvent::Rewound));
+        assert!(!game.game_over);
+        assert_eq!(game.incoming_garbage, 0);
+        assert!(game.garbage_draws() <= BOARD_H as u32);
+        // 되감은 보드와 피스 위에 남은 쓰레기 줄이 더 올라오지 않는다
+        assert_eq!(game.board, board);
+        assert_eq!(game.current.kind, kind);
+    }
+
+    #[test]
+    fn same_hole_garbage_keeps_one_column() {
+        let holes = |same| {
+            let mut game = Game::with_seed(2);
+            game.same_garbage_hole = same;
+            for _ in 0..10 {
+                let hole = game.next_garbage_hole();
+                game.add_garbage(hole);
+            }
+            let rows = &game.board.grid[BOARD_H - 10..];
+            rows.iter()
+                .map(|row| row.iter().position(Option::is_none).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let same = holes(true);
+        assert!(same.iter().all(|&hole| hole == same[0]));
+        let random = holes(false);
+        assert!(random.iter().any(|&hole| hole != random[0]));
+    }
+
+    #[test]
+    fn handicap_garbage_fills_the_bottom_rows_the_same_for_a_seed() {
+        let handicap = |seed| {
+            Game::with_seed_and_config(
+                seed,
+                GameConfig {
+                    garbage: 5,
+                    ..GameConfig::default()
+                },
+            )
+        };
+        let game = handicap(8);
+        let filled: Vec<_> = game
+            .board
+            .grid
+            .iter()
+            .filter(|row| row.iter().any(Option::is_some))
+            .collect();
+        assert_eq!(filled.len(), 5);
+        assert!(
+            game.board.grid[BOARD_H - 5..]
+                .iter()
+                .all(|row| { row.iter().filter(|c| c.is_none()).count() == 1 })
+        );
+        assert_eq!(game.garbage_draws(), 5);
+        assert_eq!(handicap(8).board, game.board);
+
+        // 보드보다 많이 깔라고 해도 맨 윗줄은 비워 둔다
+        let full = Game::with_config(GameConfig {
+            garbage: 99,
+            ..GameConfig::default()
+        });
+        assert!(full.board.grid[0].iter().all(Option::is_none));
+        assert!(full.board.grid[1].iter().any(Option::is_some));
+    }
+
+    #[test]
+    fn practice_undo_restores_the_board_score_and_piece() {
+        let mut game = Game::with_seed_and_config(
+            3,
+            GameConfig {
+                mode: GameMode::Practice,
+                ..GameConfig::default()
+            },
+        );
+        assert!(!game.undo());
+        let first = game.current.kind;
+        let queue = game.queue.clone();
+        game.apply(Input::Left);
+        game.apply(Input::HardDrop);
+        assert!(game.score > 0);
+        assert_eq!(game.pieces_placed(), 1);
+
+        game.apply(Input::Undo);
+        assert_eq!(game.board, Board::new());
+        assert_eq!((game.score, game.pieces_placed()), (0, 0));
+        assert_eq!(
+            game.current.absolute_cells(),
+            Piece::new(first).absolute_cells()
+        );
+        assert_eq!(game.queue, queue);
+
+        // 피스를 직접 바꿔도 다음 피스 순서는 그대로다
+        game.apply(Input::Cycle);
+        let cycled = ALL_PIECES[(first.index() + 1) % 7];
+        assert_eq!(game.current.kind, cycled);
+        assert_eq!(game.queue, queue);
+
+        // 기록은 최대 MAX_UNDO개만 남는다
+        for _ in 0..MAX_UNDO + 5 {
+            game.hard_drop();
+        }
+        assert_eq!(game.undo_depth(), MAX_UNDO);
+    }
+
+    #[test]
+    fn undo_and_cycle_do_nothing_outside_practice() {
+        let mut game = Game::with_seed(3);
+        let kind = game.current.kind;
+        game.apply(Input::Cycle);
+        assert_eq!(game.current.kind, kind);
+        game.apply(Input::HardDrop);
+        game.apply(Input::Undo);
+        assert_eq!(game.pieces_placed(), 1);
+    }
+
+    #[test]
+    fn zen_mode_clears_the_top_instead_of_topping_out() {
+        let mut game = Game::with_seed(1);
+        game.mode = GameMode::Zen;
+        for row in 0..6 {
+            for col in 0..BOARD_W - 1 {
+                game.board.grid[row][col] = Some(Block::Garbage);
+            }
+        }
+        game.hard_drop();
+        assert!(!game.game_over);
+        assert!(game.board.fits(&game.current.absolute_cells()));
+        // 비운 줄 아래는 그대로 남는다
+        assert!(
+            game.board.grid[..ZEN_CLEAR_ROWS]
+                .iter()
+                .flatten()
+                .all(|c| c.is_none())
+        );
+        assert!(game.board.grid[ZEN_CLEAR_ROWS][0].is_some());
+    }
+
+    #[test]
+    fn emptying_the_board_awards_a_perfect_clear() {
+        let mut game = Game::with_seed(2);
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Piece(PieceKind::O));
+        }
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+
+        assert!(game.board.grid.iter().flatten().all(|c| c.is_none()));
+        // 가로 I는 0행에서 맨 아래까지 BOARD_H-1칸을 하드 드롭한다
+        let drop = 2 * (BOARD_H as u32 - 1);
+        assert_eq!(game.score, drop + 100 + 800);
+        assert!(game.take_events().contains(&GameEvent::PerfectClear));
+    }
+
+    #[test]
+    fn scripted_pieces_come_before_the_bag() {
+        let [i, o, t, s, z, j, l] = ALL_PIECES;
+        let script = vec![t, s, z, o, i, l, j, t];
+        let mut game = Game::from_script(script, 3);
+        assert_eq!(game.current.kind, t);
+        assert_eq!(Vec::from(game.queue.clone()), vec![s, z, o, i, l]);
+
+        let mut spawned = Vec::new();
+        for _ in 0..3 {
+            game.hard_drop();
+            spawned.push(game.current.kind);
+        }
+        assert_eq!(spawned, vec![s, z, o]);
+        // 대본을 다 쓴 뒤에는 같은 시드의 가방 첫 피스부터 나온다
+        let fresh = Game::with_seed(3);
+        assert_eq!(
+            Vec::from(game.queue.clone()),
+            vec![i, l, j, t, fresh.current.kind]
+        );
+    }
+
+    #[test]
+    fn hard_drop_flashes_the_locked_cells_briefly() {
+        let mut game = Game::with_seed(5);
+        game.current = Piece::new(PieceKind::O);
+        game.hard_drop();
+        let now = Instant::now();
+        let (cells, progress) = game.lock_flash(now).expect("flashing");
+        assert_eq!(cells.len(), 4);
+        assert!(progress < 1.0);
+        assert!(game.lock_flash(now + LOCK_FLASH).is_none());
+
+        // 중력과 락 딜레이로 고정하면 번쩍이지 않는다
+        game.current = Piece::new(PieceKind::O);
+        game.sonic_drop();
+        game.lock_and_advance();
+        assert!(game.lock_flash(Instant::now()).is_none());
+    }
+
+    #[test]
+    fn first_hold_takes_the_next_piece_from_the_queue() {
+        let [i, o, t, s, z, j, l] = ALL_PIECES;
+        let mut game = Game::from_script(vec![t, s, z, o, i, l, j], 1);
+        assert!(game.hold());
+        assert_eq!(game.held, Some(t));
+        assert_eq!(game.current.kind, s);
+        assert_eq!(Vec::from(game.queue.clone()), vec![z, o, i, l, j]);
+    }
+
+    #[test]
+    fn holding_again_swaps_after_a_lock() {
+        let [i, o, t, s, z, j, l] = ALL_PIECES;
+        let mut game = Game::from_script(vec![t, s, z, o, i, l, j], 1);
+        game.hold();
+        game.hard_drop();
+        assert_eq!(game.current.kind, z);
+        assert!(game.hold());
+        assert_eq!((game.current.kind, game.held), (t, Some(z)));
+        // 바꿔 나온 피스도 스폰 위치에서 시작한다
+        assert_eq!(
+            game.current.absolute_cells(),
+            Piece::new(t).absolute_cells()
+        );
+        assert_eq!(Vec::from(game.queue.clone())[..4], [o, i, l, j]);
+    }
+
+    #[test]
+    fn hold_is_blocked_until_the_piece_locks() {
+        let [i, o, t, s, z, j, l] = ALL_PIECES;
+        let mut game = Game::from_script(vec![t, s, z, o, i, l, j], 1);
+        assert!(game.hold());
+        assert!(!game.can_hold());
+        assert!(!game.hold());
+        assert_eq!((game.current.kind, game.held), (s, Some(t)));
+        game.hard_drop();
+        assert!(game.can_hold());
+    }
+
+    #[test]
+    fn hold_limit_allows_exactly_that_many_holds() {
+        let mut game = Game::with_seed(2);
+        game.holds_remaining = Some(3);
+        for left in (0..3).rev() {
+            assert!(game.hold());
+            assert_eq!(game.holds_remaining, Some(left));
+            game.hard_drop();
+        }
+        // 다 쓰면 피스를 고정한 뒤에도 홀드할 수 없고 피스도 그대로다
+        assert!(!game.can_hold());
+        let kind = game.current.kind;
+        assert!(!game.hold());
+        assert_eq!((game.current.kind, game.holds_remaining), (kind, Some(0)));
+
+        // 막힌 홀드(고정 전 두 번째)는 횟수를 쓰지 않는다
+        let mut game = Game::with_seed(2);
+        game.holds_remaining = Some(2);
+        assert!(game.hold());
+        assert!(!game.hold());
+        assert_eq!(game.holds_remaining, Some(1));
+    }
+
+    #[test]
+    fn topping_out_rewinds_a_few_pieces_until_rewinds_run_out() {
+        let mut game = Game::with_seed(3);
+        game.rewinds_remaining = 1;
+        game.rewind_pieces = 2;
+        game.rewind_penalty = 10;
+        // 가운데에 쌓기만 하면 곧 막힌다. 고정하기 전의 보드와 점수를 적어 둔다.
+        let mut before = Vec::new();
+        while !game.take_events().contains(&GameEvent::Rewound) {
+            assert!(!game.game_over);
+            before.push((game.board.clone(), game.score, game.current.kind));
+            game.hard_drop();
+        }
+        let (board, score, kind) = &before[before.len() - 2];
+        assert_eq!(&game.board, board);
+        assert_eq!(game.score, score - 10);
+        assert_eq!(game.current.kind, *kind);
+        assert_eq!(game.rewinds_remaining, 0);
+
+        // 되감기를 다 쓰면 다음에 막힐 때 그대로 끝난다
+        while !game.game_over {
+            game.hard_drop();
+        }
+        assert_eq!(game.end_reason, Some(EndReason::TopOut));
+        assert!(!game.take_events().contains(&GameEvent::Rewound));
+    }
+
+    #[test]
+    fn swapping_into_a_blocked_spawn_tops_out() {
+        let [i, o, t, s, z, j, l] = ALL_PIECES;
+        let mut game = Game::from_script(vec![t, s, z, o, i, l, j], 1);
+        game.hold();
+        game.hard_drop();
+        // T가 나올 자리를 막아 둔다
+        for &(x, y) in &Piece::new(t).absolute_cells() {
+            if y >= 0 {
+                game.board.grid[y as usize][x as usize] = Some(Block::Garbage);
+            }
+        }
+        assert!(game.hold());
+        assert_eq!(game.end_reason, Some(EndReason::TopOut));
+        assert!(!game.hold());
+    }
+
+    #[test]
+    fn hard_drop_leaves_a_trail_above_the_landing_spot() {
+        let mut game = Game::with_seed(5);
+        game.current = Piece::new(PieceKind::I);
+        game.hard_drop();
+        let now = Instant::now();
+        let (trail, fade) = game.drop_trail(now).expect("trail");
+        // 가로 I는 0행에서 맨 아래 줄까지 내려가고, 궤적은 그 바로 위 줄에서 끝난다
+        let expected = DropTrail {
+            left: 3,
+            right: 6,
+            top: 0,
+            bottom: BOARD_H as i32 - 2,
+        };
+        assert_eq!(trail, expected);
+        assert!(fade < 1.0);
+        assert!(game.drop_trail(now + DROP_TRAIL).is_none());
+        // 점수는 궤적과 상관없이 떨어진 칸 수로만 준다
+        assert_eq!(game.score, 2 * (BOARD_H as u32 - 1));
+
+        // 다음 고정에서 지워진다
+        game.sonic_drop();
+        game.lock_and_advance();
+        assert!(game.drop_trail(Instant::now()).is_none());
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::str::FromStr;
+
+// NES(NTSC) 한 프레임 길이(µs)
+const NES_FRAME_US: u64 = 16_639;
+// NES 레벨 0부터의 한 줄 낙하 프레임 수. 마지막 값이 그 뒤 레벨에도 쓰인다
+const NES_FRAMES: [u64; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    1,
+];
+// 가이드라인 공식이 음수로 가지 않도록 이 레벨에서 멈춘다
+const GUIDELINE_MAX_LEVEL: u32 = 20;
+
+/// 모든 곡선이 가장 빠른 간격에 닿는 레벨. 이보다 높은 레벨은 떨어지는 속도가 같다.
+pub const MAX_SPEED_LEVEL: u32 = NES_FRAMES.len() as u32;
+
+/// 레벨에 따라 피스가 한 칸 떨어지는 간격을 정하는 곡선
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GravityCurve {
+    /// 레벨마다 80ms씩 빨라지고 50ms에서 멈춘다
+    #[default]
+    Linear,
+    /// 원조 NES 테트리스의 프레임 표를 따라 계단식으로 빨라진다
+    Nes,
+    /// 가이드라인 공식 `(0.8 - (레벨-1)*0.007)^(레벨-1)`초로 지수적으로 빨라진다
+    Guideline,
+}
+
+impl GravityCurve {
+    /// 메뉴에서 고를 수 있는 순서
+    pub const ALL: [GravityCurve; 3] = [
+        GravityCurve::Linear,
+        GravityCurve::Nes,
+        GravityCurve::Guideline,
+    ];
+
+    /// `level`(1부터)에서의 낙하 간격(ms).
+    pub fn interval_ms(self, level: u32) -> u64 {
+        let level = level.max(1);
+        match self {
+            GravityCurve::Linear => 1000u64.saturating_sub((level as u64 - 1) * 80).max(50),
+            GravityCurve::Nes => {
+                let i = (level as usize - 1).min(NES_FRAMES.len() - 1);
+                NES_FRAMES[i] * NES_FRAME_US / 1000
+            }
+            GravityCurve::Guideline => {
+                let n = level.min(GUIDELINE_MAX_LEVEL) - 1;
+                let secs = (0.8 - n as f64 * 0.007).powi(n as i32);
+                ((secs * 1000.0).round() as u64).max(1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for GravityCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GravityCurve::Linear => "linear",
+            GravityCurve::Nes => "nes",
+            GravityCurve::Guideline => "guideline",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for GravityCurve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "linear" => Ok(GravityCurve::Linear),
+            "nes" | "classic" => Ok(GravityCurve::Nes),
+            "guideline" => Ok(GravityCurve::Guideline),
+            _ => Err(format!(
+                "unknown gravity '{s}' (expected linear, nes or guideline)"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_drops_80ms_per_level_down_to_50() {
+        let curve = GravityCurve::Linear;
+        assert_eq!(curve.interval_ms(1), 1000);
+        assert_eq!(curve.interval_ms(5), 680);
+        assert_eq!(curve.interval_ms(12), 120);
+        assert_eq!(curve.interval_ms(15), 50);
+    }
+
+    #[test]
+    fn nes_follows_the_frame_table() {
+        let curve = GravityCurve::Nes;
+        // 48프레임, 6프레임, 5프레임이 이어지는 구간, 2프레임, 1프레임
+        assert_eq!(curve.interval_ms(1), 798);
+        assert_eq!(curve.interval_ms(10), 99);
+        assert_eq!(curve.interval_ms(11), curve.interval_ms(13));
+        assert_eq!(curve.interval_ms(20), 33);
+        assert_eq!(curve.interval_ms(30), 16);
+        assert_eq!(curve.interval_ms(99), 16);
+    }
+
+    #[test]
+    fn guideline_speeds_up_exponentially() {
+        let curve = GravityCurve::Guideline;
+        assert_eq!(curve.interval_ms(1), 1000);
+        assert_eq!(curve.interval_ms(2), 793);
+        assert_eq!(curve.interval_ms(5), 355);
+        assert_eq!(curve.interval_ms(10), 64);
+        assert_eq!(curve.interval_ms(15), 7);
+        assert_eq!(curve.interval_ms(99), 1);
+    }
+
+    #[test]
+    fn no_curve_gets_faster_after_the_max_speed_level() {
+        for curve in GravityCurve::ALL {
+            let fastest = curve.interval_ms(MAX_SPEED_LEVEL);
+            assert_eq!(curve.interval_ms(MAX_SPEED_LEVEL + 50), fastest, "{curve}");
+            assert!(curve.interval_ms(MAX_SPEED_LEVEL - 1) >= fastest, "{curve}");
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for curve in GravityCurve::ALL {
+            assert_eq!(curve.to_string().parse::<GravityCurve>(), Ok(curve));
+        }
+        assert!("fast".parse::<GravityCurve>().is_err());
+    }
+}
@@ -0,0 +1,379 @@
+use constris::Input;
+use crossterm::event::KeyEventKind;
+use std::time::{Duration, Instant};
+
+// DAS(지연 자동 이동): 처음 누른 뒤 이 시간이 지나면 자동 반복을 시작하고,
+// 이후 ARR 간격마다 한 칸씩 민다. 설정 파일의 das_ms, arr_ms로 바꾼다.
+pub const DAS_DELAY: Duration = Duration::from_millis(170);
+pub const ARR: Duration = Duration::from_millis(50);
+// 소프트 드롭 키를 누르고 있을 때 한 칸씩 내리는 기본 간격
+pub const SOFT_DROP_INTERVAL: Duration = Duration::from_millis(30);
+// 키 떼기 이벤트가 없는 터미널에서는 이 시간 동안 눌림이 다시 오지 않으면 뗀 것으로 본다
+const SOFT_DROP_TIMEOUT: Duration = Duration::from_millis(150);
+// 중력의 배수로 정한 소프트 드롭도 이보다 빠르게는 내리지 않는다
+const MIN_SOFT_DROP_INTERVAL: Duration = Duration::from_millis(5);
+
+/// 중력 낙하 간격의 `multiplier`배 빠르기로 내리는 소프트 드롭 간격
+pub fn soft_drop_interval(drop_interval_ms: u64, multiplier: u32) -> Duration {
+    Duration::from_millis(drop_interval_ms / u64::from(multiplier.max(1)))
+        .max(MIN_SOFT_DROP_INTERVAL)
+}
+
+/// 터미널이 보낸 키 이벤트 종류를 게임이 처리할 종류로 맞춘다. 무시할 이벤트면 `None`.
+///
+/// 키 떼기 이벤트를 받을 수 있으면(`release_events`) 눌림과 떼기만 쓰고, 누르고 있는 동안의
+/// 반복은 DAS와 소프트 드롭 반복이 맡는다. 받을 수 없는 터미널 중에는 반복을 `Repeat`로만
+/// 보내거나 떼기를 따로 보내는 것이 있어서, 그때는 반복도 눌림으로 보고 떼기는 버린다.
+pub fn key_kind(kind: KeyEventKind, release_events: bool) -> Option<KeyEventKind> {
+    match kind {
+        KeyEventKind::Press => Some(KeyEventKind::Press),
+        KeyEventKind::Release if release_events => Some(KeyEventKind::Release),
+        KeyEventKind::Repeat if !release_events => Some(KeyEventKind::Press),
+        _ => None,
+    }
+}
+
+/// 좌우 이동 키를 누르고 있는 상태. 키 떼기 이벤트를 받을 수 있을 때만 쓴다.
+pub struct AutoShift {
+    delay: Duration,
+    repeat: Duration,
+    dir: Option<i32>,
+    held_since: Instant,
+    last_shift: Instant,
+}
+
+impl AutoShift {
+    /// `delay`만큼 누르고 있으면 `repeat` 간격으로 민다(`repeat`은 0이 아니어야 한다).
+    pub fn new(delay: Duration, repeat: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            delay,
+            repeat,
+            dir: None,
+            held_since: now,
+            last_shift: now,
+        }
+    }
+
+    /// 방향 키를 눌렀다. 첫 칸은 호출한 쪽이 바로 옮긴다.
+    pub fn press(&mut self, dir: i32, now: Instant) {
+        self.dir = Some(dir);
+        self.held_since = now;
+        self.last_shift = now;
+    }
+
+    /// 방향 키를 누르고 있는지
+    pub fn is_held(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// `dir` 쪽 키를 누르고 있는지. 떼기 없이 같은 쪽 눌림이 또 오면 터미널의 키 반복이라
+    /// 한 칸 더 옮기거나 충전을 처음부터 다시 하지 않는다.
+    pub fn holds(&self, dir: i32) -> bool {
+        self.dir == Some(dir)
+    }
+
+    /// 누르고 있던 방향을 잊는다(일시 정지나 새 판에서 키 떼기를 못 받았을 때).
+    pub fn clear(&mut self) {
+        self.dir = None;
+    }
+
+    /// 방향 키를 뗐다. 나중에 누른 반대쪽 키가 잡혀 있으면 그대로 둔다.
+    pub fn release(&mut self, dir: i32) {
+        if self.dir == Some(dir) {
+            self.dir = None;
+        }
+    }
+
+    /// 지금까지 쌓인 자동 이동을 `(방향, 칸 수)`로 돌려준다.
+    pub fn take_shifts(&mut self, now: Instant) -> Option<(i32, u32)> {
+        let dir = self.dir?;
+        let charged_at = self.held_since + self.delay;
+        if now < charged_at {
+            return None;
+        }
+        // 충전이 끝나는 순간 첫 자동 이동이 나오도록 기준 시각을 맞춘다
+        let from = self.last_shift.max(charged_at - self.repeat);
+        let steps = (now.duration_since(from).as_millis() / self.repeat.as_millis()) as u32;
+        if steps == 0 {
+            return None;
+        }
+        self.last_shift = from + self.repeat * steps;
+        Some((dir, steps))
+    }
+}
+
+/// 소프트 드롭 키를 누르고 있는 상태. 좌우 DAS와 따로 자기 간격으로 반복한다.
+pub struct SoftDropRepeat {
+    interval: Duration,
+    // 키 떼기 이벤트를 받을 수 있는지. 없으면 눌림이 끊긴 지 오래되면 멈춘다.
+    release_events: bool,
+    // 누르고 있으면 마지막으로 한 칸 내린 시각
+    last_drop: Option<Instant>,
+    last_press: Instant,
+}
+
+impl SoftDropRepeat {
+    pub fn new(interval: Duration, release_events: bool) -> Self {
+        Self {
+            interval,
+            release_events,
+            last_drop: None,
+            last_press: Instant::now(),
+        }
+    }
+
+    /// 소프트 드롭 키를 눌렀다. 첫 칸은 호출한 쪽이 바로 내린다.
+    /// 터미널의 키 반복으로 다시 들어온 눌림은 반복 간격을 흩뜨리지 않는다.
+    pub fn press(&mut self, now: Instant) {
+        self.last_drop.get_or_insert(now);
+        self.last_press = now;
+    }
+
+    pub fn release(&mut self) {
+        self.last_drop = None;
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.last_drop.is_some()
+    }
+
+    /// 반복 간격을 바꾼다(중력의 배수로 정했으면 레벨이 오를 때마다).
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// 지금까지 쌓인 반복 낙하 칸 수
+    pub fn take_drops(&mut self, now: Instant) -> u32 {
+        let Some(from) = self.last_drop else {
+            return 0;
+        };
+        if !self.release_events
+            && now.saturating_duration_since(self.last_press) >= SOFT_DROP_TIMEOUT
+        {
+            self.last_drop = None;
+            return 0;
+        }
+        let steps = (now.saturating_duration_since(from).as_millis()
+            / self.interval.as_millis().max(1)) as u32;
+        self.last_drop = Some(from + self.interval * steps);
+        steps
+    }
+}
+
+/// 자동 낙하(중력) 시계. 간격이 프레임보다 짧으면 한 프레임에 여러 칸을 내리고,
+/// 간격에 못 미친 시간을 버리지 않도록 마지막 낙하 시각을 간격 단위로만 옮긴다.
+pub struct GravityClock {
+    last_drop: Instant,
+}
+
+impl GravityClock {
+    pub fn new(now: Instant) -> Self {
+        Self { last_drop: now }
+    }
+
+    /// 지금부터 다시 잰다(피스를 직접 내렸거나 새 피스가 나왔을 때).
+    pub fn reset(&mut self, now: Instant) {
+        self.last_drop = now;
+    }
+
+    /// 지금까지 쌓인 낙하 칸 수. 그만큼의 간격을 마지막 낙하 시각에 더한다.
+    pub fn take_drops(&mut self, interval: Duration, now: Instant) -> u32 {
+        let steps = (now.saturating_duration_since(self.last_drop).as_nanos()
+            / interval.as_nanos().max(1)) as u32;
+        self.last_drop += interval * steps;
+        steps
+    }
+
+    /// 다음 칸이 떨어지기까지 남은 시간
+    pub fn until_next(&self, interval: Duration, now: Instant) -> Duration {
+        interval.saturating_sub(now.saturating_duration_since(self.last_drop))
+    }
+}
+
+/// 누르고 있는 회전/홀드 키. 새 피스가 나오는 순간 잡혀 있으면 그 피스에 바로 넣는다(IRS/IHS).
+/// 키 떼기 이벤트를 받을 수 있을 때만 쓴다.
+pub struct InitialActions {
+    enabled: bool,
+    held: Vec<Input>,
+}
+
+impl InitialActions {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            held: Vec::new(),
+        }
+    }
+
+    /// 키를 눌렀다. 회전과 홀드만 잡아 둔다.
+    pub fn press(&mut self, input: Input) {
+        let initial = matches!(
+            input,
+            Input::Hold | Input::RotateCw | Input::RotateCcw | Input::Rotate180
+        );
+        if self.enabled && initial && !self.held.contains(&input) {
+            self.held.push(input);
+        }
+    }
+
+    pub fn release(&mut self, input: Input) {
+        self.held.retain(|&held| held != input);
+    }
+
+    pub fn is_held(&self, input: Input) -> bool {
+        self.held.contains(&input)
+    }
+
+    /// 새 피스에 넣을 입력. 홀드를 먼저 해서 꺼낸 피스가 돌아간 채로 나온다.
+    pub fn inputs(&self) -> Vec<Input> {
+        let hold = self.held.iter().filter(|&&input| input == Input::Hold);
+        let rotations = self.held.iter().filter(|&&input| input != Input::Hold);
+        hold.chain(rotations).copied().collect()
+    }
+
+    /// 판이 바뀌거나 멈추면 잡아 둔 키를 잊는다.
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_shift_waits_for_das_then_repeats_at_arr() {
+        let start = Instant::now();
+        let mut das = AutoShift::new(DAS_DELAY, ARR);
+        das.press(-1, start);
+
+        assert_eq!(das.take_shifts(start + DAS_DELAY / 2), None);
+        assert_eq!(das.take_shifts(start + DAS_DELAY), Some((-1, 1)));
+        assert_eq!(das.take_shifts(start + DAS_DELAY + ARR / 2), None);
+        assert_eq!(das.take_shifts(start + DAS_DELAY + ARR * 3), Some((-1, 3)));
+
+        das.release(-1);
+        assert_eq!(das.take_shifts(start + DAS_DELAY + ARR * 10), None);
+    }
+
+    #[test]
+    fn auto_shift_uses_the_configured_timing() {
+        let start = Instant::now();
+        let (delay, repeat) = (Duration::from_millis(100), Duration::from_millis(10));
+        let mut das = AutoShift::new(delay, repeat);
+        das.press(1, start);
+        assert_eq!(das.take_shifts(start + delay - repeat), None);
+        assert_eq!(das.take_shifts(start + delay + repeat * 4), Some((1, 5)));
+    }
+
+    #[test]
+    fn releasing_the_old_direction_keeps_the_new_one() {
+        let start = Instant::now();
+        let mut das = AutoShift::new(DAS_DELAY, ARR);
+        das.press(-1, start);
+        das.press(1, start);
+        das.release(-1);
+        assert_eq!(das.take_shifts(start + DAS_DELAY), Some((1, 1)));
+    }
+
+    #[test]
+    fn gravity_catches_up_on_every_missed_interval_without_drifting() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(30);
+        let mut gravity = GravityClock::new(start);
+        assert_eq!(gravity.take_drops(interval, start + interval / 2), 0);
+        // 한 프레임이 1초 늦게 와도 33칸을 내리고 남은 10ms는 다음 칸으로 넘긴다
+        assert_eq!(
+            gravity.take_drops(interval, start + Duration::from_secs(1)),
+            33
+        );
+        assert_eq!(
+            gravity.until_next(interval, start + Duration::from_secs(1)),
+            Duration::from_millis(20)
+        );
+        assert_eq!(
+            gravity.take_drops(interval, start + Duration::from_millis(1020)),
+            1
+        );
+
+        gravity.reset(start + Duration::from_secs(2));
+        assert_eq!(
+            gravity.take_drops(interval, start + Duration::from_millis(2029)),
+            0
+        );
+    }
+
+    #[test]
+    fn soft_drop_repeats_until_released() {
+        let start = Instant::now();
+        let mut soft = SoftDropRepeat::new(SOFT_DROP_INTERVAL, true);
+        soft.press(start);
+        assert_eq!(soft.take_drops(start + SOFT_DROP_INTERVAL / 2), 0);
+        assert_eq!(soft.take_drops(start + SOFT_DROP_INTERVAL * 3), 3);
+        // 다시 들어온 눌림은 간격을 처음부터 세지 않는다
+        soft.press(start + SOFT_DROP_INTERVAL * 3);
+        assert_eq!(soft.take_drops(start + SOFT_DROP_INTERVAL * 4), 1);
+
+        soft.release();
+        assert_eq!(soft.take_drops(start + SOFT_DROP_INTERVAL * 10), 0);
+    }
+
+    #[test]
+    fn soft_drop_multiplier_follows_gravity_down_to_a_minimum() {
+        assert_eq!(soft_drop_interval(1000, 20), Duration::from_millis(50));
+        assert_eq!(soft_drop_interval(100, 20), MIN_SOFT_DROP_INTERVAL);
+        assert_eq!(soft_drop_interval(300, 0), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn initial_actions_hold_first_and_ignore_other_keys() {
+        let mut initial = InitialActions::new(true);
+        initial.press(Input::RotateCw);
+        initial.press(Input::Left);
+        initial.press(Input::Hold);
+        initial.press(Input::RotateCw);
+        assert_eq!(initial.inputs(), [Input::Hold, Input::RotateCw]);
+        initial.release(Input::Hold);
+        assert_eq!(initial.inputs(), [Input::RotateCw]);
+
+        // 키 떼기를 모르면 눌린 채인지 알 수 없으니 잡지 않는다
+        let mut off = InitialActions::new(false);
+        off.press(Input::RotateCw);
+        assert!(off.inputs().is_empty());
+    }
+
+    #[test]
+    fn key_repeats_count_as_presses_only_without_release_events() {
+        use KeyEventKind::*;
+        assert_eq!(key_kind(Press, true), Some(Press));
+        assert_eq!(key_kind(Release, true), Some(Release));
+        assert_eq!(key_kind(Repeat, true), None);
+        // 떼기를 모르는 터미널: 반복이 곧 눌림이고, 어쩌다 온 떼기는 버린다
+        assert_eq!(key_kind(Press, false), Some(Press));
+        assert_eq!(key_kind(Repeat, false), Some(Press));
+        assert_eq!(key_kind(Release, false), None);
+    }
+
+    #[test]
+    fn repeated_presses_do_not_restart_the_das_charge() {
+        let start = Instant::now();
+        let mut das = AutoShift::new(DAS_DELAY, ARR);
+        das.press(-1, start);
+        assert!(das.holds(-1) && !das.holds(1));
+        // 같은 쪽 눌림이 다시 와도 호출한 쪽이 press를 부르지 않으니 충전은 이어진다
+        assert_eq!(das.take_shifts(start + DAS_DELAY), Some((-1, 1)));
+        das.release(-1);
+        assert!(!das.holds(-1));
+    }
+
+    #[test]
+    fn soft_drop_stops_without_presses_when_release_is_unsupported() {
+        let start = Instant::now();
+        let mut soft = SoftDropRepeat::new(SOFT_DROP_INTERVAL, false);
+        soft.press(start);
+        assert_eq!(soft.take_drops(start + SOFT_DROP_INTERVAL * 2), 2);
+        assert_eq!(soft.take_drops(start + SOFT_DROP_TIMEOUT), 0);
+        assert_eq!(soft.take_drops(start + SOFT_DROP_TIMEOUT * 2), 0);
+    }
+}
@@ -0,0 +1,552 @@
+use constris::Input;
+use crossterm::event::KeyCode;
+use std::collections::HashMap;
+
+/// 키에 묶을 수 있는 게임 동작
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    SonicDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+    Peek,
+    Undo,
+    CyclePiece,
+    Pause,
+    CycleTheme,
+    ToggleGhost,
+    ToggleGrid,
+    ToggleMute,
+    Restart,
+    SaveReplay,
+    SaveGame,
+    ToggleDebug,
+    ToggleRuler,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 23] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::SonicDrop,
+        Action::HardDrop,
+        Action::RotateCw,
+        Action::RotateCcw,
+        Action::Rotate180,
+        Action::Hold,
+        Action::Peek,
+        Action::Undo,
+        Action::CyclePiece,
+        Action::Pause,
+        Action::CycleTheme,
+        Action::ToggleGhost,
+        Action::ToggleGrid,
+        Action::ToggleMute,
+        Action::Restart,
+        Action::SaveReplay,
+        Action::SaveGame,
+        Action::ToggleDebug,
+        Action::ToggleRuler,
+        Action::Quit,
+    ];
+
+    /// 게임을 움직이는 동작이면 그에 해당하는 입력
+    pub fn input(self) -> Option<Input> {
+        match self {
+            Action::MoveLeft => Some(Input::Left),
+            Action::MoveRight => Some(Input::Right),
+            Action::SoftDrop => Some(Input::SoftDrop),
+            Action::SonicDrop => Some(Input::SonicDrop),
+            Action::HardDrop => Some(Input::HardDrop),
+            Action::RotateCw => Some(Input::RotateCw),
+            Action::RotateCcw => Some(Input::RotateCcw),
+            Action::Rotate180 => Some(Input::Rotate180),
+            Action::Peek => Some(Input::Peek),
+            Action::Hold => Some(Input::Hold),
+            Action::Undo => Some(Input::Undo),
+            Action::CyclePiece => Some(Input::Cycle),
+            Action::Pause
+            | Action::CycleTheme
+            | Action::ToggleGhost
+            | Action::ToggleGrid
+            | Action::ToggleMute
+            | Action::Restart
+            | Action::SaveReplay
+            | Action::SaveGame
+            | Action::ToggleDebug
+            | Action::ToggleRuler
+            | Action::Quit => None,
+        }
+    }
+
+    /// 설정 파일 `[keys]` 표에서 쓰는 이름
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::SonicDrop => "sonic_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateCw => "rotate_cw",
+            Action::RotateCcw => "rotate_ccw",
+            Action::Rotate180 => "rotate_180",
+            Action::Hold => "hold",
+            Action::Peek => "peek",
+            Action::Undo => "undo",
+            Action::CyclePiece => "cycle_piece",
+            Action::Pause => "pause",
+            Action::CycleTheme => "theme",
+            Action::ToggleGhost => "ghost",
+            Action::ToggleGrid => "grid",
+            Action::ToggleMute => "mute",
+            Action::Restart => "restart",
+            Action::SaveReplay => "save_replay",
+            Action::SaveGame => "save_game",
+            Action::ToggleDebug => "debug",
+            Action::ToggleRuler => "ruler",
+            Action::Quit => "quit",
+        }
+    }
+
+    fn from_config_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.config_name() == name)
+    }
+
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Action::MoveLeft => vec![KeyCode::Left],
+            Action::MoveRight => vec![KeyCode::Right],
+            Action::SoftDrop => vec![KeyCode::Down],
+            Action::SonicDrop => vec![KeyCode::Char('s')],
+            Action::HardDrop => vec![KeyCode::Char(' ')],
+            Action::RotateCw => vec![KeyCode::Up, KeyCode::Char('z')],
+            Action::RotateCcw => vec![KeyCode::Char('x')],
+            Action::Rotate180 => vec![KeyCode::Char('a')],
+            Action::Hold => vec![KeyCode::Char('c')],
+            Action::Peek => vec![KeyCode::Char('e')],
+            Action::Undo => vec![KeyCode::Char('u')],
+            Action::CyclePiece => vec![KeyCode::Char('n')],
+            Action::Pause => vec![KeyCode::Char('p')],
+            Action::CycleTheme => vec![KeyCode::Char('t')],
+            Action::ToggleGhost => vec![KeyCode::Char('g')],
+            Action::ToggleGrid => vec![KeyCode::Char('b')],
+            Action::ToggleMute => vec![KeyCode::Char('m')],
+            Action::Restart => vec![KeyCode::Char('r')],
+            Action::SaveReplay => vec![KeyCode::F(2)],
+            Action::SaveGame => vec![KeyCode::F(5)],
+            Action::ToggleDebug => vec![KeyCode::F(3)],
+            Action::ToggleRuler => vec![KeyCode::F(4)],
+            Action::Quit => vec![KeyCode::Char('q'), KeyCode::Esc],
+        }
+    }
+}
+
+/// 동작별 키 목록. 글자 키는 대소문자를 구분하지 않는다.
+#[derive(Clone)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Vec<KeyCode>>,
+    /// 켜면 좌우 이동 키가 서로 반대쪽으로 민다(설정 파일의 mirror_controls).
+    /// 묶인 키는 그대로라 바꾼 키도 함께 뒤집힌다.
+    pub mirrored: bool,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = Action::ALL
+            .into_iter()
+            .map(|action| (action, action.default_keys()))
+            .collect();
+        Self {
+            keys,
+            mirrored: false,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// 설정 파일의 `동작 이름 → 키 이름 목록`으로 바인딩을 만든다.
+    ///
+    /// 모르는 동작/키 이름이나 다른 동작과 겹치는 키가 있으면 그 동작은 기본값으로
+    /// 되돌리고, 사용자에게 보여 줄 경고를 함께 돌려준다.
+    pub fn from_names(names: &HashMap<String, Vec<String>>) -> (Self, Vec<String>) {
+        let mut bindings = Self::default();
+        let mut warnings = Vec::new();
+
+        let mut sorted: Vec<_> = names.iter().collect();
+        sorted.sort();
+        for (action_name, key_names) in sorted {
+            let Some(action) = Action::from_config_name(action_name) else {
+                warnings.push(format!("unknown action '{action_name}' ignored"));
+                continue;
+            };
+            let parsed: Result<Vec<KeyCode>, String> =
+                key_names.iter().map(|name| parse_key(name)).collect();
+            match parsed {
+                Ok(keys) if !keys.is_empty() => {
+                    bindings.keys.insert(action, keys);
+                }
+                Ok(_) => warnings.push(format!("{action_name}: no keys given, using default")),
+                Err(msg) => warnings.push(format!("{action_name}: {msg}, using default")),
+            }
+        }
+
+        // 같은 키가 여러 동작에 묶여 있으면 사용자가 바꾼 쪽을 기본값으로 되돌린다
+        for action in Action::ALL {
+            let clash = bindings.keys[&action].iter().find_map(|key| {
+                Action::ALL
+                    .into_iter()
+                    .find(|&other| other != action && bindings.keys[&other].contains(key))
+            });
+            if let Some(other) = clash
+                && names.contains_key(action.config_name())
+            {
+                warnings.push(format!(
+                    "{}: key also bound to {}, using default",
+                    action.config_name(),
+                    other.config_name()
+                ));
+                bindings.keys.insert(action, action.default_keys());
+            }
+        }
+
+        (bindings, warnings)
+    }
+
+    /// 대전의 2P 키(IJKL 쪽). 1P 기본 키와 겹치지 않게 골랐고, 설정 파일로 바꾸지 않는다.
+    /// 여기 없는 동작은 키가 없다.
+    pub fn player_two() -> Self {
+        let keys = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = match action {
+                    Action::MoveLeft => vec![KeyCode::Char('j')],
+                    Action::MoveRight => vec![KeyCode::Char('l')],
+                    Action::SoftDrop => vec![KeyCode::Char('k')],
+                    Action::HardDrop => vec![KeyCode::Char('h')],
+                    Action::RotateCw => vec![KeyCode::Char('i')],
+                    Action::Hold => vec![KeyCode::Char('o')],
+                    _ => Vec::new(),
+                };
+                (action, keys)
+            })
+            .collect();
+        Self {
+            keys,
+            mirrored: false,
+        }
+    }
+
+    /// `other`에도 묶인 키가 있어 대전 중에 가려지는 동작들
+    pub fn shadowed_by(&self, other: &KeyBindings) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| {
+                self.keys[action]
+                    .iter()
+                    .any(|&key| other.bound_action(key).is_some())
+            })
+            .collect()
+    }
+
+    /// 눌린 키가 하는 동작. 좌우를 뒤집었으면 이동 방향도 뒤집는다.
+    pub fn action_for(&self, code: KeyCode) -> Option<Action> {
+        self.bound_action(code).map(|action| self.mirror(action))
+    }
+
+    /// 눌린 키에 묶인 동작(설정 파일에 적힌 그대로)
+    fn bound_action(&self, code: KeyCode) -> Option<Action> {
+        let code = normalize(code);
+        Action::ALL
+            .into_iter()
+            .find(|action| self.keys[action].contains(&code))
+    }
+
+    /// 좌우를 뒤집었으면 `action`의 키가 실제로 하는 동작
+    fn mirror(&self, action: Action) -> Action {
+        match action {
+            Action::MoveLeft if self.mirrored => Action::MoveRight,
+            Action::MoveRight if self.mirrored => Action::MoveLeft,
+            other => other,
+        }
+    }
+
+    /// `action`의 키를 `code` 하나로 바꾼다. 다른 동작에 묶인 키거나 설정 파일에 적을 수
+    /// 없는 키면 바꾸지 않고 사용자에게 보여 줄 이유를 돌려준다.
+    pub fn rebind(&mut self, action: Action, code: KeyCode) -> Result<(), String> {
+        let code = normalize(code);
+        if key_name(code).is_none() {
+            return Err(format!("{} can't be bound", key_label(code)));
+        }
+        if let Some(other) = self.bound_action(code).filter(|&other| other != action) {
+            return Err(format!(
+                "{} is used by {}",
+                key_label(code),
+                other.config_name()
+            ));
+        }
+        self.keys.insert(action, vec![code]);
+        Ok(())
+    }
+
+    /// 설정 파일에 적을 `action`의 키 이름들
+    pub fn names(&self, action: Action) -> Vec<String> {
+        self.keys[&action]
+            .iter()
+            .filter_map(|&key| key_name(key))
+            .collect()
+    }
+
+    /// 도움말에 보여 줄 키 이름(여러 개면 `/`로 잇는다)
+    pub fn label(&self, action: Action) -> String {
+        let labels: Vec<String> = self.keys[&action]
+            .iter()
+            .map(|&key| key_label(key))
+            .collect();
+        labels.join("/")
+    }
+
+    /// 보드 아래에 보여 줄 조작 안내(이동/드롭, 회전/표시, 기타). 키가 없는 동작은 뺀다.
+    pub fn help_lines(&self) -> [String; 3] {
+        let line = |items: &[(Action, &str)]| {
+            let parts: Vec<String> = items
+                .iter()
+                .map(|&(action, name)| (self.label(action), name))
+                .filter(|(label, _)| !label.is_empty())
+                .map(|(label, name)| format!("{label} {name}"))
+                .collect();
+            format!("  {}", parts.join("  "))
+        };
+        // 좌우를 뒤집었으면 왼쪽으로 미는 키(move_right에 묶인 키)가 먼저
+        let moves = format!(
+            "{}{}",
+            self.label(self.mirror(Action::MoveLeft)),
+            self.label(self.mirror(Action::MoveRight))
+        );
+        [
+            format!(
+                "  {moves} Move{}",
+                line(&[
+                    (Action::SoftDrop, "Soft"),
+                    (Action::SonicDrop, "Sonic"),
+                    (Action::HardDrop, "Hard"),
+                    (Action::Hold, "Hold"),
+                ])
+            ),
+            line(&[
+                (Action::RotateCw, "Rotate"),
+                (Action::RotateCcw, "CCW"),
+                (Action::Rotate180, "180"),
+                (Action::CycleTheme, "Theme"),
+                (Action::ToggleGhost, "Ghost"),
+                (Action::ToggleGrid, "Grid"),
+            ]),
+            line(&[
+                (Action::Peek, "Peek"),
+                (Action::Pause, "Pause"),
+                (Action::ToggleMute, "Mute"),
+                (Action::Restart, "Restart"),
+                (Action::SaveReplay, "Save"),
+                (Action::Quit, "Quit"),
+            ]),
+        ]
+    }
+}
+
+fn normalize(code: KeyCode) -> KeyCode {
+    match code {
+        KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+        other => other,
+    }
+}
+
+/// 설정 파일의 키 이름을 읽는다(`Left`, `Space`, `Esc`, `F1`, 한 글자 등).
+pub fn parse_key(name: &str) -> Result<KeyCode, String> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        lower => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c.to_ascii_lowercase()),
+                _ => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{name}'")),
+                },
+            }
+        }
+    };
+    Ok(key)
+}
+
+/// `parse_key`가 다시 읽을 수 있는 키 이름. 설정 파일에 적을 수 없는 키면 없음.
+fn key_name(key: KeyCode) -> Option<String> {
+    let name = match key {
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::F(n @ 1..=12) => format!("F{n}"),
+        KeyCode::Char(c) if !c.is_control() && !c.is_whitespace() => c.to_string(),
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Left => "\u{2190}".to_string(),
+        KeyCode::Right => "\u{2192}".to_string(),
+        KeyCode::Up => "\u{2191}".to_string(),
+        KeyCode::Down => "\u{2193}".to_string(),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(action, keys)| {
+                let keys = keys.iter().map(|k| k.to_string()).collect();
+                (action.to_string(), keys)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rebinds_actions_case_insensitively() {
+        let (keys, warnings) =
+            KeyBindings::from_names(&names(&[("move_left", &["j"]), ("move_right", &["L"])]));
+        assert!(warnings.is_empty());
+        assert_eq!(keys.action_for(KeyCode::Char('J')), Some(Action::MoveLeft));
+        assert_eq!(keys.action_for(KeyCode::Char('l')), Some(Action::MoveRight));
+        assert_eq!(keys.action_for(KeyCode::Left), None);
+        assert_eq!(keys.action_for(KeyCode::Up), Some(Action::RotateCw));
+    }
+
+    #[test]
+    fn unknown_or_duplicate_keys_fall_back_to_defaults() {
+        let (keys, warnings) = KeyBindings::from_names(&names(&[
+            ("hard_drop", &["Hyper"]),
+            ("pause", &["q"]),
+            ("teleport", &["t"]),
+        ]));
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(keys.action_for(KeyCode::Char(' ')), Some(Action::HardDrop));
+        assert_eq!(keys.action_for(KeyCode::Char('p')), Some(Action::Pause));
+        assert_eq!(keys.action_for(KeyCode::Char('q')), Some(Action::Quit));
+    }
+
+    #[test]
+    fn rebinding_rejects_keys_owned_by_other_actions() {
+        let mut keys = KeyBindings::default();
+        assert_eq!(keys.rebind(Action::Hold, KeyCode::Char('V')), Ok(()));
+        assert_eq!(keys.action_for(KeyCode::Char('v')), Some(Action::Hold));
+        assert_eq!(keys.action_for(KeyCode::Char('c')), None);
+        assert_eq!(keys.names(Action::Hold), ["v"]);
+
+        // 다른 동작의 키, 설정 파일에 적을 수 없는 키
+        let err = keys.rebind(Action::Hold, KeyCode::Char('z')).unwrap_err();
+        assert_eq!(err, "Z is used by rotate_cw");
+        assert!(keys.rebind(Action::Hold, KeyCode::Home).is_err());
+        assert_eq!(keys.names(Action::Hold), ["v"]);
+        // 자기 키 중 하나만 남기는 것은 된다
+        assert_eq!(keys.rebind(Action::RotateCw, KeyCode::Char('z')), Ok(()));
+        assert_eq!(keys.action_for(KeyCode::Up), None);
+
+        for key in [
+            KeyCode::Char(' '),
+            KeyCode::F(4),
+            KeyCode::Left,
+            KeyCode::Char('k'),
+        ] {
+            assert_eq!(parse_key(&key_name(key).unwrap()), Ok(key));
+        }
+    }
+
+    #[test]
+    fn mirrored_controls_swap_left_and_right_after_rebinding() {
+        let mut keys = KeyBindings {
+            mirrored: true,
+            ..KeyBindings::default()
+        };
+        // 왼쪽 화살표가 오른쪽으로 민다
+        let action = keys.action_for(KeyCode::Left).unwrap();
+        assert_eq!(action, Action::MoveRight);
+        let mut game = constris::Game::with_seed(1);
+        let x = game.current.x;
+        game.apply(action.input().unwrap());
+        assert_eq!(game.current.x, x + 1);
+
+        // 바꾼 키도 뒤집히고, 묶인 키로 겹침을 검사한다
+        assert_eq!(keys.rebind(Action::MoveLeft, KeyCode::Char('j')), Ok(()));
+        assert_eq!(keys.action_for(KeyCode::Char('j')), Some(Action::MoveRight));
+        assert_eq!(keys.names(Action::MoveLeft), ["j"]);
+        assert!(keys.rebind(Action::MoveLeft, KeyCode::Right).is_err());
+        assert!(keys.help_lines()[0].starts_with("  \u{2192}J Move"));
+
+        keys.mirrored = false;
+        assert_eq!(keys.action_for(KeyCode::Char('j')), Some(Action::MoveLeft));
+    }
+
+    #[test]
+    fn help_leaves_out_unbound_actions() {
+        let [first, _, third] = KeyBindings::default().help_lines();
+        assert_eq!(
+            first,
+            "  \u{2190}\u{2192} Move  \u{2193} Soft  S Sonic  Space Hard  C Hold"
+        );
+        assert!(third.ends_with("R Restart  F2 Save  Q/Esc Quit"));
+
+        let two = KeyBindings::player_two();
+        assert_eq!(two.action_for(KeyCode::Char('I')), Some(Action::RotateCw));
+        assert_eq!(two.action_for(KeyCode::Char('q')), None);
+        assert_eq!(
+            two.help_lines(),
+            [
+                "  JL Move  K Soft  H Hard  O Hold".to_string(),
+                "  I Rotate".to_string(),
+                "  ".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn player_two_keys_leave_player_one_defaults_alone() {
+        let two = KeyBindings::player_two();
+        assert!(KeyBindings::default().shadowed_by(&two).is_empty());
+
+        // 1P가 2P 키로 바꿔 놓았으면 대전 중에 가려진다고 알려 준다
+        let mut one = KeyBindings::default();
+        one.rebind(Action::Hold, KeyCode::Char('o')).unwrap();
+        assert_eq!(one.shadowed_by(&two), [Action::Hold]);
+    }
+}
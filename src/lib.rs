@@ -0,0 +1,42 @@
+//! Constris 게임 로직.
+//!
+//! 보드, 피스, 게임 상태를 터미널과 무관하게 다룬다. 렌더링과 입력 처리는
+//! 바이너리(`main.rs`)가 담당하므로, 이 크레이트만 가져다 시뮬레이션이나
+//! 테스트를 작성할 수 있다.
+
+mod board;
+mod bot;
+mod game;
+mod gravity;
+mod mode;
+mod piece;
+mod randomizer;
+mod replay;
+mod save;
+mod scoring;
+
+pub use board::{Block, Board, Cell};
+pub use bot::plan_moves;
+pub use game::{
+    DropTrail, EndReason, Game, GameConfig, GameEvent, HARD_DROP_LOCKOUT, LOCK_DELAY,
+    MAX_LOCK_RESETS, MAX_PREVIEW, MAX_START_LEVEL, PEEK_PENALTY, REWIND_PENALTY, REWIND_PIECES,
+    ScorePopup, SoftDropLock, TSpin,
+};
+pub use gravity::{GravityCurve, MAX_SPEED_LEVEL};
+pub use mode::{CHEESE_INTERVAL, GameMode, MARATHON_LINES, SPRINT_LINES, ULTRA_TIME};
+pub use piece::{
+    ALL_PIECES, MAX_PIECE_KINDS, MAX_PIECE_SIZE, Piece, PieceDef, PieceKind, PieceSet,
+    install_piece_set, piece_set,
+};
+pub use randomizer::{MAX_BAG_COPIES, Randomizer, RandomizerKind};
+pub use replay::{Input, Replay};
+pub use scoring::ScoringMode;
+
+// 기본 보드 크기(칸 단위)
+pub const BOARD_W: usize = 10;
+pub const BOARD_H: usize = 20;
+// 고를 수 있는 보드 크기. 가장 긴 I 피스가 눕고 설 수 있어야 한다.
+pub const MIN_BOARD_W: usize = 4;
+pub const MAX_BOARD_W: usize = 30;
+pub const MIN_BOARD_H: usize = 4;
+pub const MAX_BOARD_H: usize = 40;
@@ -1,669 +1,472 @@
-use crossterm::{
-    cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
-    execute, queue,
-    style::{self, Color, Stylize},
-    terminal::{self, ClearType},
+mod app;
+mod config;
+mod debug;
+mod error;
+mod input;
+mod keys;
+mod menu;
+mod net;
+mod pieces;
+mod recording;
+mod render;
+mod sound;
+mod terminal;
+mod theme;
+
+use app::{App, SAVE_FILE, game_config};
+use clap::Parser;
+use config::Config;
+use constris::{
+    BOARD_H, BOARD_W, Game, GameConfig, GameMode, GravityCurve, MAX_BOARD_H, MAX_BOARD_W,
+    MAX_PREVIEW, MAX_START_LEVEL, MIN_BOARD_H, MIN_BOARD_W, RandomizerKind, Replay, ScoringMode,
+    install_piece_set,
 };
-use rand::Rng;
-use std::io::{self, Write};
-use std::time::{Duration, Instant};
+use error::{Error, Result};
+use keys::KeyBindings;
+use net::Peer;
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use terminal::TerminalGuard;
+use theme::Theme;
 
-// 보드 크기(칸 단위)
-const BOARD_W: usize = 10;
-const BOARD_H: usize = 20;
-
-// 각 칸은 가로 4문자 × 세로 2줄(터미널에서 정사각형에 가깝게)
-const CELL_W: usize = 4;
-const CELL_H: usize = 2;
-
-// 보드 그리기 시작 y좌표(테두리 내부)
-const BOARD_Y: u16 = 1;
-
-// ── 테트로미노 정의 ─────────────────────────────────────────────────
-
-#[derive(Clone, Copy, PartialEq)]
-enum PieceKind {
-    I,
-    O,
-    T,
-    S,
-    Z,
-    J,
-    L,
-}
-
-const ALL_PIECES: [PieceKind; 7] = [
-    PieceKind::I,
-    PieceKind::O,
-    PieceKind::T,
-    PieceKind::S,
-    PieceKind::Z,
-    PieceKind::J,
-    PieceKind::L,
-];
-
-impl PieceKind {
-    fn color(self) -> Color {
-        match self {
-            PieceKind::I => Color::Cyan,
-            PieceKind::O => Color::Yellow,
-            PieceKind::T => Color::Magenta,
-            PieceKind::S => Color::Green,
-            PieceKind::Z => Color::Red,
-            PieceKind::J => Color::Blue,
-            PieceKind::L => Color::DarkYellow,
-        }
-    }
+// ── 메인 ────────────────────────────────────────────────────────────
 
-    /// 회전 상태 0의 셀 좌표(각 피스는 4x4 그리드 기준).
-    fn cells(self) -> Vec<(i32, i32)> {
-        match self {
-            PieceKind::I => vec![(0, 1), (1, 1), (2, 1), (3, 1)],
-            PieceKind::O => vec![(1, 0), (2, 0), (1, 1), (2, 1)],
-            PieceKind::T => vec![(0, 1), (1, 1), (2, 1), (1, 0)],
-            PieceKind::S => vec![(0, 1), (1, 1), (1, 0), (2, 0)],
-            PieceKind::Z => vec![(0, 0), (1, 0), (1, 1), (2, 1)],
-            PieceKind::J => vec![(0, 0), (0, 1), (1, 1), (2, 1)],
-            PieceKind::L => vec![(2, 0), (0, 1), (1, 1), (2, 1)],
-        }
-    }
+/// 명령줄 인자. 값 검사는 clap이 하고, 앱에는 `Options`로 바꿔 넘긴다.
+#[derive(Parser, Debug)]
+#[command(version, about = "Tetris in the terminal")]
+struct Cli {
+    #[arg(long, default_value_t = GameMode::default(), help = "marathon, sprint, ultra, endless, invisible, cheese, big, practice or zen")]
+    mode: GameMode,
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=MAX_START_LEVEL as i64), help = "Starting level")]
+    level: u32,
+    #[arg(long, default_value_t = GravityCurve::default(), help = "linear, nes or guideline")]
+    gravity: GravityCurve,
+    #[arg(long, default_value_t = ScoringMode::default(), help = "guideline or nes")]
+    scoring: ScoringMode,
+    #[arg(long, help = "Fixed seed for every game (random if omitted)")]
+    seed: Option<u64>,
+    #[arg(long, value_parser = clap::value_parser!(u16).range(MIN_BOARD_W as i64..=MAX_BOARD_W as i64), help = "Board width in cells (default 10)")]
+    width: Option<u16>,
+    #[arg(long, value_parser = clap::value_parser!(u16).range(MIN_BOARD_H as i64..=MAX_BOARD_H as i64), help = "Board height in rows (default 20)")]
+    height: Option<u16>,
+    #[arg(long, value_name = "N", default_value_t = 0, value_parser = clap::value_parser!(u16).range(0..BOARD_H as i64), help = "Start with N garbage rows already at the bottom")]
+    garbage: u16,
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_PREVIEW as i64), help = "Number of next pieces to show")]
+    preview: u8,
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 200,
+        help = "Line clear animation length (0 clears instantly)"
+    )]
+    clear_delay: u64,
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), help = "Soft drop at N times the current gravity speed")]
+    soft_drop_multiplier: Option<u32>,
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Time a landed piece waits before locking (default 500)"
+    )]
+    lock_delay: Option<u64>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Moves or rotations that can restart the lock delay (default 15)"
+    )]
+    lock_resets: Option<u32>,
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Wait before the next piece appears after a lock (default 100, 0 spawns instantly)"
+    )]
+    entry_delay: Option<u64>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Holds allowed per game (default unlimited)"
+    )]
+    hold_limit: Option<u32>,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Times a top-out rewinds a few pieces instead of ending the game"
+    )]
+    rewinds: Option<u32>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "How the next pieces are picked: bag, bag2~bag9, random or tgm (default bag)"
+    )]
+    randomizer: Option<RandomizerKind>,
+    #[arg(long, help = "classic or contrast (default classic)")]
+    theme: Option<Theme>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Read settings from FILE instead of the default config.toml"
+    )]
+    config: Option<PathBuf>,
+    #[arg(long, help = "Draw cells two characters wide instead of four")]
+    narrow: bool,
+    #[arg(long, help = "Draw blocks and borders with ASCII characters only")]
+    ascii: bool,
+    #[arg(long, help = "Hide the ghost piece (does not change the config file)")]
+    no_ghost: bool,
+    #[arg(long, help = "Rotate only in place, without trying wall kicks")]
+    no_kicks: bool,
+    #[arg(long, help = "Put every cheese garbage hole in the same column")]
+    same_garbage_hole: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Play with the pieces defined in a TOML file instead of the seven tetrominoes"
+    )]
+    pieces: Option<PathBuf>,
+    #[arg(long, value_name = "FILE", help = "Play back a saved replay")]
+    replay: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with = "replay",
+        help = "Continue the game saved with F5 (constris.save)"
+    )]
+    resume: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replay", "resume"],
+        help = "Watch a bot play (any key returns to the menu)"
+    )]
+    demo: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replay", "resume", "demo", "mode"],
+        help = "Two players on one keyboard, side by side (endless)"
+    )]
+    versus: bool,
+    #[arg(
+        long,
+        value_name = "ADDR",
+        conflicts_with_all = ["replay", "resume", "demo", "mode", "versus"],
+        help = "Wait for a network versus opponent on ADDR (e.g. 0.0.0.0:7777)"
+    )]
+    host: Option<String>,
+    #[arg(
+        long,
+        value_name = "ADDR",
+        conflicts_with_all = ["replay", "resume", "demo", "mode", "versus", "host"],
+        help = "Join the network versus game hosted at ADDR"
+    )]
+    connect: Option<String>,
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..=1000), help = "Longest wait for input between frames (default 50)")]
+    tick: Option<u64>,
+    #[arg(
+        long,
+        help = "Redraw less often to save CPU and battery (gravity stays on time)"
+    )]
+    low_power: bool,
+    #[arg(long, help = "Allow F3 to toggle the performance overlay")]
+    debug: bool,
 }
 
-#[derive(Clone)]
-struct Piece {
-    kind: PieceKind,
-    cells: Vec<(i32, i32)>,
-    x: i32,
-    y: i32,
+/// 명령줄 옵션
+struct Options {
+    game: GameConfig,
+    // 있으면 모든 판을 이 시드로 시작한다
+    seed: Option<u64>,
+    // 있으면 설정 파일의 theme 대신 쓴다
+    theme: Option<Theme>,
+    // 있으면 설정 파일의 width, height 대신 쓴다
+    width: Option<usize>,
+    height: Option<usize>,
+    // 있으면 기본 위치 대신 이 설정 파일을 읽고 쓴다
+    config: Option<PathBuf>,
+    // 사이드 패널에 보여 줄 다음 피스 수
+    previews: usize,
+    // 줄 제거 애니메이션 시간
+    clear_delay: Duration,
+    // 있으면 설정 파일의 soft_drop_multiplier 대신 쓴다
+    soft_drop_multiplier: Option<u32>,
+    // 있으면 설정 파일의 lock_delay_ms, lock_resets 대신 쓴다
+    lock_delay: Option<Duration>,
+    lock_resets: Option<u32>,
+    // 있으면 설정 파일의 entry_delay_ms 대신 쓴다
+    entry_delay: Option<Duration>,
+    // 있으면 설정 파일의 hold_limit 대신 쓴다
+    hold_limit: Option<u32>,
+    // 있으면 설정 파일의 rewinds 대신 쓴다
+    rewinds: Option<u32>,
+    // 있으면 설정 파일의 randomizer 대신 쓴다
+    randomizer: Option<RandomizerKind>,
+    // 칸을 가로 2문자로 그린다(설정 파일의 narrow와 둘 중 하나만 켜도 된다)
+    narrow: bool,
+    // 블록 문자 대신 `[]`, `#`, `+-|`로 그린다
+    ascii: bool,
+    // 설정 파일과 상관없이 이번 실행에서 고스트를 숨긴다
+    no_ghost: bool,
+    // 벽 킥 없이 제자리 회전만 한다(설정 파일의 wall_kicks = false와 같다)
+    no_kicks: bool,
+    // 쓰레기 줄 구멍을 모두 같은 열에 낸다(설정 파일의 same_garbage_hole = true와 같다)
+    same_garbage_hole: bool,
+    // 기본 테트로미노 대신 쓸 피스 세트 파일
+    pieces: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    // 저장해 둔 게임을 이어 한다
+    resume: bool,
+    // 메뉴 대신 봇이 두는 데모로 시작한다
+    demo: bool,
+    // 메뉴 없이 둘이 하는 대전으로 시작한다(모드는 끝없이)
+    versus: bool,
+    // 네트워크 대전: 이 주소에서 상대를 기다리거나, 이 주소의 호스트에 접속한다
+    host: Option<String>,
+    connect: Option<String>,
+    // 있으면 설정 파일의 tick_ms 대신 쓴다
+    tick: Option<Duration>,
+    // 프레임을 덜 자주 그린다
+    low_power: bool,
+    // F3으로 성능 오버레이를 켤 수 있게 한다
+    debug: bool,
 }
 
-impl Piece {
-    fn new(kind: PieceKind) -> Self {
-        let cells = kind.cells();
-        Self {
-            kind,
-            cells,
-            // 4x4 기준으로 중앙에 스폰
-            x: (BOARD_W as i32 - CELL_W as i32) / 2,
-            // 보드 위에서 시작해 자연스럽게 내려오도록 음수 y
-            y: -1,
-        }
-    }
-
-    // 현재 피스의 절대 좌표 목록
-    fn absolute_cells(&self) -> Vec<(i32, i32)> {
-        self.cells
-            .iter()
-            .map(|&(cx, cy)| (self.x + cx, self.y + cy))
-            .collect()
-    }
-
-    // 시계 방향 90도 회전 좌표
-    fn rotated_cw(&self) -> Vec<(i32, i32)> {
-        if self.kind == PieceKind::O {
-            return self.cells.clone();
+impl From<Cli> for Options {
+    fn from(cli: Cli) -> Self {
+        Options {
+            game: GameConfig {
+                mode: if cli.versus || cli.host.is_some() || cli.connect.is_some() {
+                    GameMode::Endless
+                } else {
+                    cli.mode
+                },
+                start_level: cli.level,
+                gravity: cli.gravity,
+                scoring: cli.scoring,
+                garbage: cli.garbage.into(),
+                // 보드 크기와 랜덤 방법은 설정 파일의 값과 함께 App에서 정한다
+                width: BOARD_W,
+                height: BOARD_H,
+                randomizer: RandomizerKind::default(),
+            },
+            seed: cli.seed,
+            theme: cli.theme,
+            width: cli.width.map(usize::from),
+            height: cli.height.map(usize::from),
+            config: cli.config,
+            previews: cli.preview.into(),
+            clear_delay: Duration::from_millis(cli.clear_delay),
+            soft_drop_multiplier: cli.soft_drop_multiplier,
+            lock_delay: cli.lock_delay.map(Duration::from_millis),
+            lock_resets: cli.lock_resets,
+            entry_delay: cli.entry_delay.map(Duration::from_millis),
+            hold_limit: cli.hold_limit,
+            rewinds: cli.rewinds,
+            randomizer: cli.randomizer,
+            narrow: cli.narrow,
+            ascii: cli.ascii,
+            no_ghost: cli.no_ghost,
+            no_kicks: cli.no_kicks,
+            same_garbage_hole: cli.same_garbage_hole,
+            pieces: cli.pieces,
+            replay: cli.replay,
+            resume: cli.resume,
+            demo: cli.demo,
+            versus: cli.versus,
+            host: cli.host,
+            connect: cli.connect,
+            tick: cli.tick.map(Duration::from_millis),
+            low_power: cli.low_power,
+            debug: cli.debug,
         }
-        let size = if self.kind == PieceKind::I { 4 } else { 3 };
-        self.cells
-            .iter()
-            .map(|&(cx, cy)| (size - 1 - cy, cx))
-            .collect()
     }
 }
 
-// ── 보드 ────────────────────────────────────────────────────────────
-
-type Cell = Option<Color>;
-
-struct Board {
-    grid: [[Cell; BOARD_W]; BOARD_H],
-}
-
-impl Board {
-    fn new() -> Self {
-        Self {
-            grid: [[None; BOARD_W]; BOARD_H],
-        }
-    }
-
-    /// 해당 좌표가 비어 있고 보드 범위를 만족하는지 확인
-    fn is_free(&self, x: i32, y: i32) -> bool {
-        if x < 0 || x >= BOARD_W as i32 {
-            return false;
+fn main() {
+    // 터미널은 run이 끝나기 전에 되돌려 두었으니 메시지가 셸에 그대로 남는다
+    if let Err(err) = run() {
+        // 사용법 오류와 --help, --version은 clap이 알맞은 형식과 종료 코드로 보여 준다
+        if let Error::Usage(err) = err {
+            err.exit();
         }
-        if y >= BOARD_H as i32 {
-            return false;
-        }
-        // 보드 위쪽(y < 0)은 스폰/회전을 위해 허용
-        if y < 0 {
-            return true;
-        }
-        self.grid[y as usize][x as usize].is_none()
-    }
-
-    /// 모든 좌표가 유효한 위치인지 확인
-    fn fits(&self, cells: &[(i32, i32)]) -> bool {
-        cells.iter().all(|&(x, y)| self.is_free(x, y))
-    }
-
-    /// 피스를 보드에 고정
-    fn lock(&mut self, cells: &[(i32, i32)], color: Color) {
-        for &(x, y) in cells {
-            if y >= 0 && y < BOARD_H as i32 && x >= 0 && x < BOARD_W as i32 {
-                self.grid[y as usize][x as usize] = Some(color);
-            }
-        }
-    }
-
-    /// 가득 찬 줄을 제거하고 제거한 줄 수를 반환.
-    fn clear_lines(&mut self) -> u32 {
-        let mut cleared = 0u32;
-        let mut kept: Vec<[Cell; BOARD_W]> = Vec::new();
-        for y in 0..BOARD_H {
-            if self.grid[y].iter().all(|c| c.is_some()) {
-                cleared += 1;
-            } else {
-                kept.push(self.grid[y]);
-            }
-        }
-        // 위에 빈 줄을 채우고, 남은 줄은 아래로 내린다(순서 유지)
-        let empty_count = BOARD_H - kept.len();
-        for y in 0..BOARD_H {
-            if y < empty_count {
-                self.grid[y] = [None; BOARD_W];
-            } else {
-                self.grid[y] = kept[y - empty_count];
-            }
-        }
-        cleared
+        eprintln!("constris: {err}");
+        std::process::exit(err.exit_code());
     }
 }
 
-// ── 게임 상태 ───────────────────────────────────────────────────────
-
-struct Game {
-    board: Board,
-    current: Piece,
-    next: PieceKind,
-    score: u32,
-    lines: u32,
-    level: u32,
-    game_over: bool,
-}
+fn run() -> Result<()> {
+    // 잘못된 인자는 raw mode에 들어가기 전에 알려 주고 끝낸다
+    let options: Options = Cli::try_parse().map_err(Error::Usage)?.into();
 
-impl Game {
-    fn new() -> Self {
-        let mut rng = rand::thread_rng();
-        let kind = ALL_PIECES[rng.gen_range(0..ALL_PIECES.len())];
-        let next = ALL_PIECES[rng.gen_range(0..ALL_PIECES.len())];
-        Self {
-            board: Board::new(),
-            current: Piece::new(kind),
-            next,
-            score: 0,
-            lines: 0,
-            level: 1,
-            game_over: false,
-        }
+    // 피스 세트는 리플레이, 저장 파일, 설정의 피스 글자를 읽기 전에 정해 둔다
+    if let Some(path) = &options.pieces {
+        let set = pieces::load(path)?;
+        install_piece_set(set).expect("no piece is made before the set is installed");
     }
 
-    fn spawn_next(&mut self) {
-        let mut rng = rand::thread_rng();
-        self.current = Piece::new(self.next);
-        self.next = ALL_PIECES[rng.gen_range(0..ALL_PIECES.len())];
-        // 스폰 위치가 막혀 있으면 게임 오버
-        if !self.board.fits(&self.current.absolute_cells()) {
-            self.game_over = true;
-        }
+    // 리플레이 파일과 저장 파일도 화면을 바꾸기 전에 읽어 둔다
+    let replay = options.replay.as_deref().map(load_replay).transpose()?;
+    let resume = options
+        .resume
+        .then(|| load_save(Path::new(SAVE_FILE)))
+        .transpose()?;
+
+    // 설정 파일 경고는 대체 화면에 들어가기 전에 출력해 두어야 종료 후에도 보인다
+    let (config, warnings) = Config::load(options.config.clone());
+    for warning in &warnings {
+        eprintln!("constris: {warning}");
     }
-
-    /// 이동 시도(성공 시 true)
-    fn try_move(&mut self, dx: i32, dy: i32) -> bool {
-        let mut moved = self.current.clone();
-        moved.x += dx;
-        moved.y += dy;
-        if self.board.fits(&moved.absolute_cells()) {
-            self.current = moved;
-            true
-        } else {
-            false
+    // 대전에서는 2P 키가 먼저라 1P가 2P 키로 바꿔 둔 동작은 듣지 않는다
+    if options.versus {
+        for action in config.keys.shadowed_by(&KeyBindings::player_two()) {
+            eprintln!(
+                "constris: {}: key also used by player two, ignored in versus",
+                action.config_name()
+            );
         }
     }
 
-    fn try_rotate(&mut self) -> bool {
-        if self.current.kind == PieceKind::O {
-            return true;
-        }
-        let rotated_cells = self.current.rotated_cw();
-
-        // 벽 킥 오프셋 후보
-        let kicks: &[(i32, i32)] = if self.current.kind == PieceKind::I {
-            &[
-                (0, 0),
-                (-1, 0),
-                (1, 0),
-                (-2, 0),
-                (2, 0),
-                (0, -1),
-                (0, -2),
-            ]
-        } else {
-            &[(0, 0), (-1, 0), (1, 0), (0, -1), (-1, -1), (1, -1)]
-        };
+    // 네트워크 대전 상대도 화면을 바꾸기 전에 기다려 둔다(기다리는 동안 안내가 보이게)
+    let peer = connect_peer(&options, &net::rules(&game_config(&options, &config)))?;
 
-        for &(kx, ky) in kicks {
-            let abs: Vec<(i32, i32)> = rotated_cells
-                .iter()
-                .map(|&(cx, cy)| (self.current.x + cx + kx, self.current.y + cy + ky))
-                .collect();
-            if self.board.fits(&abs) {
-                self.current.cells = rotated_cells;
-                self.current.x += kx;
-                self.current.y += ky;
-                return true;
-            }
-        }
-        false
-    }
-
-    /// 하드 드롭: 내려갈 수 있을 때까지 이동 후 고정
-    fn hard_drop(&mut self) {
-        while self.try_move(0, 1) {}
-        self.lock_and_advance();
-    }
-
-    /// 현재 피스를 고정하고 점수/레벨 갱신 후 다음 피스 스폰
-    fn lock_and_advance(&mut self) {
-        let cells = self.current.absolute_cells();
-        let color = self.current.kind.color();
-        self.board.lock(&cells, color);
-
-        let cleared = self.board.clear_lines();
-        if cleared > 0 {
-            self.lines += cleared;
-            self.score += match cleared {
-                1 => 100 * self.level,
-                2 => 300 * self.level,
-                3 => 500 * self.level,
-                4 => 800 * self.level,
-                _ => 0,
-            };
-            self.level = self.lines / 10 + 1;
-        }
-
-        self.spawn_next();
-    }
-
-    /// 레벨에 따른 낙하 간격(ms).
-    fn drop_interval_ms(&self) -> u64 {
-        let base = 1000u64;
-        let min_interval = 50u64;
-        base.saturating_sub((self.level as u64 - 1) * 80)
-            .max(min_interval)
-    }
+    // 여기부터 guard가 사라질 때까지(패닉 포함) 터미널을 게임 화면으로 쓴다
+    let guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+    let result = App::new(&options, &config, guard.das_enabled(), replay, resume, peer)
+        .and_then(|mut app| app.run(&mut stdout));
+    drop(guard);
 
-    /// 고스트 피스(하드 드롭 그림자) 위치 계산.
-    fn ghost_cells(&self) -> Vec<(i32, i32)> {
-        let mut ghost = self.current.clone();
-        loop {
-            let next_cells: Vec<(i32, i32)> = ghost
-                .cells
-                .iter()
-                .map(|&(cx, cy)| (ghost.x + cx, ghost.y + cy + 1))
-                .collect();
-            if self.board.fits(&next_cells) {
-                ghost.y += 1;
-            } else {
-                break;
-            }
-        }
-        ghost.absolute_cells()
-    }
+    result
 }
 
-// ── 렌더링 ──────────────────────────────────────────────────────────
-
-fn draw(stdout: &mut io::Stdout, game: &Game) -> io::Result<()> {
-    // 화면 상에서의 보드 크기(문자 단위)
-    let board_char_w = BOARD_W * CELL_W; // 20
-    let board_char_h = BOARD_H * CELL_H; // 40
-
-    // 현재 피스와 고스트 좌표
-    let piece_cells = game.current.absolute_cells();
-    let ghost_cells = game.ghost_cells();
-    let piece_color = game.current.kind.color();
-
-    // ── 위쪽 테두리 ──
-    queue!(stdout, cursor::MoveTo(0, 0), style::Print("\u{250c}"))?;
-    for _ in 0..board_char_w {
-        queue!(stdout, style::Print("\u{2500}"))?;
-    }
-    queue!(stdout, style::Print("\u{2510}"))?;
-
-    // ── 보드 영역 ──
-    for row in 0..BOARD_H {
-        for sub in 0..CELL_H {
-            let screen_y = BOARD_Y + (row * CELL_H + sub) as u16;
-            queue!(
-                stdout,
-                cursor::MoveTo(0, screen_y),
-                style::Print("\u{2502}")
-            )?;
-
-            for col in 0..BOARD_W {
-                let (cx, cy) = (col as i32, row as i32);
-
-                let is_piece = piece_cells.contains(&(cx, cy));
-                let is_ghost = !is_piece && ghost_cells.contains(&(cx, cy));
-                let board_color = game.board.grid[row][col];
-
-                if is_piece {
-                    queue!(
-                        stdout,
-                        style::PrintStyledContent("\u{2588}\u{2588}\u{2588}\u{2588}".with(piece_color))
-                    )?;
-                } else if let Some(c) = board_color {
-                    queue!(
-                        stdout,
-                        style::PrintStyledContent("\u{2588}\u{2588}\u{2588}\u{2588}".with(c))
-                    )?;
-                } else if is_ghost {
-                    queue!(
-                        stdout,
-                        style::PrintStyledContent("\u{2591}\u{2591}\u{2591}\u{2591}".with(Color::DarkGrey))
-                    )?;
-                } else if sub == 0 {
-                    queue!(
-                        stdout,
-                        style::PrintStyledContent("  . ".with(Color::DarkGrey))
-                    )?;
-                } else {
-                    queue!(stdout, style::Print("    "))?;
-                }
-            }
-            queue!(stdout, style::Print("\u{2502}"))?;
-
-            // ── 사이드 패널 ──
-            draw_side_panel(stdout, game, screen_y)?;
-        }
+/// `--host`면 상대가 접속할 때까지 기다렸다가 시드를 정해 보내고, `--connect`면 호스트의 시드를 받는다.
+/// 어느 쪽이든 `rules`가 상대와 다르면 대전하지 않는다.
+fn connect_peer(options: &Options, rules: &str) -> Result<Option<(Peer, u64)>> {
+    let net_error = |addr: &str, err: io::Error| Error::Net {
+        addr: addr.to_string(),
+        msg: err.to_string(),
+    };
+    if let Some(addr) = &options.host {
+        let listener = TcpListener::bind(addr).map_err(|e| net_error(addr, e))?;
+        eprintln!("constris: waiting for an opponent on {addr}");
+        let seed = options.seed.unwrap_or_else(rand::random);
+        let peer = Peer::accept(&listener, seed, rules).map_err(|e| net_error(addr, e))?;
+        return Ok(Some((peer, seed)));
     }
-
-    // ── 아래쪽 테두리 ──
-    let bot_y = BOARD_Y + board_char_h as u16;
-    queue!(
-        stdout,
-        cursor::MoveTo(0, bot_y),
-        style::Print("\u{2514}")
-    )?;
-    for _ in 0..board_char_w {
-        queue!(stdout, style::Print("\u{2500}"))?;
+    if let Some(addr) = &options.connect {
+        return Peer::connect(addr, rules)
+            .map(Some)
+            .map_err(|e| net_error(addr, e));
     }
-    queue!(stdout, style::Print("\u{2518}"))?;
-
-    // 조작 안내(보드 아래)
-    let help_y = bot_y + 1;
-    queue!(
-        stdout,
-        cursor::MoveTo(0, help_y),
-        style::Print("  \u{2190}\u{2192} Move  \u{2193} Soft  Space Hard  \u{2191}/Z Rotate  Q Quit")
-    )?;
-
-    stdout.flush()
-}
-
-const PANEL_W: usize = 20;
-
-fn draw_side_panel(
-    stdout: &mut io::Stdout,
-    game: &Game,
-    screen_y: u16,
-) -> io::Result<()> {
-    let panel_x = (BOARD_W * CELL_W) as u16 + 2 + 2; // after right border + gap
-
-    // 보드 상단 기준 라인 인덱스(0부터)
-    let line = screen_y - BOARD_Y;
-
-    queue!(stdout, cursor::MoveTo(panel_x, screen_y))?;
-
-    match line {
-        0 => {
-            queue!(
-                stdout,
-                style::PrintStyledContent(
-                    format!("{:<PANEL_W$}", "NEXT").with(Color::White)
-                )
-            )?;
-        }
-        2..=9 => {
-            let preview_row = ((line - 2) / CELL_H as u16) as i32;
-            let next_cells = game.next.cells();
-            let next_color = game.next.color();
-
-            for pcol in 0..4i32 {
-                if next_cells.contains(&(pcol, preview_row)) {
-                    queue!(
-                        stdout,
-                        style::PrintStyledContent("\u{2588}\u{2588}\u{2588}\u{2588}".with(next_color))
-                    )?;
-                } else {
-                    queue!(stdout, style::Print("    "))?;
-                }
-            }
-            // 남는 칸 채우기(4셀×4문자=16, PANEL_W=20이므로 4문자 추가)
-            queue!(stdout, style::Print("    "))?;
-        }
-        12 => {
-            queue!(
-                stdout,
-                style::PrintStyledContent(
-                    format!("{:<PANEL_W$}", format!("Score: {}", game.score))
-                        .with(Color::White)
-                )
-            )?;
-        }
-        14 => {
-            queue!(
-                stdout,
-                style::PrintStyledContent(
-                    format!("{:<PANEL_W$}", format!("Lines: {}", game.lines))
-                        .with(Color::White)
-                )
-            )?;
-        }
-        16 => {
-            queue!(
-                stdout,
-                style::PrintStyledContent(
-                    format!("{:<PANEL_W$}", format!("Level: {}", game.level))
-                        .with(Color::White)
-                )
-            )?;
-        }
-        _ => {}
-    }
-
-    Ok(())
-}
-
-fn draw_game_over(stdout: &mut io::Stdout, game: &Game) -> io::Result<()> {
-    // 화면 중앙에 GAME OVER 패널 배치
-    let cx = (BOARD_W * CELL_W / 2) as u16;
-    let cy = (BOARD_H * CELL_H / 2) as u16;
-
-    let msg = "  GAME OVER  ";
-    let score_msg = format!("  Score: {}  ", game.score);
-    let quit_msg = "  R Retry  Q Quit  ";
-
-    let w = msg.len().max(score_msg.len()).max(quit_msg.len()) as u16;
-    let left = cx - w / 2;
-
-    queue!(
-        stdout,
-        cursor::MoveTo(left, cy - 1),
-        style::PrintStyledContent(msg.on(Color::Red).with(Color::White)),
-        cursor::MoveTo(left, cy),
-        style::PrintStyledContent(score_msg.on(Color::Red).with(Color::White)),
-        cursor::MoveTo(left, cy + 1),
-        style::PrintStyledContent(quit_msg.on(Color::Red).with(Color::White)),
-    )?;
-    stdout.flush()
+    Ok(None)
 }
 
-// ── 메인 ────────────────────────────────────────────────────────────
-
-fn main() -> io::Result<()> {
-    let mut stdout = io::stdout();
-
-    // 입력 처리와 화면 제어를 위해 raw mode + 대체 화면 진입
-    terminal::enable_raw_mode()?;
-    execute!(
-        stdout,
-        terminal::EnterAlternateScreen,
-        cursor::Hide,
-        terminal::Clear(ClearType::All)
-    )?;
-
-    let result = run_game(&mut stdout);
-
-    // 종료 시 터미널 상태 복구
-    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
-    terminal::disable_raw_mode()?;
-
-    result
+fn load_replay(path: &Path) -> Result<Replay> {
+    let replay_error = |msg| Error::Replay {
+        path: path.to_path_buf(),
+        msg,
+    };
+    let text = fs::read_to_string(path).map_err(|e| replay_error(e.to_string()))?;
+    Replay::parse(&text).map_err(replay_error)
 }
 
-fn run_game(stdout: &mut io::Stdout) -> io::Result<()> {
-    let mut game = Game::new();
-    let mut last_drop = Instant::now();
-
-    loop {
-        // ── 화면 그리기 ──
-        draw(stdout, &game)?;
-
-        if game.game_over {
-            draw_game_over(stdout, &game)?;
-            loop {
-                // 게임 오버 상태에서는 재시작/종료 입력만 처리
-                if event::poll(Duration::from_millis(200))?
-                    && let Event::Key(KeyEvent {
-                        code,
-                        kind: KeyEventKind::Press,
-                        ..
-                    }) = event::read()?
-                {
-                    match code {
-                        KeyCode::Char('r') | KeyCode::Char('R') => {
-                            game = Game::new();
-                            last_drop = Instant::now();
-                            queue!(stdout, terminal::Clear(ClearType::All))?;
-                            break;
-                        }
-                        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
-                            return Ok(());
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-
-        // ── 입력 처리 ──
-        let tick = Duration::from_millis(50);
-        if event::poll(tick)?
-            && let Event::Key(KeyEvent {
-                code,
-                modifiers,
-                kind: KeyEventKind::Press,
-                ..
-            }) = event::read()?
-        {
-            match code {
-                KeyCode::Left => {
-                    game.try_move(-1, 0);
-                }
-                KeyCode::Right => {
-                    game.try_move(1, 0);
-                }
-                KeyCode::Down => {
-                    if !game.try_move(0, 1) {
-                        game.lock_and_advance();
-                    }
-                    last_drop = Instant::now();
-                }
-                KeyCode::Up | KeyCode::Char('z') | KeyCode::Char('Z') => {
-                    game.try_rotate();
-                }
-                KeyCode::Char(' ') => {
-                    game.hard_drop();
-                    last_drop = Instant::now();
-                }
-                KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
-                    return Ok(());
-                }
-                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
-                    return Ok(());
-                }
-                _ => {}
-            }
-        }
-
-        // ── 자동 낙하(중력) ──
-        let interval = Duration::from_millis(game.drop_interval_ms());
-        if last_drop.elapsed() >= interval {
-            if !game.try_move(0, 1) {
-                game.lock_and_advance();
-            }
-            last_drop = Instant::now();
-        }
-    }
+fn load_save(path: &Path) -> Result<Game> {
+    let save_error = |msg| Error::Save {
+        path: path.to_path_buf(),
+        msg,
+    };
+    let text = fs::read_to_string(path).map_err(|e| save_error(e.to_string()))?;
+    Game::load(&text).map_err(save_error)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashSet;
+    use clap::CommandFactory;
 
     #[test]
-    fn clears_single_full_row() {
-        // 한 줄이 가득 찼을 때 정확히 1줄 제거되는지 확인
-        let mut board = Board::new();
-        let y = BOARD_H - 1;
-        for x in 0..BOARD_W {
-            board.grid[y][x] = Some(Color::Red);
-        }
-
-        let cleared = board.clear_lines();
-        assert_eq!(cleared, 1);
-        assert!(board.grid[y].iter().all(|c| c.is_none()));
-    }
-
-    #[test]
-    fn clears_multiple_rows_and_compacts() {
-        // 여러 줄 제거 후 아래로 내려오는지 확인
-        let mut board = Board::new();
-        let bottom = BOARD_H - 1;
-        let above = BOARD_H - 2;
-        for x in 0..BOARD_W {
-            board.grid[bottom][x] = Some(Color::Blue);
-            board.grid[above][x] = Some(Color::Blue);
+    fn parses_options_and_rejects_out_of_range_values() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from([
+            "constris",
+            "--mode",
+            "zen",
+            "--seed",
+            "7",
+            "--width",
+            "6",
+            "--no-ghost",
+            "--no-kicks",
+            "--lock-delay",
+            "1000",
+        ])
+        .unwrap();
+        let options = Options::from(cli);
+        assert_eq!(options.game.mode, GameMode::Zen);
+        assert_eq!((options.width, options.height), (Some(6), None));
+        assert_eq!(options.theme, None);
+        assert_eq!(options.seed, Some(7));
+        assert!(options.no_ghost);
+        assert!(options.no_kicks);
+        assert_eq!(options.lock_delay, Some(Duration::from_secs(1)));
+        assert_eq!(options.lock_resets, None);
+        assert_eq!(options.hold_limit, None);
+        assert_eq!(options.tick, None);
+        assert!(!options.low_power);
+        let limited = Cli::try_parse_from(["constris", "--hold-limit", "5"]).unwrap();
+        assert_eq!(Options::from(limited).hold_limit, Some(5));
+        let forgiving = Cli::try_parse_from(["constris", "--rewinds", "2"]).unwrap();
+        assert_eq!(Options::from(forgiving).rewinds, Some(2));
+        let tgm = Cli::try_parse_from(["constris", "--randomizer", "tgm"]).unwrap();
+        assert_eq!(Options::from(tgm).randomizer, Some(RandomizerKind::Tgm));
+        // --garbage 5면 맨 아래 다섯 줄만 차 있고 줄마다 구멍이 하나
+        let handicap = Cli::try_parse_from(["constris", "--garbage", "5"]).unwrap();
+        let game = Game::with_config(Options::from(handicap).game);
+        let filled: Vec<_> = game
+            .board
+            .grid
+            .iter()
+            .filter(|row| row.iter().any(Option::is_some))
+            .collect();
+        assert_eq!(filled.len(), 5);
+        assert!(
+            filled
+                .iter()
+                .all(|row| row.iter().filter(|c| c.is_none()).count() == 1)
+        );
+        let saving = Cli::try_parse_from(["constris", "--low-power", "--tick", "80"]).unwrap();
+        let saving = Options::from(saving);
+        assert!(saving.low_power);
+        assert_eq!(saving.tick, Some(Duration::from_millis(80)));
+
+        for bad in [
+            ["--level", "16"],
+            ["--width", "3"],
+            ["--preview", "0"],
+            ["--garbage", "20"],
+            ["--randomizer", "bag10"],
+            ["--soft-drop-multiplier", "0"],
+            ["--tick", "0"],
+        ] {
+            let err = Cli::try_parse_from(["constris", bad[0], bad[1]]).unwrap_err();
+            assert_eq!(err.exit_code(), 2);
         }
-        board.grid[BOARD_H - 3][0] = Some(Color::Green);
-
-        let cleared = board.clear_lines();
-        assert_eq!(cleared, 2);
-        assert!(board.grid[bottom][0].is_some());
-        assert!(board.grid[above].iter().all(|c| c.is_none()));
-    }
-
-    #[test]
-    fn rotates_t_piece_clockwise() {
-        // T피스 회전 결과가 기대 좌표와 일치하는지 확인
-        let piece = Piece::new(PieceKind::T);
-        let rotated = piece.rotated_cw();
-        let actual: HashSet<(i32, i32)> = rotated.into_iter().collect();
-        let expected: HashSet<(i32, i32)> =
-            [(1, 0), (1, 1), (1, 2), (2, 1)].into_iter().collect();
-        assert_eq!(actual, expected);
+        let both = Cli::try_parse_from(["constris", "--resume", "--replay", "a.replay"]);
+        assert!(both.is_err());
+        assert!(Cli::try_parse_from(["constris", "--demo", "--resume"]).is_err());
+
+        let versus = Options::from(Cli::try_parse_from(["constris", "--versus"]).unwrap());
+        assert!(versus.versus);
+        assert_eq!(versus.game.mode, GameMode::Endless);
+        assert!(Cli::try_parse_from(["constris", "--versus", "--mode", "sprint"]).is_err());
+        let guest = Cli::try_parse_from(["constris", "--connect", "10.0.0.2:7777"]).unwrap();
+        assert_eq!(Options::from(guest).game.mode, GameMode::Endless);
+        let both = [
+            "constris",
+            "--host",
+            "0.0.0.0:7777",
+            "--connect",
+            "10.0.0.2:7777",
+        ];
+        assert!(Cli::try_parse_from(both).is_err());
     }
 }
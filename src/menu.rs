@@ -0,0 +1,346 @@
+use crate::keys::{Action, KeyBindings};
+use crate::theme::Theme;
+use constris::{GameConfig, GameMode, GravityCurve, MAX_START_LEVEL, ScoringMode};
+use crossterm::event::KeyCode;
+
+/// 시작 메뉴의 줄
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Row {
+    Mode,
+    Level,
+    Gravity,
+    Scoring,
+    Theme,
+    Controls,
+    Start,
+}
+
+const ROWS: [Row; 7] = [
+    Row::Mode,
+    Row::Level,
+    Row::Gravity,
+    Row::Scoring,
+    Row::Theme,
+    Row::Controls,
+    Row::Start,
+];
+
+// 값이 바뀌어도 패널 폭이 그대로여야 이전 글자가 남지 않는다
+const MENU_W: usize = 26;
+
+// 조작 화면에 한 번에 보여 주는 동작 수(나머지는 고른 줄을 따라 넘긴다)와 패널 폭
+const CONTROLS_ROWS: usize = 10;
+const CONTROLS_W: usize = 30;
+
+/// 시작 메뉴: 위아래로 줄을 고르고 좌우로 값을 바꾼다.
+pub struct Menu {
+    pub config: GameConfig,
+    pub theme: Theme,
+    selected: usize,
+    // 조작 화면을 열었으면 그 상태
+    controls: Option<Controls>,
+}
+
+/// 조작 화면에서 바꾼 것(설정 파일에 적어 둘 것)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlsChange {
+    Key(Action),
+    Mirror,
+}
+
+// 조작 화면의 줄 수: 동작들 뒤에 좌우 뒤집기 줄이 하나 더 있다
+const CONTROLS_LINES: usize = Action::ALL.len() + 1;
+
+/// 조작 화면: 동작을 골라 Enter를 누른 뒤 새 키를 누르면 그 키로 바꾼다.
+/// 마지막 줄에서 Enter를 누르면 좌우 이동을 뒤집는다.
+struct Controls {
+    selected: usize,
+    // 다음에 누르는 키를 새 키로 받는 중인지
+    capturing: bool,
+    // 마지막으로 바꾼 결과나 바꾸지 못한 이유
+    message: Option<String>,
+}
+
+impl Menu {
+    /// 명령줄에서 받은 값을 처음 선택값으로 쓴다.
+    pub fn new(config: GameConfig, theme: Theme) -> Self {
+        Self {
+            config,
+            theme,
+            selected: 0,
+            controls: None,
+        }
+    }
+
+    /// Enter를 눌렀다. 조작 줄이면 조작 화면을 열고, 아니면 게임을 시작해야 하므로 true.
+    pub fn select(&mut self) -> bool {
+        if ROWS[self.selected] == Row::Controls {
+            self.controls = Some(Controls {
+                selected: 0,
+                capturing: false,
+                message: None,
+            });
+            return false;
+        }
+        true
+    }
+
+    pub fn in_controls(&self) -> bool {
+        self.controls.is_some()
+    }
+
+    /// 조작 화면에서 누른 키. 위아래로 고르고 Enter로 새 키를 기다리며, Esc는 기다리기를
+    /// 취소하거나 시작 메뉴로 돌아간다. 키나 좌우 뒤집기를 바꿨으면 설정 파일에 적을 그것.
+    pub fn controls_key(
+        &mut self,
+        code: KeyCode,
+        keys: &mut KeyBindings,
+    ) -> Option<ControlsChange> {
+        let controls = self.controls.as_mut()?;
+        // 좌우 뒤집기 줄이면 동작이 없다
+        let action = Action::ALL.get(controls.selected).copied();
+        if controls.capturing
+            && let Some(action) = action
+        {
+            controls.capturing = false;
+            if code == KeyCode::Esc {
+                controls.message = None;
+                return None;
+            }
+            return match keys.rebind(action, code) {
+                Ok(()) => {
+                    controls.message =
+                        Some(format!("{} = {}", action.config_name(), keys.label(action)));
+                    Some(ControlsChange::Key(action))
+                }
+                Err(reason) => {
+                    controls.message = Some(reason);
+                    None
+                }
+            };
+        }
+        let n = CONTROLS_LINES;
+        match code {
+            KeyCode::Up => controls.selected = (controls.selected + n - 1) % n,
+            KeyCode::Down => controls.selected = (controls.selected + 1) % n,
+            KeyCode::Enter if action.is_some() => {
+                controls.capturing = true;
+                controls.message = None;
+            }
+            KeyCode::Enter => {
+                keys.mirrored = !keys.mirrored;
+                controls.message = Some(format!("mirror_controls = {}", on_off(keys.mirrored)));
+                return Some(ControlsChange::Mirror);
+            }
+            KeyCode::Esc | KeyCode::Backspace => self.controls = None,
+            _ => {}
+        }
+        None
+    }
+
+    /// 조작 화면 아래에 바꾼 결과 대신 보여 줄 말(설정 파일에 저장하지 못했을 때)
+    pub fn set_controls_message(&mut self, message: String) {
+        if let Some(controls) = &mut self.controls {
+            controls.message = Some(message);
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = (self.selected + ROWS.len() - 1) % ROWS.len();
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1) % ROWS.len();
+    }
+
+    /// 선택한 줄의 값을 바꾼다(`step`은 -1 또는 1).
+    pub fn adjust(&mut self, step: i32) {
+        match ROWS[self.selected] {
+            Row::Mode => {
+                let modes = GameMode::ALL;
+                let i = modes
+                    .iter()
+                    .position(|&m| m == self.config.mode)
+                    .unwrap_or(0);
+                let n = modes.len() as i32;
+                self.config.mode = modes[(i as i32 + step).rem_euclid(n) as usize];
+            }
+            Row::Level => {
+                let level = self.config.start_level as i32 + step;
+                self.config.start_level = level.clamp(1, MAX_START_LEVEL as i32) as u32;
+            }
+            Row::Gravity => {
+                let curves = GravityCurve::ALL;
+                let i = curves
+                    .iter()
+                    .position(|&c| c == self.config.gravity)
+                    .unwrap_or(0);
+                let n = curves.len() as i32;
+                self.config.gravity = curves[(i as i32 + step).rem_euclid(n) as usize];
+            }
+            Row::Scoring => {
+                let rules = ScoringMode::ALL;
+                let i = rules
+                    .iter()
+                    .position(|&s| s == self.config.scoring)
+                    .unwrap_or(0);
+                let n = rules.len() as i32;
+                self.config.scoring = rules[(i as i32 + step).rem_euclid(n) as usize];
+            }
+            // 테마는 두 가지뿐이라 방향과 상관없이 바꾼다
+            Row::Theme => self.theme = self.theme.next(),
+            Row::Controls | Row::Start => {}
+        }
+    }
+
+    /// 안내 패널에 그릴 줄들(선택한 줄 앞에 `>`)
+    pub fn lines(&self, keys: &KeyBindings) -> Vec<String> {
+        if let Some(controls) = &self.controls {
+            return controls.lines(keys);
+        }
+        let mut lines = vec!["      CONSTRIS      ".to_string(), String::new()];
+        for (i, row) in ROWS.iter().enumerate() {
+            let marker = if i == self.selected { '>' } else { ' ' };
+            let text = match row {
+                Row::Mode => format!("Mode    < {} >", self.config.mode),
+                Row::Level => format!("Level   < {} >", self.config.start_level),
+                Row::Gravity => format!("Gravity < {} >", self.config.gravity),
+                Row::Scoring => format!("Scoring < {} >", self.config.scoring),
+                Row::Theme => format!("Theme   < {} >", self.theme.name()),
+                Row::Controls => "Controls".to_string(),
+                Row::Start => "Start".to_string(),
+            };
+            lines.push(format!(" {marker} {text:<w$}", w = MENU_W - 3));
+        }
+        lines.push(String::new());
+        lines.push("  Enter Start  Q Quit  ".to_string());
+        lines
+    }
+}
+
+impl Controls {
+    fn lines(&self, keys: &KeyBindings) -> Vec<String> {
+        let pad = |text: String| format!("{text:<CONTROLS_W$.CONTROLS_W$}");
+        let mut lines = vec![pad("      CONTROLS".to_string()), pad(String::new())];
+        // 고른 줄이 늘 보이도록 보여 줄 범위를 옮긴다
+        let first = self
+            .selected
+            .saturating_sub(CONTROLS_ROWS - 1)
+            .min(CONTROLS_LINES - CONTROLS_ROWS);
+        for i in (first..CONTROLS_LINES).take(CONTROLS_ROWS) {
+            let marker = if i == self.selected { '>' } else { ' ' };
+            let (name, value) = match Action::ALL.get(i) {
+                Some(action) if i == self.selected && self.capturing => {
+                    (action.config_name(), "...".to_string())
+                }
+                Some(action) => (action.config_name(), keys.label(*action)),
+                None => ("mirror", on_off(keys.mirrored).to_string()),
+            };
+            lines.push(pad(format!(" {marker} {name:<12}{value}")));
+        }
+        lines.push(pad(String::new()));
+        let hint = match (&self.message, self.capturing) {
+            (_, true) => "  Press a key  Esc Cancel".to_string(),
+            (Some(message), false) => format!("  {message}"),
+            (None, false) => "  Enter Change  Esc Back".to_string(),
+        };
+        lines.push(pad(hint));
+        lines
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusts_selected_row_and_clamps_level() {
+        let mut menu = Menu::new(GameConfig::default(), Theme::Classic);
+        menu.adjust(-1);
+        assert_eq!(menu.config.mode, GameMode::Zen);
+
+        menu.down();
+        menu.adjust(-1);
+        assert_eq!(menu.config.start_level, 1);
+        for _ in 0..20 {
+            menu.adjust(1);
+        }
+        assert_eq!(menu.config.start_level, MAX_START_LEVEL);
+
+        menu.down();
+        menu.adjust(-1);
+        assert_eq!(menu.config.gravity, GravityCurve::Guideline);
+
+        menu.down();
+        menu.adjust(1);
+        assert_eq!(menu.config.scoring, ScoringMode::Nes);
+
+        menu.up();
+        menu.up();
+        menu.up();
+        menu.up();
+        assert_eq!(ROWS[menu.selected], Row::Start);
+        assert!(menu.select());
+    }
+
+    #[test]
+    fn controls_capture_the_next_key_and_reject_clashes() {
+        let mut menu = Menu::new(GameConfig::default(), Theme::Classic);
+        let mut keys = KeyBindings::default();
+        while ROWS[menu.selected] != Row::Controls {
+            menu.down();
+        }
+        assert!(!menu.select());
+        assert!(menu.in_controls());
+
+        // 첫 줄(move_left)을 J로 바꾼다
+        assert_eq!(menu.controls_key(KeyCode::Enter, &mut keys), None);
+        assert!(menu.lines(&keys)[2].contains("..."));
+        assert_eq!(
+            menu.controls_key(KeyCode::Char('j'), &mut keys),
+            Some(ControlsChange::Key(Action::MoveLeft))
+        );
+        assert_eq!(keys.action_for(KeyCode::Char('j')), Some(Action::MoveLeft));
+        assert!(menu.lines(&keys).last().unwrap().contains("move_left = J"));
+
+        // 다른 동작의 키는 받지 않고, Esc는 기다리기만 취소한다
+        menu.controls_key(KeyCode::Down, &mut keys);
+        menu.controls_key(KeyCode::Enter, &mut keys);
+        assert_eq!(menu.controls_key(KeyCode::Char('J'), &mut keys), None);
+        assert_eq!(keys.action_for(KeyCode::Right), Some(Action::MoveRight));
+        assert!(
+            menu.lines(&keys)
+                .last()
+                .unwrap()
+                .contains("used by move_left")
+        );
+        menu.controls_key(KeyCode::Enter, &mut keys);
+        assert_eq!(menu.controls_key(KeyCode::Esc, &mut keys), None);
+        assert!(menu.in_controls());
+
+        // 맨 위에서 올라가면 마지막 줄(좌우 뒤집기)이 보이고, 줄 수는 그대로다
+        menu.controls_key(KeyCode::Up, &mut keys);
+        menu.controls_key(KeyCode::Up, &mut keys);
+        let lines = menu.lines(&keys);
+        assert_eq!(lines.len(), CONTROLS_ROWS + 4);
+        assert!(lines[CONTROLS_ROWS].starts_with("   quit"));
+        assert!(lines[CONTROLS_ROWS + 1].starts_with(" > mirror      off"));
+        assert!(lines.iter().all(|line| line.chars().count() == CONTROLS_W));
+
+        // Enter로 좌우를 뒤집으면 J(move_left)가 오른쪽으로 민다
+        assert_eq!(
+            menu.controls_key(KeyCode::Enter, &mut keys),
+            Some(ControlsChange::Mirror)
+        );
+        assert!(keys.mirrored);
+        assert_eq!(keys.action_for(KeyCode::Char('j')), Some(Action::MoveRight));
+        assert!(menu.lines(&keys)[CONTROLS_ROWS + 1].starts_with(" > mirror      on"));
+
+        menu.controls_key(KeyCode::Esc, &mut keys);
+        assert!(!menu.in_controls());
+    }
+}
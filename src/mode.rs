@@ -0,0 +1,138 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+// 마라톤/스프린트 모드의 목표 줄 수
+pub const MARATHON_LINES: u32 = 150;
+pub const SPRINT_LINES: u32 = 40;
+// 울트라 모드의 제한 시간
+pub const ULTRA_TIME: Duration = Duration::from_secs(120);
+// 치즈 모드에서 쓰레기 줄이 올라오는 간격
+pub const CHEESE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 게임 규칙(종료 조건) 종류
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// 150줄을 지우면 승리로 끝난다
+    Marathon,
+    /// 40줄을 얼마나 빨리 지우는지 겨룬다(점수보다 시간이 기준)
+    Sprint,
+    /// 2분 동안 최대한 많은 점수를 낸다
+    Ultra,
+    /// 쌓여서 막힐 때까지 계속한다
+    #[default]
+    Endless,
+    /// 엔드리스와 같지만 고정된 블록이 잠시 뒤 보이지 않게 된다
+    Invisible,
+    /// 일정 시간마다 구멍 하나 뚫린 쓰레기 줄이 아래에서 올라온다
+    Cheese,
+    /// 엔드리스와 같지만 피스의 칸 하나가 보드의 2x2칸을 차지하고 두 칸씩 움직인다
+    Big,
+    /// 중력 없이 피스를 놓아 보고, 고정을 되돌리거나 피스를 직접 골라 연습한다(끝나지 않는다)
+    Practice,
+    /// 막혀도 끝나지 않고 위쪽 몇 줄을 비워 계속한다
+    Zen,
+}
+
+impl GameMode {
+    /// 메뉴에서 고를 수 있는 순서
+    pub const ALL: [GameMode; 9] = [
+        GameMode::Endless,
+        GameMode::Marathon,
+        GameMode::Sprint,
+        GameMode::Ultra,
+        GameMode::Invisible,
+        GameMode::Cheese,
+        GameMode::Big,
+        GameMode::Practice,
+        GameMode::Zen,
+    ];
+
+    /// 이 모드를 끝내는 줄 수(없으면 무한).
+    pub fn line_goal(self) -> Option<u32> {
+        match self {
+            GameMode::Marathon => Some(MARATHON_LINES),
+            GameMode::Sprint => Some(SPRINT_LINES),
+            GameMode::Ultra
+            | GameMode::Endless
+            | GameMode::Invisible
+            | GameMode::Cheese
+            | GameMode::Big
+            | GameMode::Practice
+            | GameMode::Zen => None,
+        }
+    }
+
+    /// 제한 시간이 있는 모드면 그 시간.
+    pub fn time_limit(self) -> Option<Duration> {
+        match self {
+            GameMode::Ultra => Some(ULTRA_TIME),
+            _ => None,
+        }
+    }
+
+    /// 쓰레기 줄이 올라오는 모드면 그 간격.
+    pub fn garbage_interval(self) -> Option<Duration> {
+        match self {
+            GameMode::Cheese => Some(CHEESE_INTERVAL),
+            _ => None,
+        }
+    }
+
+    /// 피스의 칸 하나가 보드에서 차지하는 한 변의 칸 수(빅 모드는 2).
+    /// 이동과 킥도 이 배율로 움직인다.
+    pub fn cell_scale(self) -> i32 {
+        match self {
+            GameMode::Big => 2,
+            _ => 1,
+        }
+    }
+
+    /// 피스가 저절로 내려오는 모드인지(연습 모드는 직접 내릴 때까지 떠 있다).
+    pub fn has_gravity(self) -> bool {
+        self != GameMode::Practice
+    }
+
+    /// 점수 대신 걸린 시간으로 기록을 매기는 모드인지.
+    pub fn is_timed(self) -> bool {
+        self == GameMode::Sprint
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GameMode::Marathon => "marathon",
+            GameMode::Sprint => "sprint",
+            GameMode::Ultra => "ultra",
+            GameMode::Endless => "endless",
+            GameMode::Invisible => "invisible",
+            GameMode::Cheese => "cheese",
+            GameMode::Big => "big",
+            GameMode::Practice => "practice",
+            GameMode::Zen => "zen",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "marathon" => Ok(GameMode::Marathon),
+            "sprint" => Ok(GameMode::Sprint),
+            "ultra" => Ok(GameMode::Ultra),
+            "endless" => Ok(GameMode::Endless),
+            "invisible" => Ok(GameMode::Invisible),
+            "cheese" => Ok(GameMode::Cheese),
+            "big" => Ok(GameMode::Big),
+            "practice" => Ok(GameMode::Practice),
+            "zen" => Ok(GameMode::Zen),
+            _ => Err(format!(
+                "unknown mode '{s}' (expected marathon, sprint, ultra, endless, invisible, cheese, big, practice or zen)"
+            )),
+        }
+    }
+}
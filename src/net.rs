@@ -0,0 +1,307 @@
+use constris::{GameConfig, piece_set};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+// 접속할 때 주고받는 프로토콜 번호. 메시지 형식이 바뀌면 올리고, 다른 번호와는 대전하지 않는다.
+const PROTOCOL: u8 = 2;
+
+// 메시지 하나의 최대 길이(바이트). 이보다 길다고 하면 깨진 연결로 본다.
+const MAX_FRAME: usize = 128;
+
+/// 네트워크 대전에서 주고받는 메시지. 보드는 보내지 않고 각자 자기 게임만 돌린다.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// 접속 직후 서로 보내는 프로토콜 번호와 규칙(`rules`), 그리고 호스트가 정한 두 판의 시드
+    Hello {
+        protocol: u8,
+        seed: u64,
+        rules: String,
+    },
+    /// 상대에게 보내는 쓰레기 줄 수
+    Attack(u32),
+    /// 피스를 하나 고정했다(상대 화면에 놓은 피스 수를 보여 준다)
+    Lock,
+    /// 막혀서 졌다
+    GameOver,
+}
+
+impl Message {
+    /// 첫 바이트가 종류, 나머지가 값(빅 엔디언)
+    fn encode(self) -> Vec<u8> {
+        match self {
+            Message::Hello {
+                protocol,
+                seed,
+                rules,
+            } => {
+                let mut bytes = vec![0, protocol];
+                bytes.extend(seed.to_be_bytes());
+                bytes.extend(rules.into_bytes());
+                bytes
+            }
+            Message::Attack(rows) => {
+                let mut bytes = vec![1];
+                bytes.extend(rows.to_be_bytes());
+                bytes
+            }
+            Message::Lock => vec![2],
+            Message::GameOver => vec![3],
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0, protocol, rest @ ..] => {
+                let (seed, rules) = rest.split_at_checked(8)?;
+                Some(Message::Hello {
+                    protocol: *protocol,
+                    seed: u64::from_be_bytes(seed.try_into().ok()?),
+                    rules: String::from_utf8(rules.to_vec()).ok()?,
+                })
+            }
+            [1, rows @ ..] => Some(Message::Attack(u32::from_be_bytes(rows.try_into().ok()?))),
+            [2] => Some(Message::Lock),
+            [3] => Some(Message::GameOver),
+            _ => None,
+        }
+    }
+}
+
+/// 두 판이 같은 피스를 받으려면 같아야 하는 규칙: 모드, 보드 크기, 랜덤 방식, 피스 세트.
+/// 세트는 글자 뒤에 모양의 체크섬을 붙여 글자만 같은 다른 세트도 가려낸다.
+pub fn rules(config: &GameConfig) -> String {
+    // FNV-1a: 실행 파일이나 Rust 버전이 달라도 같은 값이 나온다
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut feed = |byte: u8| hash = (hash ^ byte as u32).wrapping_mul(0x0100_0193);
+    for kind in piece_set().kinds() {
+        let def = kind.def();
+        feed(def.size as u8);
+        for &(x, y) in &def.cells {
+            feed(x as u8);
+            feed(y as u8);
+        }
+    }
+    format!(
+        "{} {}x{} {} {}#{hash:08x}",
+        config.mode,
+        config.width,
+        config.height,
+        config.randomizer,
+        piece_set().letters()
+    )
+}
+
+/// 상대가 보낸 첫 메시지가 같은 프로토콜과 같은 규칙의 Hello인지 보고 시드를 꺼낸다.
+fn check_hello(bytes: &[u8], ours: &str) -> io::Result<u64> {
+    let invalid = |msg: String| io::Error::new(ErrorKind::InvalidData, msg);
+    match Message::decode(bytes) {
+        Some(Message::Hello { protocol, .. }) if protocol != PROTOCOL => Err(invalid(format!(
+            "opponent speaks protocol {protocol}, expected {PROTOCOL}"
+        ))),
+        Some(Message::Hello { seed, rules, .. }) if rules == ours => Ok(seed),
+        Some(Message::Hello { rules, .. }) => Err(invalid(format!(
+            "opponent plays '{rules}', this side plays '{ours}'"
+        ))),
+        _ => Err(invalid("not a constris peer".to_string())),
+    }
+}
+
+/// 버퍼 앞에 온전한 메시지(4바이트 길이 + 내용)가 있으면 떼어 낸다.
+fn take_frame(buf: &mut Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+    let Some(len) = buf.get(..4) else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(ErrorKind::InvalidData, "message too long"));
+    }
+    if buf.len() < 4 + len {
+        return Ok(None);
+    }
+    let frame = buf[4..4 + len].to_vec();
+    buf.drain(..4 + len);
+    Ok(Some(frame))
+}
+
+/// 대전 상대와의 TCP 연결. 접속한 뒤에는 기다리지 않고(non-blocking) 읽는다.
+pub struct Peer {
+    stream: TcpStream,
+    // 아직 메시지 하나가 되지 않은 받은 바이트
+    buf: Vec<u8>,
+}
+
+impl Peer {
+    /// 상대가 접속할 때까지 기다린 뒤 `seed`와 규칙을 보내고, 상대의 규칙이 같은지 확인한다.
+    pub fn accept(listener: &TcpListener, seed: u64, rules: &str) -> io::Result<Self> {
+        let (mut stream, _) = listener.accept()?;
+        write_hello(&mut stream, seed, rules)?;
+        check_hello(&read_blocking(&mut stream)?, rules)?;
+        Self::new(stream)
+    }
+
+    /// 호스트에 접속해 두 판이 함께 쓸 시드를 받는다. 규칙이 다르면 대전하지 않는다.
+    pub fn connect(addr: &str, rules: &str) -> io::Result<(Self, u64)> {
+        let mut stream = TcpStream::connect(addr)?;
+        let hello = read_blocking(&mut stream)?;
+        // 호스트도 확인할 수 있게 내 규칙을 먼저 보내고 나서 비교한다
+        write_hello(&mut stream, 0, rules)?;
+        let seed = check_hello(&hello, rules)?;
+        Ok((Self::new(stream)?, seed))
+    }
+
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        // 메시지가 작아서 모아 보내면 공격이 늦게 닿는다
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        Ok(Self {
+            stream,
+            buf: Vec::new(),
+        })
+    }
+
+    pub fn send(&mut self, message: Message) -> io::Result<()> {
+        // 논블로킹이어도 몇 바이트짜리는 한 번에 들어간다. 안 들어가면 끊긴 것으로 본다.
+        self.stream.write_all(&frame(message))
+    }
+
+    /// 지금까지 도착한 메시지들. 상대가 연결을 끊었거나 이상한 것을 보내면 오류.
+    pub fn poll(&mut self) -> io::Result<Vec<Message>> {
+        let mut chunk = [0; 256];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.buf.extend(&chunk[..n]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let mut messages = Vec::new();
+        while let Some(frame) = take_frame(&mut self.buf)? {
+            let message = Message::decode(&frame)
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "unknown message"))?;
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+}
+
+/// 4바이트 길이를 앞에 붙인 메시지
+fn frame(message: Message) -> Vec<u8> {
+    let body = message.encode();
+    let mut frame = (body.len() as u32).to_be_bytes().to_vec();
+    frame.extend(body);
+    frame
+}
+
+fn write_hello(stream: &mut TcpStream, seed: u64, rules: &str) -> io::Result<()> {
+    stream.write_all(&frame(Message::Hello {
+        protocol: PROTOCOL,
+        seed,
+        rules: rules.to_string(),
+    }))
+}
+
+/// 접속 직후 첫 메시지를 기다려 읽는다(아직 논블로킹으로 바꾸기 전).
+fn read_blocking(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(ErrorKind::InvalidData, "message too long"));
+    }
+    let mut body = vec![0; len];
+    stream.read_exact(&mut body)?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constris::{MAX_PIECE_KINDS, RandomizerKind};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn frames_come_out_whole_even_when_split() {
+        let mut bytes = Vec::new();
+        for message in [Message::Attack(4), Message::Lock, Message::GameOver] {
+            bytes.extend(frame(message));
+        }
+        let mut buf = bytes[..6].to_vec();
+        assert_eq!(take_frame(&mut buf).unwrap(), None);
+        buf.extend(&bytes[6..]);
+        let mut messages = Vec::new();
+        while let Some(frame) = take_frame(&mut buf).unwrap() {
+            messages.push(Message::decode(&frame).unwrap());
+        }
+        assert_eq!(
+            messages,
+            [Message::Attack(4), Message::Lock, Message::GameOver]
+        );
+        assert!(buf.is_empty());
+
+        let mut huge = 1000u32.to_be_bytes().to_vec();
+        assert!(take_frame(&mut huge).is_err());
+    }
+
+    #[test]
+    fn host_and_guest_share_the_seed_and_see_a_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let guest = thread::spawn(move || Peer::connect(&addr, "endless 10x20").unwrap());
+        let mut host = Peer::accept(&listener, 42, "endless 10x20").unwrap();
+        let (mut guest, seed) = guest.join().unwrap();
+        assert_eq!(seed, 42);
+
+        host.send(Message::Attack(2)).unwrap();
+        let mut received = Vec::new();
+        while received.is_empty() {
+            received = guest.poll().unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(received, [Message::Attack(2)]);
+
+        // 상대가 끊으면 poll이 오류를 돌려준다
+        drop(host);
+        let mut result = guest.poll();
+        while let Ok(messages) = &result {
+            assert!(messages.is_empty());
+            thread::sleep(Duration::from_millis(5));
+            result = guest.poll();
+        }
+    }
+
+    #[test]
+    fn both_sides_refuse_an_opponent_with_other_rules() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let guest = thread::spawn(move || Peer::connect(&addr, "endless 12x20").err());
+        let host = Peer::accept(&listener, 42, "endless 10x20").err();
+        let guest = guest.join().unwrap();
+        for err in [host, guest] {
+            let err = err.expect("different rules must not connect");
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+            assert!(err.to_string().contains("endless 12x20"));
+        }
+    }
+
+    #[test]
+    fn rules_tell_apart_board_sizes_and_randomizers() {
+        let config = GameConfig::default();
+        let wide = GameConfig {
+            width: 12,
+            ..config
+        };
+        let random = GameConfig {
+            randomizer: RandomizerKind::PureRandom,
+            ..config
+        };
+        assert_ne!(rules(&config), rules(&wide));
+        assert_ne!(rules(&config), rules(&random));
+        assert_eq!(rules(&config), rules(&GameConfig::default()));
+        // 세트가 가장 커져도 Hello 한 메시지에 들어간다(종류, 프로토콜, 시드가 10바이트)
+        let letters = MAX_PIECE_KINDS - piece_set().len();
+        assert!(rules(&config).len() + letters + 10 <= MAX_FRAME);
+    }
+}
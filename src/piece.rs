@@ -0,0 +1,574 @@
+use crate::BOARD_W;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::OnceLock;
+
+// ── 피스 세트 ───────────────────────────────────────────────────────
+
+/// 한 세트에 넣을 수 있는 피스 종류 수(사이드 패널에 종류별 개수를 두 개씩 일곱 줄)
+pub const MAX_PIECE_KINDS: usize = 14;
+/// 회전 상자 한 변의 최대 칸 수
+pub const MAX_PIECE_SIZE: i32 = 5;
+
+// 스폰 모양의 최대 높이(미리보기를 한 줄에 한 칸씩 그려도 네 줄에 들어간다)
+const MAX_SPAWN_HEIGHT: i32 = 4;
+
+/// 피스 한 종류의 정의
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PieceDef {
+    /// 저장 파일과 리플레이에 쓰는 한 글자 이름(대문자 A~Z)
+    pub letter: char,
+    /// 회전 상태 0의 셀 좌표(회전 상자 왼쪽 위가 (0, 0))
+    pub cells: Vec<(i32, i32)>,
+    /// 회전 상자 한 변의 칸 수. 이 상자의 가운데를 축으로 돌린다.
+    pub size: i32,
+    /// 클래식 테마의 색(RGB). 기본 세트는 테마에 정해 둔 색을 쓴다.
+    pub color: (u8, u8, u8),
+}
+
+impl PieceDef {
+    /// 90도 돌려도 (옮기기만 하면) 같은 모양이면 돌지 않는다(O처럼).
+    pub fn rotates(&self) -> bool {
+        let turned = rotate_cw(&self.cells, self.size);
+        normalized(&self.cells) != normalized(&turned)
+    }
+}
+
+/// 왼쪽 위 칸이 (0, 0)에 오도록 옮긴 칸 집합
+fn normalized(cells: &[(i32, i32)]) -> HashSet<(i32, i32)> {
+    let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    cells.iter().map(|&(x, y)| (x - left, y - top)).collect()
+}
+
+/// 한 변이 `size`인 상자 안에서 시계 방향 90도
+fn rotate_cw(cells: &[(i32, i32)], size: i32) -> Vec<(i32, i32)> {
+    cells.iter().map(|&(cx, cy)| (size - 1 - cy, cx)).collect()
+}
+
+/// 한 변이 `size`인 상자 안에서 반시계 방향 90도
+fn rotate_ccw(cells: &[(i32, i32)], size: i32) -> Vec<(i32, i32)> {
+    cells.iter().map(|&(cx, cy)| (cy, size - 1 - cx)).collect()
+}
+
+/// 한 판에 나오는 피스 종류들. 기본은 가이드라인 테트로미노 7종이다.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PieceSet {
+    defs: Vec<PieceDef>,
+    standard: bool,
+}
+
+impl PieceSet {
+    /// 테트로미노 I O T S Z J L(I는 4x4, O는 가운데 2x2, 나머지는 3x3 상자)
+    pub fn standard() -> Self {
+        let def = |letter, cells: [(i32, i32); 4], size, color| PieceDef {
+            letter,
+            cells: cells.to_vec(),
+            size,
+            color,
+        };
+        Self {
+            defs: vec![
+                def('I', [(0, 1), (1, 1), (2, 1), (3, 1)], 4, (0, 255, 255)),
+                def('O', [(1, 0), (2, 0), (1, 1), (2, 1)], 4, (255, 255, 0)),
+                def('T', [(0, 1), (1, 1), (2, 1), (1, 0)], 3, (255, 0, 255)),
+                def('S', [(0, 1), (1, 1), (1, 0), (2, 0)], 3, (0, 255, 0)),
+                def('Z', [(0, 0), (1, 0), (1, 1), (2, 1)], 3, (255, 0, 0)),
+                def('J', [(0, 0), (0, 1), (1, 1), (2, 1)], 3, (0, 0, 255)),
+                def('L', [(2, 0), (0, 1), (1, 1), (2, 1)], 3, (255, 128, 0)),
+            ],
+            standard: true,
+        }
+    }
+
+    /// 직접 정의한 세트. 종류 수, 글자, 상자 크기, 칸 좌표가 맞지 않으면 이유와 함께 실패한다.
+    ///
+    /// SRS 킥 표와 T-스핀 판정은 기본 세트에만 쓰고, 직접 정의한 피스는 모두
+    /// J L S T Z와 같은 킥 표로 돌린다.
+    pub fn custom(defs: Vec<PieceDef>) -> Result<Self, String> {
+        if defs.is_empty() || defs.len() > MAX_PIECE_KINDS {
+            return Err(format!("expected 1 to {MAX_PIECE_KINDS} pieces"));
+        }
+        for (i, def) in defs.iter().enumerate() {
+            let letter = def.letter;
+            if !letter.is_ascii_uppercase() {
+                return Err(format!("piece {}: letter must be A-Z", i + 1));
+            }
+            if defs[..i].iter().any(|other| other.letter == letter) {
+                return Err(format!("piece {letter}: letter is used twice"));
+            }
+            if !(1..=MAX_PIECE_SIZE).contains(&def.size) {
+                return Err(format!(
+                    "piece {letter}: size must be 1 to {MAX_PIECE_SIZE}"
+                ));
+            }
+            let inside =
+                |&(x, y): &(i32, i32)| (0..def.size).contains(&x) && (0..def.size).contains(&y);
+            if def.cells.is_empty() || !def.cells.iter().all(inside) {
+                return Err(format!(
+                    "piece {letter}: cells must lie inside the {0}x{0} box",
+                    def.size
+                ));
+            }
+            if normalized(&def.cells).len() != def.cells.len() {
+                return Err(format!("piece {letter}: a cell is listed twice"));
+            }
+            let top = def.cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+            let bottom = def.cells.iter().map(|&(_, y)| y).max().unwrap_or(0);
+            if bottom - top + 1 > MAX_SPAWN_HEIGHT {
+                return Err(format!(
+                    "piece {letter}: spawn shape is taller than {MAX_SPAWN_HEIGHT} rows"
+                ));
+            }
+        }
+        Ok(Self {
+            defs,
+            standard: false,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    /// 세트 안의 모든 종류(가방에 넣는 순서)
+    pub fn kinds(&self) -> impl Iterator<Item = PieceKind> + use<> {
+        (0..self.defs.len() as u8).map(PieceKind)
+    }
+
+    /// 기본 테트로미노 세트인지
+    pub fn is_standard(&self) -> bool {
+        self.standard
+    }
+
+    /// 종류별 글자를 순서대로 이은 것(리플레이가 같은 세트로 기록됐는지 확인한다)
+    pub fn letters(&self) -> String {
+        self.defs.iter().map(|def| def.letter).collect()
+    }
+
+    /// 가장 큰 회전 상자의 한 변. 보드는 이보다 좁아질 수 없다.
+    pub fn max_size(&self) -> usize {
+        self.defs.iter().map(|def| def.size).max().unwrap_or(1) as usize
+    }
+
+    /// 미리보기 모양(`PieceKind::preview_cells`)이 모두 들어가는 가로, 세로 칸 수
+    pub fn preview_size(&self) -> (i32, i32) {
+        self.kinds().fold((0, 0), |(w, h), kind| {
+            let cells = kind.preview_cells();
+            let right = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+            let bottom = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+            (w.max(right), h.max(bottom))
+        })
+    }
+}
+
+static PIECE_SET: OnceLock<PieceSet> = OnceLock::new();
+static STANDARD_SET: OnceLock<PieceSet> = OnceLock::new();
+
+/// 이 프로세스에서 쓸 피스 세트를 정한다. 피스를 처음 만들기 전에 한 번만 정할 수 있고,
+/// 이미 정해졌으면 받은 세트를 그대로 돌려준다.
+pub fn install_piece_set(set: PieceSet) -> Result<(), PieceSet> {
+    PIECE_SET.set(set)
+}
+
+/// 지금 쓰는 피스 세트(따로 정하지 않았으면 기본 세트)
+pub fn piece_set() -> &'static PieceSet {
+    PIECE_SET.get_or_init(|| standard_set().clone())
+}
+
+fn standard_set() -> &'static PieceSet {
+    STANDARD_SET.get_or_init(PieceSet::standard)
+}
+
+// ── 피스 종류 ───────────────────────────────────────────────────────
+
+/// 피스 종류: 지금 쓰는 세트 안에서의 번호. 기본 세트의 종류는 이름 붙은 상수로 쓴다.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PieceKind(u8);
+
+/// 기본 세트의 7종
+pub const ALL_PIECES: [PieceKind; 7] = [
+    PieceKind::I,
+    PieceKind::O,
+    PieceKind::T,
+    PieceKind::S,
+    PieceKind::Z,
+    PieceKind::J,
+    PieceKind::L,
+];
+
+impl PieceKind {
+    pub const I: PieceKind = PieceKind(0);
+    pub const O: PieceKind = PieceKind(1);
+    pub const T: PieceKind = PieceKind(2);
+    pub const S: PieceKind = PieceKind(3);
+    pub const Z: PieceKind = PieceKind(4);
+    pub const J: PieceKind = PieceKind(5);
+    pub const L: PieceKind = PieceKind(6);
+
+    /// 세트 안에서의 위치(피스별 통계 배열의 인덱스)
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn def(self) -> &'static PieceDef {
+        self.def_in(piece_set())
+    }
+
+    /// `set`에서의 정의. 세트에 없는 번호면 패닉한다(기본 세트 상수는 기본 세트에서만 쓴다).
+    fn def_in(self, set: &'static PieceSet) -> &'static PieceDef {
+        &set.defs[self.index()]
+    }
+
+    /// 저장 파일에 쓰는 한 글자 이름
+    pub fn letter(self) -> char {
+        self.def().letter
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        piece_set().kinds().find(|kind| kind.letter() == letter)
+    }
+
+    /// 회전 상태 0의 셀 좌표(회전 상자 기준)
+    pub fn cells(self) -> Vec<(i32, i32)> {
+        self.def().cells.clone()
+    }
+
+    /// 미리보기에 그릴 모양. 기본 세트는 상자 안 자리 그대로, 직접 정의한 피스는 왼쪽 위로 붙인다.
+    pub fn preview_cells(self) -> Vec<(i32, i32)> {
+        if piece_set().is_standard() {
+            return self.cells();
+        }
+        normalized(&self.def().cells).into_iter().collect()
+    }
+
+    /// 회전 상자 한 변의 칸 수
+    pub fn size(self) -> i32 {
+        self.def().size
+    }
+}
+
+// 화면과 테스트 메시지에는 번호 대신 글자로 보인다
+impl fmt::Debug for PieceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match piece_set().defs.get(self.index()) {
+            Some(def) => write!(f, "{}", def.letter),
+            None => write!(f, "PieceKind({})", self.0),
+        }
+    }
+}
+
+// ── SRS 벽 킥 표 ────────────────────────────────────────────────────
+//
+// 회전 상태: 0 = 스폰, 1 = R(시계 방향 1회), 2 = 180도, 3 = L.
+// 가이드라인 표를 그대로 옮겨 y가 위로 증가한다(적용할 때 부호를 뒤집는다).
+// 행 순서: 0→R, R→0, R→2, 2→R, 2→L, L→2, L→0, 0→L.
+
+const JLSTZ_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+];
+
+const I_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+];
+
+// 180도 회전은 SRS 표가 없어 작은 목록을 따로 쓴다(화면 좌표, 위가 -y).
+const FLIP_KICKS: [(i32, i32); 4] = [(0, 0), (0, -1), (1, 0), (-1, 0)];
+
+/// `from`에서 `to` 상태로 회전할 때 시도할 킥 오프셋(y는 화면 방향, 아래가 +).
+///
+/// 인접한 상태 사이는 SRS 표, 180도는 `FLIP_KICKS`를 쓴다. 돌지 않는 피스(O)는 킥이 없다.
+/// 직접 정의한 세트는 I도 O도 아니므로 모두 J L S T Z 표를 쓴다.
+pub fn srs_kicks(kind: PieceKind, from: u8, to: u8) -> Vec<(i32, i32)> {
+    if !kind.def().rotates() {
+        return vec![(0, 0)];
+    }
+    if (from + 2) % 4 == to {
+        return FLIP_KICKS.to_vec();
+    }
+    let row = match (from, to) {
+        (0, 1) => 0,
+        (1, 0) => 1,
+        (1, 2) => 2,
+        (2, 1) => 3,
+        (2, 3) => 4,
+        (3, 2) => 5,
+        (3, 0) => 6,
+        (0, 3) => 7,
+        _ => return vec![(0, 0)],
+    };
+    let table = if piece_set().is_standard() && kind == PieceKind::I {
+        &I_KICKS
+    } else {
+        &JLSTZ_KICKS
+    };
+    table[row].iter().map(|&(x, y)| (x, -y)).collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Piece {
+    pub kind: PieceKind,
+    pub cells: Vec<(i32, i32)>,
+    /// SRS 회전 상태(0~3)
+    pub rotation: u8,
+    pub x: i32,
+    pub y: i32,
+    /// `cells`의 한 칸이 보드에서 차지하는 한 변의 칸 수(빅 모드는 2)
+    pub scale: i32,
+}
+
+impl Piece {
+    /// 기본 너비(`BOARD_W`) 보드의 스폰 위치에 놓인 피스
+    pub fn new(kind: PieceKind) -> Self {
+        Self::spawn(kind, BOARD_W)
+    }
+
+    /// 너비가 `board_w`인 보드의 스폰 위치에 놓인 피스
+    pub fn spawn(kind: PieceKind, board_w: usize) -> Self {
+        Self::spawn_rotated(kind, board_w, 0)
+    }
+
+    /// 회전 상태 `rotation`(0~3, 넘으면 4로 나눈 나머지)으로 미리 돌려서 스폰한다.
+    /// 돌린 모양의 맨 윗줄이 상태 0의 맨 윗줄과 같은 줄에 오게 맞춘다.
+    pub fn spawn_rotated(kind: PieceKind, board_w: usize, rotation: u8) -> Self {
+        let mut piece = Self {
+            kind,
+            cells: kind.cells(),
+            rotation: rotation % 4,
+            x: 0,
+            y: 0,
+            scale: 1,
+        };
+        let top = |cells: &[(i32, i32)]| cells.iter().map(|&(_, cy)| cy).min().unwrap_or(0);
+        let spawn_top = top(&piece.cells);
+        for _ in 0..piece.rotation {
+            piece.cells = piece.rotated_cw();
+        }
+        // 실제로 차지하는 열 폭을 가운데에 맞춘다(홀수 폭이면 왼쪽으로 한 칸).
+        // 상태 0에서 I는 3~6열, O는 4~5열, 나머지는 3~5열에 나온다.
+        let left = piece.cells.iter().map(|&(cx, _)| cx).min().unwrap_or(0);
+        let right = piece.cells.iter().map(|&(cx, _)| cx).max().unwrap_or(0);
+        piece.x = (board_w as i32 - (right - left + 1)) / 2 - left;
+        // 보드 위에서 시작해 자연스럽게 내려오도록 음수 y
+        piece.y = -1 + spawn_top - top(&piece.cells);
+        piece
+    }
+
+    /// 칸 하나를 `scale` x `scale`칸으로 키운 피스. 위치도 같은 배율로 옮기므로
+    /// 폭을 `scale`로 나눈 보드에 스폰한 피스를 키우면 실제 보드의 스폰 위치가 된다.
+    pub fn scaled(mut self, scale: i32) -> Self {
+        self.x *= scale;
+        self.y *= scale;
+        self.scale = scale;
+        self
+    }
+
+    // 현재 피스의 절대 좌표 목록
+    pub fn absolute_cells(&self) -> Vec<(i32, i32)> {
+        let s = self.scale;
+        self.cells
+            .iter()
+            .flat_map(|&(cx, cy)| {
+                (0..s * s).map(move |i| (self.x + cx * s + i % s, self.y + cy * s + i / s))
+            })
+            .collect()
+    }
+
+    // 시계 방향 90도 회전 좌표(돌지 않는 피스는 그대로)
+    pub fn rotated_cw(&self) -> Vec<(i32, i32)> {
+        if !self.kind.def().rotates() {
+            return self.cells.clone();
+        }
+        rotate_cw(&self.cells, self.kind.size())
+    }
+
+    // 180도 회전 좌표(시계 방향 두 번)
+    pub fn rotated_180(&self) -> Vec<(i32, i32)> {
+        let mut once = self.clone();
+        once.cells = self.rotated_cw();
+        once.rotated_cw()
+    }
+
+    // 반시계 방향 90도 회전 좌표
+    pub fn rotated_ccw(&self) -> Vec<(i32, i32)> {
+        if !self.kind.def().rotates() {
+            return self.cells.clone();
+        }
+        rotate_ccw(&self.cells, self.kind.size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_columns(kind: PieceKind) -> Vec<i32> {
+        let mut cols: Vec<i32> = Piece::new(kind)
+            .absolute_cells()
+            .iter()
+            .map(|&(x, _)| x)
+            .collect();
+        cols.sort();
+        cols.dedup();
+        cols
+    }
+
+    #[test]
+    fn spawns_centered_per_guideline() {
+        assert_eq!(spawn_columns(PieceKind::I), vec![3, 4, 5, 6]);
+        assert_eq!(spawn_columns(PieceKind::O), vec![4, 5]);
+        for kind in [
+            PieceKind::T,
+            PieceKind::S,
+            PieceKind::Z,
+            PieceKind::J,
+            PieceKind::L,
+        ] {
+            assert_eq!(spawn_columns(kind), vec![3, 4, 5], "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn spawns_in_the_requested_orientation() {
+        for kind in ALL_PIECES {
+            let top = |piece: &Piece| piece.absolute_cells().iter().map(|&(_, y)| y).min();
+            let upright = Piece::new(kind);
+            let mut expected = upright.clone();
+            for rotation in 0..4u8 {
+                let piece = Piece::spawn_rotated(kind, BOARD_W, rotation);
+                let shape: HashSet<(i32, i32)> = piece.cells.iter().copied().collect();
+                assert_eq!(shape, expected.cells.iter().copied().collect(), "{kind:?}");
+                assert_eq!(piece.rotation, rotation);
+                // 맨 윗줄은 그대로, 가로는 보드 안에 들어간다
+                assert_eq!(top(&piece), top(&upright), "{kind:?} {rotation}");
+                assert!(
+                    piece
+                        .absolute_cells()
+                        .iter()
+                        .all(|&(x, _)| (0..BOARD_W as i32).contains(&x))
+                );
+                expected.cells = expected.rotated_cw();
+            }
+        }
+        // 세로로 선 I도 좁은 보드 가운데에 나온다
+        let i = Piece::spawn_rotated(PieceKind::I, 4, 1);
+        assert!(i.absolute_cells().iter().all(|&(x, _)| x == 1));
+    }
+
+    #[test]
+    fn scaled_pieces_cover_blocks_of_cells() {
+        let o = Piece::spawn(PieceKind::O, BOARD_W / 2).scaled(2);
+        let mut cells = o.absolute_cells();
+        cells.sort();
+        // 폭 5의 논리 보드에서 1~2열, 실제 보드에서 2~5열에 4x4칸
+        let mut expected: Vec<_> = (2..6).flat_map(|x| (-2..2).map(move |y| (x, y))).collect();
+        expected.sort();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn rotates_t_piece_clockwise() {
+        // T피스 회전 결과가 기대 좌표와 일치하는지 확인
+        let piece = Piece::new(PieceKind::T);
+        let rotated = piece.rotated_cw();
+        let actual: HashSet<(i32, i32)> = rotated.into_iter().collect();
+        let expected: HashSet<(i32, i32)> = [(1, 0), (1, 1), (1, 2), (2, 1)].into_iter().collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn flip_equals_two_clockwise_turns() {
+        let mut piece = Piece::new(PieceKind::L);
+        let flipped = piece.rotated_180();
+        piece.cells = piece.rotated_cw();
+        piece.cells = piece.rotated_cw();
+        assert_eq!(flipped, piece.cells);
+
+        let o = Piece::new(PieceKind::O);
+        assert_eq!(o.rotated_180(), o.cells);
+    }
+
+    #[test]
+    fn ccw_undoes_cw() {
+        for kind in ALL_PIECES {
+            let mut piece = Piece::new(kind);
+            let original: HashSet<(i32, i32)> = piece.cells.iter().copied().collect();
+            piece.cells = piece.rotated_cw();
+            let restored: HashSet<(i32, i32)> = piece.rotated_ccw().into_iter().collect();
+            assert_eq!(restored, original, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn custom_pieces_turn_inside_their_own_box() {
+        let def = |letter, cells: &[(i32, i32)], size| PieceDef {
+            letter,
+            cells: cells.to_vec(),
+            size,
+            color: (255, 255, 255),
+        };
+        // 5x5 상자의 가로 I 펜토미노는 가운데 열로 선다
+        let long = def('I', &[(0, 2), (1, 2), (2, 2), (3, 2), (4, 2)], 5);
+        assert_eq!(
+            rotate_cw(&long.cells, long.size),
+            vec![(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]
+        );
+        assert_eq!(rotate_ccw(&rotate_cw(&long.cells, 5), 5), long.cells);
+        assert!(long.rotates());
+        // 90도 돌려도 같은 모양(X 펜토미노, 한 칸짜리)은 돌지 않는다
+        let plus = def('X', &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)], 3);
+        assert!(!plus.rotates());
+        assert!(!def('D', &[(0, 0)], 1).rotates());
+        assert!(!PieceSet::standard().defs[PieceKind::O.index()].rotates());
+
+        let set = PieceSet::custom(vec![long.clone(), plus.clone()]).unwrap();
+        assert_eq!((set.len(), set.max_size()), (2, 5));
+        assert!(!set.is_standard());
+
+        let set: &'static PieceSet = Box::leak(Box::new(set));
+        assert_eq!(PieceKind::O.def_in(set), &plus);
+    }
+
+    #[test]
+    fn custom_sets_reject_bad_definitions() {
+        let def = |letter, cells: Vec<(i32, i32)>, size| PieceDef {
+            letter,
+            cells,
+            size,
+            color: (0, 0, 0),
+        };
+        let error = |defs| PieceSet::custom(defs).unwrap_err();
+        assert!(error(Vec::new()).contains("pieces"));
+        assert!(error(vec![def('a', vec![(0, 0)], 1)]).contains("A-Z"));
+        assert!(
+            error(vec![def('A', vec![(0, 0)], 1), def('A', vec![(0, 0)], 1)]).contains("twice")
+        );
+        assert!(error(vec![def('A', vec![(0, 0)], 6)]).contains("size"));
+        assert!(error(vec![def('A', vec![(0, 3)], 3)]).contains("box"));
+        assert!(error(vec![def('A', vec![(0, 0), (0, 0)], 2)]).contains("twice"));
+        let tall = (0..5).map(|y| (0, y)).collect();
+        assert!(error(vec![def('A', tall, 5)]).contains("taller"));
+        let many = (b'A'..=b'O')
+            .map(|c| def(c as char, vec![(0, 0)], 1))
+            .collect();
+        assert!(error(many).contains("pieces"));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::error::{Error, Result};
+use constris::{PieceDef, PieceSet};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// 피스 세트 파일에 적힌 그대로의 모양
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PiecesFile {
+    piece: Vec<PieceEntry>,
+}
+
+/// `[[piece]]` 하나: 글자, 회전 상자 크기, `"#rrggbb"` 색, 상태 0의 `[x, y]` 칸들
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PieceEntry {
+    letter: char,
+    size: i32,
+    color: String,
+    cells: Vec<(i32, i32)>,
+}
+
+/// `path`의 TOML 파일에서 피스 세트를 읽는다.
+pub fn load(path: &Path) -> Result<PieceSet> {
+    let pieces_error = |msg| Error::Pieces {
+        path: path.to_path_buf(),
+        msg,
+    };
+    let text = fs::read_to_string(path).map_err(|e| pieces_error(e.to_string()))?;
+    parse(&text).map_err(pieces_error)
+}
+
+fn parse(text: &str) -> std::result::Result<PieceSet, String> {
+    let file: PiecesFile = toml::from_str(text).map_err(|e| e.message().to_string())?;
+    let defs = file
+        .piece
+        .into_iter()
+        .map(|entry| {
+            let color = parse_color(&entry.color).ok_or(format!(
+                "piece {}: bad color '{}'",
+                entry.letter, entry.color
+            ))?;
+            Ok(PieceDef {
+                letter: entry.letter,
+                cells: entry.cells,
+                size: entry.size,
+                color,
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, String>>()?;
+    PieceSet::custom(defs)
+}
+
+/// `"#rrggbb"` 형식의 색
+fn parse_color(text: &str) -> Option<(u8, u8, u8)> {
+    let hex = text.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_pieces_with_their_box_and_color() {
+        let set = parse(
+            r##"
+            [[piece]]
+            letter = "P"
+            size = 3
+            color = "#ff8800"
+            cells = [[0, 0], [1, 0], [0, 1], [1, 1], [0, 2]]
+
+            [[piece]]
+            letter = "X"
+            size = 3
+            color = "#00aaff"
+            cells = [[1, 0], [0, 1], [1, 1], [2, 1], [1, 2]]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.letters(), "PX");
+        assert!(!set.is_standard());
+        assert_eq!(set.max_size(), 3);
+    }
+
+    #[test]
+    fn rejects_bad_colors_and_shapes() {
+        let piece = |color: &str, cells: &str| {
+            parse(&format!(
+                "[[piece]]\nletter = \"Q\"\nsize = 2\ncolor = \"{color}\"\ncells = {cells}\n"
+            ))
+        };
+        assert!(piece("#11223", "[[0, 0]]").unwrap_err().contains("color"));
+        assert!(piece("red", "[[0, 0]]").is_err());
+        // 회전 상자 밖의 칸
+        assert!(
+            piece("#112233", "[[0, 0], [2, 0]]")
+                .unwrap_err()
+                .contains("box")
+        );
+        assert!(piece("#112233", "[[0, 0], [1, 1]]").is_ok());
+        // 피스가 하나도 없는 파일, 모르는 항목
+        assert!(parse("").is_err());
+        assert!(parse("[[piece]]\nletter = \"Q\"\nshape = 1\n").is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{PieceKind, piece_set};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+// TGM식: 최근 몇 피스를 기억하고, 겹치면 몇 번까지 다시 뽑는지
+const TGM_HISTORY: usize = 4;
+const TGM_ROLLS: u32 = 4;
+// 가방 하나에 넣을 수 있는 종류별 최대 개수
+pub const MAX_BAG_COPIES: u32 = 9;
+// 저장한 게임에서 되살릴 수 있는 최대 꺼낸 수. 한 판에 이보다 많이 놓는 일은 없다고 본다.
+const MAX_RESTORE_PULLS: u32 = 10_000_000;
+
+/// 다음 피스를 고르는 방법. 같은 시드로 시작하면 같은 순서가 나오도록 난수원은 밖에서 받는다.
+pub trait Randomizer {
+    fn next(&mut self, rng: &mut StdRng) -> PieceKind;
+
+    /// `Game`을 복제할 때 지금까지의 상태(가방에 남은 피스, 기억한 피스)까지 복제한다.
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// 설정에서 고르는 랜덤 방법
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RandomizerKind {
+    /// 세트의 모든 종류를 하나씩 넣고 섞은 가방에서 꺼낸다
+    #[default]
+    Bag7,
+    /// 매번 모든 종류 중에서 고르게 뽑는다
+    PureRandom,
+    /// 종류마다 N개씩 넣은 큰 가방(`bag2`면 기본 세트에서 14개)
+    BagN(u32),
+    /// 매번 고르게 뽑되 최근 4피스와 겹치면 4번까지 다시 뽑는다
+    Tgm,
+}
+
+impl RandomizerKind {
+    pub fn build(self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::Bag7 => Box::new(BagRandomizer::new(1)),
+            RandomizerKind::BagN(copies) => Box::new(BagRandomizer::new(copies)),
+            RandomizerKind::PureRandom => Box::new(PureRandom),
+            RandomizerKind::Tgm => Box::new(HistoryRandomizer::default()),
+        }
+    }
+}
+
+impl fmt::Display for RandomizerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandomizerKind::Bag7 => f.write_str("bag"),
+            RandomizerKind::PureRandom => f.write_str("random"),
+            RandomizerKind::BagN(copies) => write!(f, "bag{copies}"),
+            RandomizerKind::Tgm => f.write_str("tgm"),
+        }
+    }
+}
+
+impl FromStr for RandomizerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        match s.as_str() {
+            "bag" | "7bag" => Ok(RandomizerKind::Bag7),
+            "random" => Ok(RandomizerKind::PureRandom),
+            "tgm" => Ok(RandomizerKind::Tgm),
+            _ => match s.strip_prefix("bag").and_then(|n| n.parse().ok()) {
+                Some(1) => Ok(RandomizerKind::Bag7),
+                Some(copies @ 2..=MAX_BAG_COPIES) => Ok(RandomizerKind::BagN(copies)),
+                _ => Err(format!(
+                    "unknown randomizer '{s}' (expected bag, bag2~bag{MAX_BAG_COPIES}, random or tgm)"
+                )),
+            },
+        }
+    }
+}
+
+/// 종류마다 `copies`개씩 넣고 섞은 가방. 뒤에서부터 꺼내고 비면 다시 채운다.
+#[derive(Clone)]
+struct BagRandomizer {
+    copies: u32,
+    pieces: Vec<PieceKind>,
+}
+
+impl BagRandomizer {
+    fn new(copies: u32) -> Self {
+        Self {
+            copies,
+            pieces: Vec::new(),
+        }
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next(&mut self, rng: &mut StdRng) -> PieceKind {
+        if self.pieces.is_empty() {
+            for _ in 0..self.copies {
+                self.pieces.extend(piece_set().kinds());
+            }
+            self.pieces.shuffle(rng);
+        }
+        self.pieces.pop().expect("bag was just refilled")
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+struct PureRandom;
+
+impl Randomizer for PureRandom {
+    fn next(&mut self, rng: &mut StdRng) -> PieceKind {
+        let index = rng.gen_range(0..piece_set().len());
+        piece_set()
+            .kinds()
+            .nth(index)
+            .expect("index is inside the set")
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// TGM식: 최근에 나온 피스와 겹치면 다시 뽑아 같은 피스가 몰려 나오는 일을 줄인다.
+#[derive(Clone, Default)]
+struct HistoryRandomizer {
+    history: VecDeque<PieceKind>,
+}
+
+impl Randomizer for HistoryRandomizer {
+    fn next(&mut self, rng: &mut StdRng) -> PieceKind {
+        let mut kind = PureRandom.next(rng);
+        for _ in 1..TGM_ROLLS {
+            if !self.history.contains(&kind) {
+                break;
+            }
+            kind = PureRandom.next(rng);
+        }
+        if self.history.len() == TGM_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(kind);
+        kind
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// 게임의 피스 공급원: 고른 랜덤 방법과 그것이 쓰는 유일한 난수원.
+/// 저장한 게임을 이어 할 때 같은 순서가 이어지도록 꺼낸 횟수를 센다.
+#[derive(Clone)]
+pub(crate) struct PieceSource {
+    pub(crate) kind: RandomizerKind,
+    pub(crate) pulls: u32,
+    randomizer: Box<dyn Randomizer>,
+    rng: StdRng,
+}
+
+impl PieceSource {
+    pub(crate) fn new(seed: u64, kind: RandomizerKind) -> Self {
+        Self {
+            kind,
+            pulls: 0,
+            randomizer: kind.build(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// `seed`로 시작해 `pulls`개를 꺼낸 뒤의 공급원. 그 게임이 꺼냈을 수 있는 `most`개보다
+    /// 많으면 깨진 값으로 보고 다시 꺼내 보지 않는다.
+    pub(crate) fn restore(
+        seed: u64,
+        kind: RandomizerKind,
+        pulls: u32,
+        most: u32,
+    ) -> Result<Self, String> {
+        if pulls > most.min(MAX_RESTORE_PULLS) {
+            return Err(format!("bad randomizer pull count {pulls}"));
+        }
+        let mut source = Self::new(seed, kind);
+        for _ in 0..pulls {
+            source.pull();
+        }
+        Ok(source)
+    }
+
+    pub(crate) fn pull(&mut self) -> PieceKind {
+        self.pulls += 1;
+        self.randomizer.next(&mut self.rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(kind: RandomizerKind, count: usize) -> Vec<PieceKind> {
+        let mut source = PieceSource::new(21, kind);
+        (0..count).map(|_| source.pull()).collect()
+    }
+
+    fn counts(pieces: &[PieceKind]) -> Vec<usize> {
+        let mut counts = vec![0; piece_set().len()];
+        for kind in pieces {
+            counts[kind.index()] += 1;
+        }
+        counts
+    }
+
+    fn missing(pieces: &[PieceKind]) -> usize {
+        counts(pieces).iter().filter(|&&count| count == 0).count()
+    }
+
+    #[test]
+    fn bags_deal_every_kind_once_per_copy() {
+        let n = piece_set().len();
+        for chunk in run(RandomizerKind::Bag7, n * 20).chunks(n) {
+            assert_eq!(counts(chunk), vec![1; n]);
+        }
+        // 두 개씩 넣은 가방은 14피스마다 종류별로 두 번, 같은 피스가 연달아 나올 수도 있다
+        let big = run(RandomizerKind::BagN(2), n * 2 * 20);
+        for chunk in big.chunks(n * 2) {
+            assert_eq!(counts(chunk), vec![2; n]);
+        }
+        assert!(big.windows(2).any(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn pure_random_covers_every_kind_without_a_fixed_share() {
+        let pieces = run(RandomizerKind::PureRandom, 700);
+        let counts = counts(&pieces);
+        assert!(counts.iter().all(|&count| count > 50 && count < 150));
+        // 가방과 달리 일곱 개 안에 빠지는 종류가 있다
+        assert!(pieces.chunks(7).any(|chunk| missing(chunk) > 0));
+    }
+
+    #[test]
+    fn tgm_repeats_recent_pieces_less_often_than_pure_random() {
+        let repeats =
+            |pieces: &[PieceKind]| pieces.windows(2).filter(|pair| pair[0] == pair[1]).count();
+        let random = run(RandomizerKind::PureRandom, 2000);
+        let tgm = run(RandomizerKind::Tgm, 2000);
+        // 고르게 뽑으면 일곱 번에 한 번꼴로 바로 앞과 같고, TGM식은 다시 뽑아도 계속 겹쳐야 같다
+        assert!(repeats(&random) > 200);
+        assert!(repeats(&tgm) * 3 < repeats(&random));
+        assert!(counts(&tgm).iter().all(|&count| count > 200));
+    }
+
+    #[test]
+    fn same_seed_and_pull_count_continue_the_same_sequence() {
+        for kind in [
+            RandomizerKind::Bag7,
+            RandomizerKind::PureRandom,
+            RandomizerKind::BagN(3),
+            RandomizerKind::Tgm,
+        ] {
+            let mut source = PieceSource::new(5, kind);
+            for _ in 0..10 {
+                source.pull();
+            }
+            let mut restored = PieceSource::restore(5, kind, source.pulls, 10).unwrap();
+            let next: Vec<_> = (0..30).map(|_| source.pull()).collect();
+            assert_eq!((0..30).map(|_| restored.pull()).collect::<Vec<_>>(), next);
+        }
+    }
+
+    #[test]
+    fn restore_refuses_more_pulls_than_the_game_could_make() {
+        assert!(PieceSource::restore(5, RandomizerKind::Bag7, 11, 10).is_err());
+        let limit = MAX_RESTORE_PULLS + 1;
+        assert!(PieceSource::restore(5, RandomizerKind::Bag7, limit, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn parses_randomizer_names() {
+        assert_eq!("bag".parse(), Ok(RandomizerKind::Bag7));
+        assert_eq!("Random".parse(), Ok(RandomizerKind::PureRandom));
+        assert_eq!("bag2".parse(), Ok(RandomizerKind::BagN(2)));
+        assert_eq!("bag1".parse(), Ok(RandomizerKind::Bag7));
+        assert_eq!("tgm".parse(), Ok(RandomizerKind::Tgm));
+        assert!("bag0".parse::<RandomizerKind>().is_err());
+        assert!("bag10".parse::<RandomizerKind>().is_err());
+        for kind in [RandomizerKind::BagN(4), RandomizerKind::Tgm] {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+    }
+}
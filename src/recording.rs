@@ -0,0 +1,96 @@
+use constris::{Game, Input, Replay};
+use std::time::Instant;
+
+/// 일시정지 시간을 뺀 플레이 시계. 리플레이 기록과 재생이 같은 기준을 쓴다.
+pub struct PlayClock {
+    start: Instant,
+    paused_at: Option<Instant>,
+}
+
+impl PlayClock {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            start: now,
+            paused_at: None,
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        self.paused_at.get_or_insert(now);
+    }
+
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(at) = self.paused_at.take() {
+            self.start += now.saturating_duration_since(at);
+        }
+    }
+
+    /// 시작 후 흐른 시간(ms)
+    pub fn ms(&self, now: Instant) -> u64 {
+        let until = self.paused_at.unwrap_or(now);
+        until.saturating_duration_since(self.start).as_millis() as u64
+    }
+}
+
+/// 기록된 입력을 시각에 맞춰 하나씩 내어 준다.
+pub struct Playback {
+    replay: Replay,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, next: 0 }
+    }
+
+    /// 처음부터 다시 재생할 게임
+    pub fn rewind(&mut self) -> Game {
+        self.next = 0;
+        self.replay.new_game()
+    }
+
+    /// `ms`까지 차례가 된 입력들(기록된 순서대로)
+    pub fn due(&mut self, ms: u64) -> Vec<Input> {
+        let rest = &self.replay.inputs[self.next..];
+        let n = rest.iter().take_while(|&&(at, _)| at <= ms).count();
+        self.next += n;
+        rest[..n].iter().map(|&(_, input)| input).collect()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next == self.replay.inputs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constris::GameConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn clock_skips_paused_time() {
+        let t0 = Instant::now();
+        let mut clock = PlayClock::new(t0);
+        clock.pause(t0 + Duration::from_millis(100));
+        assert_eq!(clock.ms(t0 + Duration::from_millis(900)), 100);
+        clock.resume(t0 + Duration::from_millis(1000));
+        assert_eq!(clock.ms(t0 + Duration::from_millis(1050)), 150);
+    }
+
+    #[test]
+    fn playback_hands_out_inputs_in_time_order() {
+        let mut replay = Replay::new(7, GameConfig::default());
+        replay.push(0, Input::Left);
+        replay.push(50, Input::Gravity);
+        replay.push(50, Input::HardDrop);
+        replay.push(200, Input::Right);
+        let mut playback = Playback::new(replay);
+
+        assert_eq!(playback.due(10), vec![Input::Left]);
+        assert_eq!(playback.due(60), vec![Input::Gravity, Input::HardDrop]);
+        assert!(playback.due(100).is_empty());
+        assert_eq!(playback.due(300), vec![Input::Right]);
+        assert!(playback.finished());
+    }
+}
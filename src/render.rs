@@ -0,0 +1,1656 @@
+use crate::keys::KeyBindings;
+use crate::menu::Menu;
+use crate::theme::{GhostStyle, Glyphs, Theme};
+use constris::{
+    Block, Board, Cell, DropTrail, EndReason, Game, GameEvent, GameMode, PieceKind, ScorePopup,
+    piece_set,
+};
+use crossterm::{
+    cursor, queue,
+    style::{self, Color, Stylize},
+};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// 각 칸은 가로 4문자 × 세로 2줄(터미널에서 정사각형에 가깝게).
+// 좁은 칸(`--narrow`)은 가로 2문자라 보드 폭이 절반이 된다.
+pub const CELL_W: usize = 4;
+pub const NARROW_CELL_W: usize = 2;
+pub const CELL_H: usize = 2;
+
+// 보드 그리기 시작 y좌표(테두리 내부)
+const BOARD_Y: u16 = 1;
+
+const PANEL_W: usize = 20;
+
+// 하드 드롭 번쩍임을 몇 단계로 어둡게 할지
+const LOCK_FLASH_STEPS: f32 = 2.0;
+// 하드 드롭 궤적이 사라질 때까지 흐려지는 단계 수
+const TRAIL_STEPS: f32 = 3.0;
+
+// 사이드 패널 줄 수(마지막 줄이 알림 줄)
+const PANEL_LINES: usize = 39;
+
+// 보드 아래 조작 안내 줄 수(`KeyBindings::help_lines`)
+const HELP_LINES: u16 = 3;
+
+// 좌표 눈금을 켰을 때 보드 왼쪽에 비워 두는 폭(줄 번호 두 자리와 간격)
+const RULER_W: u16 = 3;
+
+/// 보드 크기와 칸 폭으로 정해지는 화면 배치
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    cols: usize,
+    rows: usize,
+    // 칸 하나의 가로 문자 수(`CELL_W` 또는 `NARROW_CELL_W`)
+    cell_w: usize,
+    // 왼쪽 테두리의 화면 열(대전의 2P 보드는 1P 화면 오른쪽에서 시작한다)
+    left: u16,
+    // 보드를 위아래로 뒤집어 그린다(게임 규칙은 그대로, 바닥이 화면 위쪽에 온다)
+    flipped: bool,
+    // 왼쪽에 줄 번호, 아래 테두리 밑에 열 번호를 그린다(`left`는 이미 그만큼 밀려 있다)
+    ruler: bool,
+}
+
+impl Layout {
+    pub fn new(board: &Board, cell_w: usize) -> Self {
+        Self {
+            cols: board.width(),
+            rows: board.height(),
+            cell_w,
+            left: 0,
+            flipped: false,
+            ruler: false,
+        }
+    }
+
+    /// 보드 안쪽 폭(문자)
+    fn board_w(self) -> usize {
+        self.cols * self.cell_w
+    }
+
+    /// 아래쪽 테두리 y좌표
+    fn bottom_y(self) -> u16 {
+        BOARD_Y + (self.rows * CELL_H) as u16
+    }
+
+    /// 보드 `row`행의 `sub`번째 줄이 놓이는 화면 y좌표(뒤집으면 칸 안의 줄 순서도 뒤집힌다)
+    fn cell_y(self, row: usize, sub: usize) -> u16 {
+        let line = row * CELL_H + sub;
+        let line = if self.flipped {
+            self.rows * CELL_H - 1 - line
+        } else {
+            line
+        };
+        BOARD_Y + line as u16
+    }
+
+    /// 오른쪽 테두리와 한 칸 간격 뒤
+    fn panel_x(self) -> u16 {
+        self.left + (self.board_w() + 2 + 2) as u16
+    }
+
+    /// 조작 안내 첫 줄. 보드가 낮으면 사이드 패널 아래에 둔다.
+    /// 좌표 눈금을 켰으면 열 번호 줄만큼 내려간다.
+    fn help_y(self) -> u16 {
+        let bottom = BOARD_Y + (self.rows * CELL_H).max(PANEL_LINES) as u16 + 1;
+        if self.ruler {
+            bottom.max(self.bottom_y() + 2)
+        } else {
+            bottom
+        }
+    }
+
+    /// 게임 화면 전체에 필요한 터미널 크기: (왼쪽 여백)+보드+테두리, 간격, 사이드 패널 / 조작 안내까지
+    pub fn screen(self) -> (u16, u16) {
+        (self.panel_x() + PANEL_W as u16, self.help_y() + HELP_LINES)
+    }
+
+    /// 화면 위치가 보드 테두리 안이면 그 보드 열. 테두리와 바깥은 None.
+    pub fn board_col(self, column: u16, row: u16) -> Option<usize> {
+        if !(BOARD_Y..self.bottom_y()).contains(&row) {
+            return None;
+        }
+        let x = column.checked_sub(self.left + 1)? as usize;
+        (x < self.board_w()).then(|| x / self.cell_w)
+    }
+
+    pub fn fits(self, (cols, rows): (u16, u16)) -> bool {
+        let (w, h) = self.screen();
+        cols >= w && rows >= h
+    }
+}
+
+/// 화면의 한 칸에 보이는 것
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CellView {
+    Empty,
+    /// 격자선을 켰을 때의 빈칸
+    Grid,
+    Block(PieceKind),
+    Garbage,
+    /// 고스트 칸. 피스 색으로 칠할 때만 그 피스 종류가 있다.
+    Ghost(Option<PieceKind>),
+    /// 끈끈한 드롭이 확인을 기다리는 동안 밝게 칠한 착지 자리
+    Landing(PieceKind),
+    /// 막혀서 끝난 게임에서 새 피스가 쌓인 블록과 겹친 칸
+    Overlap,
+    /// 지워지는 중인 찬 줄의 남은 칸
+    Flash,
+    /// 하드 드롭으로 막 고정한 칸(단계가 오를수록 어두워진다)
+    LockFlash(u8),
+    /// 하드 드롭한 피스가 지나온 빈칸(단계가 오를수록 흐려진다)
+    Trail(u8),
+}
+
+/// 한 프레임에서 보드 칸을 정하는 상태(고정된 블록, 현재 피스, 고스트)
+struct Frame {
+    grid: Vec<Vec<Cell>>,
+    piece_kind: PieceKind,
+    piece: Vec<(i32, i32)>,
+    ghost: Vec<(i32, i32)>,
+    topped_out: bool,
+    // 줄 제거 애니메이션: 지워지는 줄들과 양옆에서부터 이미 사라진 칸 수
+    clearing: Vec<usize>,
+    cleared_cols: usize,
+    // 하드 드롭 번쩍임: 막 고정한 칸들과 지금 단계
+    flash: Vec<(i32, i32)>,
+    flash_step: u8,
+    // 하드 드롭 궤적과 지금 단계(궤적을 끄면 없음)
+    trail: Option<(DropTrail, u8)>,
+    // 빈칸에 격자선을 그리는지
+    grid_lines: bool,
+    // 고스트를 피스 색으로 칠하는지
+    ghost_tint: bool,
+    // 하드 드롭이 확인을 기다리는 중이라 고스트 자리를 밝게 칠하는지
+    landing: bool,
+}
+
+impl Frame {
+    fn capture(
+        game: &Game,
+        show_trail: bool,
+        show_flash: bool,
+        grid_lines: bool,
+        ghost: GhostStyle,
+    ) -> Self {
+        // 인비저블 모드에서 숨은 칸은 빈칸으로 그린다
+        let now = Instant::now();
+        let mut grid = game.board.grid.clone();
+        for (y, row) in grid.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if !game.cell_visible(x as i32, y as i32, now) {
+                    *cell = None;
+                }
+            }
+        }
+        let flash = game.lock_flash(now).filter(|_| show_flash);
+        let landing = game.drop_pending(now);
+        Self {
+            grid,
+            piece_kind: game.current.kind,
+            piece: game.current.absolute_cells(),
+            // 고스트를 끈 경우 그리지 않는다(확인을 기다리는 착지 자리는 그린다)
+            ghost: if game.show_ghost || landing {
+                game.ghost_cells()
+            } else {
+                Vec::new()
+            },
+            topped_out: game.end_reason == Some(EndReason::TopOut),
+            clearing: game.clearing.clone(),
+            // 진행도에 따라 양 끝에서 가운데로 사라진다
+            cleared_cols: game
+                .clear_progress(now)
+                .map_or(0, |p| (p * (game.board.width() / 2) as f32).ceil() as usize),
+            flash: flash.map_or_else(Vec::new, |(cells, _)| cells.to_vec()),
+            flash_step: flash.map_or(0, |(_, p)| (p * LOCK_FLASH_STEPS) as u8),
+            trail: game
+                .drop_trail(now)
+                .filter(|_| show_trail)
+                .map(|(trail, p)| (trail, (p * TRAIL_STEPS) as u8)),
+            grid_lines,
+            ghost_tint: ghost == GhostStyle::Tint,
+            landing,
+        }
+    }
+
+    fn empty(&self) -> CellView {
+        if self.grid_lines {
+            CellView::Grid
+        } else {
+            CellView::Empty
+        }
+    }
+
+    fn view(&self, col: usize, row: usize) -> CellView {
+        let pos = (col as i32, row as i32);
+        if self.clearing.contains(&row) {
+            let from_edge = col.min(self.grid[row].len() - 1 - col);
+            return if from_edge < self.cleared_cols {
+                self.empty()
+            } else {
+                CellView::Flash
+            };
+        }
+        // 피스가 바닥에 닿아 고스트와 겹치면 피스가 이긴다
+        if self.piece.contains(&pos) {
+            // 어디서 막혔는지 보이도록 겹친 칸을 강조한다
+            if self.topped_out && self.grid[row][col].is_some() {
+                CellView::Overlap
+            } else {
+                CellView::Block(self.piece_kind)
+            }
+        } else if let Some(block) = self.grid[row][col] {
+            if self.flash.contains(&pos) {
+                return CellView::LockFlash(self.flash_step);
+            }
+            match block {
+                Block::Piece(kind) => CellView::Block(kind),
+                Block::Garbage => CellView::Garbage,
+            }
+        } else if self.ghost.contains(&pos) {
+            if self.landing {
+                CellView::Landing(self.piece_kind)
+            } else {
+                CellView::Ghost(self.ghost_tint.then_some(self.piece_kind))
+            }
+        } else if let Some((trail, step)) = self.trail
+            && trail.contains(pos.0, pos.1)
+        {
+            CellView::Trail(step)
+        } else {
+            self.empty()
+        }
+    }
+}
+
+/// 직전에 그린 프레임을 기억해서 바뀐 칸만 다시 그리는 렌더러
+pub struct Renderer {
+    /// `None`이면 다음 프레임을 처음부터 전부 그린다
+    prev: Option<Frame>,
+    /// 미리보기로 보여 줄 다음 피스 수(1~`MAX_PREVIEW`)
+    previews: usize,
+    /// 칸 하나의 가로 문자 수
+    cell_w: usize,
+    /// 칸과 테두리에 쓰는 글자(`--ascii`면 ASCII만)
+    glyphs: Glyphs,
+    /// 레벨에 따라 테두리 색을 바꾸는지(설정 파일의 level_colors)
+    pub level_colors: bool,
+    /// 하드 드롭 궤적을 그리는지(설정 파일의 drop_trail)
+    pub drop_trail: bool,
+    /// 빈칸에 격자선을 그리는지(설정 파일의 grid, 실행 중 전환)
+    pub grid_lines: bool,
+    /// 쌓인 블록이 맨 위에서 이 줄 수 안으로 들어오면 그 줄들의 테두리를 빨갛게 칠한다(0이면 끈다)
+    pub danger_rows: usize,
+    /// 보드를 그리기 시작하는 화면 열(대전의 2P 렌더러만 0이 아니다)
+    pub left: u16,
+    /// 보드를 위아래로 뒤집어 그리는지(설정 파일의 flipped)
+    pub flipped: bool,
+    /// 줄을 지워 얻은 점수를 보드 위에 띄우는지(설정 파일의 score_popups)
+    pub score_popups: bool,
+    /// 보드 바깥에 줄/열 번호를 그리는지(`--debug`나 연습 모드에서 전환)
+    pub ruler: bool,
+    /// 고스트를 칠하는 방법(설정 파일의 ghost_style)
+    pub ghost_style: GhostStyle,
+    /// 하드 드롭 번쩍임, 궤적, 점수 알림을 모두 끄는지(설정 파일의 reduce_motion)
+    pub reduce_motion: bool,
+    /// 줄 목표가 있는 모드에서 Lines 밑에 진행 막대를 그리는지(설정 파일의 goal_bar)
+    pub goal_bar: bool,
+    /// 직전 프레임에서 점수 알림으로 덮은 보드 줄(다음 프레임에 그 줄을 다시 그린다)
+    prev_popup_row: Option<usize>,
+    prev_border: Color,
+    prev_danger: usize,
+    prev_queue: Vec<PieceKind>,
+    prev_theme: Theme,
+    prev_paused: bool,
+    prev_layout: Option<Layout>,
+    /// 직전 프레임에서 다시 그린 보드 칸 수(디버그 오버레이용)
+    cells_drawn: usize,
+}
+
+impl Renderer {
+    pub fn new(previews: usize, cell_w: usize, glyphs: Glyphs) -> Self {
+        Self {
+            prev: None,
+            previews,
+            cell_w,
+            glyphs,
+            level_colors: false,
+            drop_trail: false,
+            grid_lines: false,
+            danger_rows: 0,
+            left: 0,
+            flipped: false,
+            score_popups: false,
+            ruler: false,
+            ghost_style: GhostStyle::default(),
+            reduce_motion: false,
+            goal_bar: true,
+            prev_popup_row: None,
+            prev_border: Color::Reset,
+            prev_danger: 0,
+            prev_queue: Vec::new(),
+            prev_theme: Theme::default(),
+            prev_paused: false,
+            prev_layout: None,
+            cells_drawn: 0,
+        }
+    }
+
+    /// 이 렌더러의 칸 폭으로 `board`를 그릴 때의 화면 배치
+    pub fn layout(&self, board: &Board) -> Layout {
+        let margin = if self.ruler { RULER_W } else { 0 };
+        Layout {
+            left: self.left + margin,
+            flipped: self.flipped,
+            ruler: self.ruler,
+            ..Layout::new(board, self.cell_w)
+        }
+    }
+
+    pub fn cells_drawn(&self) -> usize {
+        self.cells_drawn
+    }
+
+    /// 화면을 지웠거나 안내 패널이 보드를 덮었을 때 다음 프레임을 전부 다시 그리게 한다.
+    pub fn invalidate(&mut self) {
+        self.prev = None;
+    }
+
+    pub fn draw(
+        &mut self,
+        out: &mut impl Write,
+        game: &Game,
+        keys: &KeyBindings,
+        theme: Theme,
+    ) -> io::Result<()> {
+        // 테마나 보드 크기가 바뀌거나 일시정지 패널이 닫히면 모든 칸을 다시 그려야 한다
+        let layout = self.layout(&game.board);
+        if theme != self.prev_theme
+            || game.paused != self.prev_paused
+            || self.prev_layout != Some(layout)
+        {
+            self.invalidate();
+        }
+        // 잠깐 보였다 사라지는 효과(궤적, 번쩍임, 점수 알림)는 reduce_motion 하나로 모두 끈다
+        let motion = !self.reduce_motion;
+        let frame = Frame::capture(
+            game,
+            self.drop_trail && motion,
+            motion,
+            self.grid_lines,
+            self.ghost_style,
+        );
+        let (cols, rows) = (game.board.width(), game.board.height());
+
+        // 레벨이 올라 테두리 색이 바뀌면 칸은 그대로 두고 테두리만 다시 그린다
+        let border = if self.level_colors {
+            border_color(game.level)
+        } else {
+            Color::Reset
+        };
+        // 위험 표시가 켜지거나 꺼져도 테두리만 다시 그린다
+        let danger = danger_rows(&game.board, self.danger_rows);
+        if self.prev.is_none() || border != self.prev_border || danger != self.prev_danger {
+            draw_frame(out, layout, keys, self.glyphs, border, danger)?;
+        }
+
+        // 점수 알림 글자가 덮은 줄은 칸이 그대로여도 다시 그린다
+        let popup = game
+            .score_popup(Instant::now())
+            .filter(|_| self.score_popups && motion);
+        let popup_row = popup.map(|(popup, _)| popup.row);
+        let covered = |row| popup_row == Some(row) || self.prev_popup_row == Some(row);
+
+        self.cells_drawn = 0;
+        match &self.prev {
+            None => {
+                for row in 0..rows {
+                    for col in 0..cols {
+                        draw_cell(
+                            out,
+                            layout,
+                            col,
+                            row,
+                            frame.view(col, row),
+                            theme,
+                            self.glyphs,
+                        )?;
+                    }
+                }
+                self.cells_drawn = cols * rows;
+            }
+            Some(prev) => {
+                for row in 0..rows {
+                    for col in 0..cols {
+                        let view = frame.view(col, row);
+                        if view != prev.view(col, row) || covered(row) {
+                            draw_cell(out, layout, col, row, view, theme, self.glyphs)?;
+                            self.cells_drawn += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((popup, progress)) = popup {
+            draw_score_popup(out, layout, popup, progress)?;
+        }
+
+        let full = self.prev.is_none();
+        // 큰 피스가 있는 세트는 다음 피스 하나만 보여 준다
+        let previews = if compact_preview() { 1 } else { self.previews };
+        let upcoming: Vec<PieceKind> = game.queue.iter().take(previews).copied().collect();
+        let preview = full || self.prev_queue != upcoming;
+        draw_side_panel(
+            out,
+            layout,
+            game,
+            theme,
+            self.glyphs,
+            full,
+            preview.then_some(&upcoming[..]),
+        )?;
+        if self.goal_bar {
+            draw_goal_bar(out, layout, game, self.glyphs)?;
+        }
+
+        self.prev = Some(frame);
+        self.prev_popup_row = popup_row;
+        self.prev_layout = Some(layout);
+        self.prev_queue = upcoming;
+        self.prev_theme = theme;
+        self.prev_border = border;
+        self.prev_danger = danger;
+        self.prev_paused = game.paused;
+        out.flush()
+    }
+}
+
+// 테두리 색이 이 레벨 수마다 다음 색으로 넘어간다
+const LEVELS_PER_BORDER_COLOR: u32 = 3;
+// 처음 레벨들은 원래 색, 그 뒤로 눈에 덜 띄는 색들을 돌아가며 쓴다
+const BORDER_COLORS: [Color; 6] = [
+    Color::Reset,
+    Color::DarkCyan,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkGreen,
+    Color::DarkYellow,
+];
+
+/// `level`(1부터)에서 쓸 테두리 색
+fn border_color(level: u32) -> Color {
+    let step = level.saturating_sub(1) / LEVELS_PER_BORDER_COLOR;
+    BORDER_COLORS[step as usize % BORDER_COLORS.len()]
+}
+
+/// 가장 높이 쌓인 열이 맨 위에서 `threshold`줄 안에 들어왔으면 빨갛게 칠할 위쪽 줄 수, 아니면 0
+fn danger_rows(board: &Board, threshold: usize) -> usize {
+    let threshold = threshold.min(board.height());
+    let stack = board.column_heights().into_iter().max().unwrap_or(0);
+    if threshold > 0 && stack > board.height() - threshold {
+        threshold
+    } else {
+        0
+    }
+}
+
+/// 테두리와 조작 안내처럼 프레임마다 바뀌지 않는 부분.
+/// 위쪽 `danger`줄(뒤집었으면 화면 아래쪽)은 테두리를 경고 색으로 칠한다.
+fn draw_frame(
+    out: &mut impl Write,
+    layout: Layout,
+    keys: &KeyBindings,
+    glyphs: Glyphs,
+    border: Color,
+    danger: usize,
+) -> io::Result<()> {
+    let board_char_w = layout.board_w();
+    let bot_y = layout.bottom_y();
+    let right_x = layout.left + (board_char_w + 1) as u16;
+    let horizontal = glyphs.horizontal.to_string().repeat(board_char_w);
+    let [top_left, top_right, bottom_left, bottom_right] = glyphs.corners;
+    let danger_lines = (danger * CELL_H) as u16;
+    let in_danger = |y: u16| {
+        if layout.flipped {
+            y >= bot_y - danger_lines
+        } else {
+            y < BOARD_Y + danger_lines
+        }
+    };
+    let warn = if danger > 0 { Color::Red } else { border };
+    let (top, bottom) = if layout.flipped {
+        (border, warn)
+    } else {
+        (warn, border)
+    };
+
+    queue!(
+        out,
+        cursor::MoveTo(layout.left, 0),
+        style::PrintStyledContent(format!("{top_left}{horizontal}{top_right}").with(top))
+    )?;
+    for y in BOARD_Y..bot_y {
+        let border = if in_danger(y) { Color::Red } else { border };
+        queue!(
+            out,
+            cursor::MoveTo(layout.left, y),
+            style::PrintStyledContent(glyphs.vertical.with(border)),
+            cursor::MoveTo(right_x, y),
+            style::PrintStyledContent(glyphs.vertical.with(border))
+        )?;
+    }
+    queue!(
+        out,
+        cursor::MoveTo(layout.left, bot_y),
+        style::PrintStyledContent(format!("{bottom_left}{horizontal}{bottom_right}").with(bottom))
+    )?;
+    if layout.ruler {
+        draw_ruler(out, layout)?;
+    }
+
+    // 조작 안내(보드 아래)
+    for (i, help) in keys.help_lines().iter().enumerate() {
+        queue!(
+            out,
+            cursor::MoveTo(layout.left, layout.help_y() + i as u16),
+            style::Print(help)
+        )?;
+    }
+    Ok(())
+}
+
+/// 좌표 눈금: 보드 왼쪽에 줄 번호(바닥이 1), 아래 테두리 밑에 열 번호(왼쪽이 1).
+/// 뒤집은 보드는 줄 번호도 함께 뒤집히고, 좁은 칸은 열 번호의 끝자리만 쓴다.
+fn draw_ruler(out: &mut impl Write, layout: Layout) -> io::Result<()> {
+    for row in 0..layout.rows {
+        let number = format!("{:>2} ", layout.rows - row);
+        queue!(
+            out,
+            cursor::MoveTo(layout.left - RULER_W, layout.cell_y(row, 0)),
+            style::PrintStyledContent(number.with(Color::DarkGrey))
+        )?;
+    }
+    let w = layout.cell_w;
+    let numbers: String = (1..=layout.cols)
+        .map(|col| {
+            if w > 2 {
+                format!("{col:^w$}")
+            } else {
+                format!("{:>w$}", col % 10)
+            }
+        })
+        .collect();
+    queue!(
+        out,
+        cursor::MoveTo(layout.left + 1, layout.bottom_y() + 1),
+        style::PrintStyledContent(numbers.with(Color::DarkGrey))
+    )
+}
+
+/// 지운 줄 자리 가운데에 `+점수`를 띄운다. 줄 제거 종류에 따라 색이 다르고 점점 어두워진다.
+fn draw_score_popup(
+    out: &mut impl Write,
+    layout: Layout,
+    popup: ScorePopup,
+    progress: f32,
+) -> io::Result<()> {
+    let (bright, dim) = if popup.perfect {
+        (Color::Yellow, Color::DarkYellow)
+    } else if popup.t_spin {
+        (Color::Magenta, Color::DarkMagenta)
+    } else if popup.cleared == 4 {
+        (Color::Cyan, Color::DarkCyan)
+    } else {
+        (Color::White, Color::Grey)
+    };
+    let color = match progress {
+        p if p < 0.5 => bright,
+        p if p < 0.8 => dim,
+        _ => Color::DarkGrey,
+    };
+    // 좁은 보드에서도 테두리를 넘지 않는다
+    let text: String = format!("+{}", popup.points)
+        .chars()
+        .take(layout.board_w())
+        .collect();
+    let x = layout.left + 1 + (layout.board_w().saturating_sub(text.len()) / 2) as u16;
+    queue!(
+        out,
+        cursor::MoveTo(x, layout.cell_y(popup.row, 0)),
+        style::PrintStyledContent(text.with(color).bold())
+    )
+}
+
+fn draw_cell(
+    out: &mut impl Write,
+    layout: Layout,
+    col: usize,
+    row: usize,
+    view: CellView,
+    theme: Theme,
+    glyphs: Glyphs,
+) -> io::Result<()> {
+    let w = layout.cell_w;
+    let x = layout.left + (1 + col * w) as u16;
+    for sub in 0..CELL_H {
+        queue!(out, cursor::MoveTo(x, layout.cell_y(row, sub)))?;
+        match view {
+            CellView::Block(kind) => {
+                queue!(
+                    out,
+                    style::PrintStyledContent(theme.block(kind, sub, w, glyphs))
+                )?;
+            }
+            CellView::Garbage => queue!(out, style::PrintStyledContent(theme.garbage(w, glyphs)))?,
+            CellView::Ghost(tint) => {
+                queue!(out, style::PrintStyledContent(theme.ghost(tint, w, glyphs)))?;
+            }
+            CellView::Landing(kind) => {
+                queue!(
+                    out,
+                    style::PrintStyledContent(theme.landing(kind, w, glyphs))
+                )?;
+            }
+            CellView::Overlap => queue!(out, style::PrintStyledContent(theme.overlap(w, glyphs)))?,
+            CellView::Flash => queue!(out, style::PrintStyledContent(theme.flash(w, glyphs)))?,
+            CellView::LockFlash(step) => {
+                queue!(
+                    out,
+                    style::PrintStyledContent(theme.lock_flash(step, w, glyphs))
+                )?;
+            }
+            CellView::Trail(step) => {
+                queue!(out, style::PrintStyledContent(theme.trail(step, w, glyphs)))?;
+            }
+            // 빈칸은 윗줄 가운데쯤에 점 하나
+            CellView::Empty if sub == 0 => {
+                let dot = format!("{:>d$}{:<r$}", ".", "", d = w / 2 + 1, r = w - w / 2 - 1);
+                queue!(out, style::PrintStyledContent(dot.with(Color::DarkGrey)))?;
+            }
+            CellView::Empty => queue!(out, style::Print(" ".repeat(w)))?,
+            // 격자선: 칸마다 윗줄 전체와 왼쪽 끝에 흐린 점
+            CellView::Grid => {
+                let len = if sub == 0 { w } else { 1 };
+                let line = format!("{:<w$}", glyphs.grid.to_string().repeat(len));
+                queue!(out, style::PrintStyledContent(line.with(Color::DarkGrey)))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 터미널이 너무 작을 때 보드 대신 보여 줄 안내(화면은 크기 변경 때 이미 지워져 있다)
+pub fn draw_too_small(
+    out: &mut impl Write,
+    layout: Layout,
+    (cols, rows): (u16, u16),
+) -> io::Result<()> {
+    let (need_w, need_h) = layout.screen();
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        style::PrintStyledContent("Terminal too small".with(Color::Red)),
+        cursor::MoveTo(0, 1),
+        style::Print(format!("need {need_w}x{need_h}, have {cols}x{rows}")),
+    )?;
+    out.flush()
+}
+
+/// 미리보기 칸(4x2)에 들어가지 않는 피스가 세트에 있으면 다음 피스를 한 줄에 한 칸씩
+/// 줄여 그린다(가로 5칸, 세로 4줄까지).
+fn compact_preview() -> bool {
+    let (cols, rows) = piece_set().preview_size();
+    cols > 4 || rows > 2
+}
+
+/// 사이드 패널. 글자는 매번, 피스 블록은 `full`일 때만 다시 그린다.
+/// 미리보기는 바뀌었을 때만 `preview`로 다음 피스들을 받아 다시 그린다.
+fn draw_side_panel(
+    out: &mut impl Write,
+    layout: Layout,
+    game: &Game,
+    theme: Theme,
+    glyphs: Glyphs,
+    full: bool,
+    preview: Option<&[PieceKind]>,
+) -> io::Result<()> {
+    let panel_x = layout.panel_x();
+    let w = layout.cell_w;
+    // 작은 미리보기는 보드 칸의 절반 폭
+    let mini_w = (w / 2).max(1);
+
+    // 보드 상단 기준 라인 인덱스(0부터)
+    for line in 0..PANEL_LINES as u16 {
+        let mut x = panel_x;
+        let text = match line {
+            // 오른쪽 절반에는 홀드한 피스와 (제한했으면) 남은 홀드 횟수. 다시 홀드할 수 없으면 흐리게.
+            0 => {
+                let half = PANEL_W / 2;
+                queue!(
+                    out,
+                    cursor::MoveTo(panel_x, BOARD_Y + line),
+                    style::PrintStyledContent(format!("{:<half$}", "NEXT").with(Color::White))
+                )?;
+                x += half as u16;
+                let (held, color) = match game.held {
+                    Some(kind) if game.can_hold() => (format!("{kind:?}"), theme.piece_color(kind)),
+                    Some(kind) => (format!("{kind:?}"), Color::DarkGrey),
+                    None => ("-".to_string(), Color::DarkGrey),
+                };
+                let left = game
+                    .holds_remaining
+                    .map_or(String::new(), |left| format!(" {left}"));
+                format!("{:<half$}", format!("HOLD {held}{left}")).with(color)
+            }
+            // 바로 다음 피스는 보드와 같은 크기로
+            2..=5 if let Some(upcoming) = preview => {
+                let (preview_row, sub, cols) = if compact_preview() {
+                    ((line - 2) as i32, 0, piece_set().preview_size().0 as usize)
+                } else {
+                    let row = ((line - 2) / CELL_H as u16) as i32;
+                    (row, ((line - 2) % CELL_H as u16) as usize, 4)
+                };
+                let next = upcoming[0];
+                let next_cells = next.preview_cells();
+
+                queue!(out, cursor::MoveTo(panel_x, BOARD_Y + line))?;
+                for pcol in 0..cols as i32 {
+                    if next_cells.contains(&(pcol, preview_row)) {
+                        queue!(
+                            out,
+                            style::PrintStyledContent(theme.block(next, sub, w, glyphs))
+                        )?;
+                    } else {
+                        queue!(out, style::Print(" ".repeat(w)))?;
+                    }
+                }
+                // 4칸(큰 세트는 5칸까지)을 그리고 남는 폭은 공백으로 채운다
+                queue!(out, style::Print(" ".repeat(PANEL_W - cols * w)))?;
+                continue;
+            }
+            // 그 뒤 피스들은 반 크기(칸 폭의 절반×1줄)로 두 개씩 나란히
+            7 | 8 | 10 | 11 if let Some(upcoming) = preview => {
+                let preview_row = ((line - 7) % 3) as i32;
+                let first = if line < 10 { 1 } else { 3 };
+
+                queue!(out, cursor::MoveTo(panel_x, BOARD_Y + line))?;
+                for i in first..first + 2 {
+                    match upcoming.get(i) {
+                        Some(&kind) => {
+                            let cells = kind.preview_cells();
+                            for pcol in 0..4i32 {
+                                if cells.contains(&(pcol, preview_row)) {
+                                    queue!(
+                                        out,
+                                        style::PrintStyledContent(
+                                            theme.mini_block(kind, mini_w, glyphs)
+                                        )
+                                    )?;
+                                } else {
+                                    queue!(out, style::Print(" ".repeat(mini_w)))?;
+                                }
+                            }
+                        }
+                        None => queue!(out, style::Print(" ".repeat(4 * mini_w)))?,
+                    }
+                    // 두 피스 사이 간격
+                    queue!(out, style::Print("  "))?;
+                }
+                // 남는 폭 채우기(칸 폭 4일 때는 8+2+8+2=PANEL_W라 남지 않는다)
+                queue!(
+                    out,
+                    style::Print(" ".repeat(PANEL_W - 2 * (4 * mini_w + 2)))
+                )?;
+                continue;
+            }
+            12 => format!("{:<PANEL_W$}", format!("Score: {}", game.score)).with(Color::White),
+            // 마라톤과 스프린트는 목표 줄 수까지 함께, 끝없는 모드는 지운 줄 수만
+            14 => {
+                let lines = match game.mode.line_goal() {
+                    Some(goal) => format!("Lines: {} / {goal}", game.lines),
+                    None => format!("Lines: {}", game.lines),
+                };
+                format!("{lines:<PANEL_W$}").with(Color::White)
+            }
+            16 => format!("{:<PANEL_W$}", format!("Level: {}", game.level)).with(Color::White),
+            18 => {
+                // 콤보는 두 번째 연속 제거부터 1로 센다
+                let combo = game.combo.max(0);
+                format!("{:<PANEL_W$}", format!("Combo: {combo}")).with(Color::White)
+            }
+            20 => {
+                let b2b = if game.back_to_back { "B2B" } else { "" };
+                format!("{b2b:<PANEL_W$}").with(Color::Yellow)
+            }
+            22 if game.mode.time_limit().is_some() => {
+                let left = game.remaining().unwrap_or_default();
+                let time = format!("Left: {}", format_time(left));
+                format!("{time:<PANEL_W$}").with(Color::White)
+            }
+            // 대전에서 받아 둔 쓰레기 줄(다음에 못 지우고 고정하면 올라온다)
+            22 if game.incoming_garbage > 0 => {
+                let incoming = format!("Incoming: {}", game.incoming_garbage);
+                format!("{incoming:<PANEL_W$}").with(Color::Red)
+            }
+            // 연습 모드는 시간 대신 되돌릴 수 있는 고정 수
+            22 if game.mode == GameMode::Practice => {
+                let undo = format!("Undo: {}", game.undo_depth());
+                format!("{undo:<PANEL_W$}").with(Color::White)
+            }
+            22 if game.mode.is_timed() => {
+                let time = format!("Time: {}", format_time(game.elapsed()));
+                format!("{time:<PANEL_W$}").with(Color::White)
+            }
+            // 받아 둔 쓰레기 줄이 올라오거나 상쇄되면 그 줄을 지운다
+            22 => format!("{:<PANEL_W$}", "").with(Color::White),
+            24 => format!("{:<PANEL_W$}", format!("Pieces: {}", game.pieces_placed()))
+                .with(Color::White),
+            26 => {
+                let pps = format!("PPS: {:.2}", game.pieces_per_second());
+                format!("{pps:<PANEL_W$}").with(Color::White)
+            }
+            // 종류별 개수: 피스 모양 대신 한 칸짜리 블록으로 색을 보여 준다.
+            // 일곱 종류가 넘는 세트는 한 줄에 두 종류씩.
+            28..=34 => {
+                let per_line = piece_set().len().div_ceil(7);
+                let slot = PANEL_W / per_line;
+                for i in 0..per_line {
+                    let slot_x = panel_x + (i * slot) as u16;
+                    let index = (line - 28) as usize * per_line + i;
+                    let Some(kind) = piece_set().kinds().nth(index) else {
+                        queue!(
+                            out,
+                            cursor::MoveTo(slot_x, BOARD_Y + line),
+                            style::Print(" ".repeat(slot))
+                        )?;
+                        continue;
+                    };
+                    if full {
+                        queue!(
+                            out,
+                            cursor::MoveTo(slot_x, BOARD_Y + line),
+                            style::PrintStyledContent(theme.block(kind, 0, w, glyphs))
+                        )?;
+                    }
+                    let count = format!(" {}", game.piece_counts[kind.index()]);
+                    queue!(
+                        out,
+                        cursor::MoveTo(slot_x + w as u16, BOARD_Y + line),
+                        style::PrintStyledContent(
+                            format!("{count:<pad$}", pad = slot - w).with(Color::White)
+                        )
+                    )?;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        queue!(
+            out,
+            cursor::MoveTo(x, BOARD_Y + line),
+            style::PrintStyledContent(text)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 줄 목표가 있는 모드에서 패널의 Lines 바로 밑 줄에 진행 막대를 그린다.
+fn draw_goal_bar(
+    out: &mut impl Write,
+    layout: Layout,
+    game: &Game,
+    glyphs: Glyphs,
+) -> io::Result<()> {
+    let Some(goal) = game.mode.line_goal() else {
+        return Ok(());
+    };
+    let color = if game.lines >= goal {
+        Color::Green
+    } else {
+        Color::Cyan
+    };
+    queue!(
+        out,
+        cursor::MoveTo(layout.panel_x(), BOARD_Y + 15),
+        style::PrintStyledContent(progress_bar(game.lines, goal, PANEL_W, glyphs).with(color))
+    )
+}
+
+/// `goal`줄 중 `lines`줄 만큼 찬 `width`칸짜리 막대. 목표를 넘겨도 꽉 찬 막대다.
+fn progress_bar(lines: u32, goal: u32, width: usize, glyphs: Glyphs) -> String {
+    let filled = (lines.min(goal) as usize * width) / goal.max(1) as usize;
+    let [full, empty] = glyphs.bar;
+    std::iter::repeat_n(full, filled)
+        .chain(std::iter::repeat_n(empty, width - filled))
+        .collect()
+}
+
+/// 플레이 시간을 mm:ss.mmm 형식으로 만든다.
+pub fn format_time(elapsed: Duration) -> String {
+    let ms = elapsed.as_millis();
+    format!("{:02}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
+
+/// 보드 중앙에 여러 줄짜리 안내 패널을 그린다. 좁은 보드에서는 화면 왼쪽 끝에서 멈춘다.
+fn draw_overlay(
+    out: &mut impl Write,
+    layout: Layout,
+    lines: &[String],
+    bg: Color,
+) -> io::Result<()> {
+    let cx = layout.left + (layout.board_w() / 2) as u16;
+    let cy = (layout.rows * CELL_H / 2) as u16;
+
+    // 가장 긴 줄에 맞춰 모든 줄을 같은 폭으로 채운다
+    let w = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let left = cx.saturating_sub(w as u16 / 2);
+    let top = cy.saturating_sub(lines.len() as u16 / 2);
+
+    for (i, line) in lines.iter().enumerate() {
+        queue!(
+            out,
+            cursor::MoveTo(left, top + i as u16),
+            style::PrintStyledContent(format!("{line:<w$}").on(bg).with(Color::White)),
+        )?;
+    }
+    out.flush()
+}
+
+pub fn draw_game_over(out: &mut impl Write, layout: Layout, game: &Game) -> io::Result<()> {
+    // 화면 중앙에 GAME OVER(목표 달성 시 YOU WIN) 패널 배치
+    let (title, bg) = match game.end_reason {
+        Some(EndReason::GoalReached) => ("  YOU WIN  ", Color::DarkGreen),
+        Some(EndReason::TimeUp) => ("  TIME UP  ", Color::DarkBlue),
+        Some(EndReason::TopOut) | None => ("  GAME OVER  ", Color::Red),
+    };
+    // 시간 기록 모드에서 완주하면 점수 대신 기록을 보여 준다
+    let result = if game.mode.is_timed() && game.won() {
+        format!("  Time: {}  ", format_time(game.elapsed()))
+    } else {
+        format!("  Score: {}  ", game.score)
+    };
+    let lines = [title.to_string(), result, "  R Retry  Q Quit  ".to_string()];
+    draw_overlay(out, layout, &lines, bg)
+}
+
+/// 대전이 끝난 뒤 보드마다 띄우는 결과. 네트워크 대전은 다시 하기 없이 종료만 안내한다.
+pub fn draw_versus_result(
+    out: &mut impl Write,
+    layout: Layout,
+    won: bool,
+    retry: bool,
+) -> io::Result<()> {
+    let (title, bg) = if won {
+        ("  WINNER  ", Color::DarkGreen)
+    } else {
+        ("  GAME OVER  ", Color::Red)
+    };
+    let keys = if retry {
+        "  R Retry  Q Quit  "
+    } else {
+        "  Q Quit  "
+    };
+    let lines = [title.to_string(), keys.to_string()];
+    draw_overlay(out, layout, &lines, bg)
+}
+
+/// 게임 오버 뒤 한 판 통계. 닫으면 결과 패널로 돌아간다.
+pub fn draw_stats(out: &mut impl Write, layout: Layout, game: &Game) -> io::Result<()> {
+    let [singles, doubles, triples, tetrises] = game.clear_counts;
+    let rows = [
+        ("Singles", singles.to_string()),
+        ("Doubles", doubles.to_string()),
+        ("Triples", triples.to_string()),
+        ("Tetrises", tetrises.to_string()),
+        ("T-Spins", game.t_spins.to_string()),
+        ("Max combo", game.max_combo.to_string()),
+        ("Soft drop", game.soft_drop_cells.to_string()),
+        ("Hard drop", game.hard_drop_cells.to_string()),
+        ("Pieces", game.pieces_placed().to_string()),
+        ("Time", format_time(game.elapsed())),
+        ("PPS", format!("{:.2}", game.pieces_per_second())),
+    ];
+    let mut lines = vec!["  STATS".to_string(), String::new()];
+    lines.extend(
+        rows.iter()
+            .map(|(label, value)| format!("  {label:<10}{value:>10}  ")),
+    );
+    lines.push(String::new());
+    lines.push("  Enter Back".to_string());
+    draw_overlay(out, layout, &lines, Color::DarkBlue)
+}
+
+/// 사이드 패널 아래쪽에 짧은 알림 한 줄
+pub fn draw_status(out: &mut impl Write, layout: Layout, text: &str) -> io::Result<()> {
+    queue!(
+        out,
+        cursor::MoveTo(layout.panel_x(), BOARD_Y + PANEL_LINES as u16 - 1),
+        style::PrintStyledContent(format!("{text:<PANEL_W$}").with(Color::Green))
+    )?;
+    out.flush()
+}
+
+/// `--debug`로 켠 성능 오버레이. 사이드 패널 아래쪽 빈 줄들에 그린다.
+pub fn draw_debug(
+    out: &mut impl Write,
+    layout: Layout,
+    fps: usize,
+    frame_ms: f64,
+    drop_ms: u64,
+    cells: usize,
+) -> io::Result<()> {
+    let lines = [
+        format!("FPS {fps}  {frame_ms:.2}ms"),
+        format!("Drop {drop_ms}ms"),
+        format!("Cells {cells}"),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        queue!(
+            out,
+            cursor::MoveTo(layout.panel_x(), BOARD_Y + 35 + i as u16),
+            style::PrintStyledContent(format!("{line:<PANEL_W$}").with(Color::DarkGrey))
+        )?;
+    }
+    out.flush()
+}
+
+/// 이벤트에 띄울 알림. `showing`은 이미 떠 있는 알림이 있는지다.
+/// 같은 줄 제거로 퍼펙트 클리어도 했으면 레벨 업은 그 알림을 덮지 않는다.
+pub fn banner_for(event: GameEvent, showing: bool) -> Option<&'static str> {
+    match event {
+        GameEvent::PerfectClear => Some("PERFECT CLEAR"),
+        GameEvent::Rewound => Some("REWIND"),
+        GameEvent::LevelUp if !showing => Some("LEVEL UP"),
+        _ => None,
+    }
+}
+
+/// 퍼펙트 클리어처럼 플레이 중 잠깐 보드 가운데 띄우는 알림
+pub fn draw_banner(out: &mut impl Write, layout: Layout, text: &str) -> io::Result<()> {
+    draw_overlay(out, layout, &[format!("  {text}  ")], Color::DarkYellow)
+}
+
+/// 시작 메뉴
+pub fn draw_menu(
+    out: &mut impl Write,
+    layout: Layout,
+    menu: &Menu,
+    keys: &KeyBindings,
+) -> io::Result<()> {
+    draw_overlay(out, layout, &menu.lines(keys), Color::DarkBlue)
+}
+
+/// 게임 시작 전 남은 초
+pub fn draw_countdown(out: &mut impl Write, layout: Layout, secs: u64) -> io::Result<()> {
+    draw_overlay(out, layout, &[format!("   {secs}   ")], Color::DarkGrey)
+}
+
+/// 실수로 누른 키가 바로 실행되지 않도록 Y/N을 묻는 패널
+pub fn draw_confirm(out: &mut impl Write, layout: Layout, question: &str) -> io::Result<()> {
+    let lines = [format!("  {question}  "), "  Y Yes  N No  ".to_string()];
+    draw_overlay(out, layout, &lines, Color::DarkMagenta)
+}
+
+pub fn draw_paused(out: &mut impl Write, layout: Layout) -> io::Result<()> {
+    let lines = [
+        "    PAUSED    ".to_string(),
+        "  P Resume  Q Quit  ".to_string(),
+    ];
+    draw_overlay(out, layout, &lines, Color::Blue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constris::{BOARD_H, BOARD_W, GameConfig, Piece};
+
+    /// 그린 출력을 화면 버퍼에 적용한다. 커서 이동(`ESC[행;열H`)과 글자만 따르고
+    /// 색 같은 나머지 이스케이프 시퀀스는 건너뛴다.
+    fn apply_to_screen(screen: &mut [Vec<char>], out: &[u8]) {
+        let text = String::from_utf8_lossy(out);
+        let mut chars = text.chars().peekable();
+        let (mut row, mut col) = (0, 0);
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                if let Some(cell) = screen.get_mut(row).and_then(|line| line.get_mut(col)) {
+                    *cell = c;
+                }
+                col += 1;
+                continue;
+            }
+            chars.next_if_eq(&'[');
+            let mut params = String::new();
+            while let Some(&p) = chars.peek()
+                && !p.is_ascii_alphabetic()
+            {
+                params.push(p);
+                chars.next();
+            }
+            if chars.next() == Some('H') {
+                let (r, c) = params.split_once(';').unwrap_or(("1", "1"));
+                row = r.parse::<usize>().unwrap_or(1) - 1;
+                col = c.parse::<usize>().unwrap_or(1) - 1;
+            }
+        }
+    }
+
+    #[test]
+    fn default_board_needs_64_by_45() {
+        let layout = Layout::new(&Board::new(), CELL_W);
+        assert_eq!(layout.screen(), (64, 45));
+        assert!(layout.fits((80, 45)) && !layout.fits((63, 45)));
+        // 낮은 보드도 사이드 패널과 조작 안내가 들어갈 높이는 필요하다
+        let small = Layout::new(&Board::with_size(6, 12), CELL_W);
+        assert_eq!(small.screen(), (48, 44));
+        // 테두리 한 칸 뒤부터 CELL_W문자가 한 열이다
+        let row = BOARD_Y;
+        assert_eq!(layout.board_col(1, row), Some(0));
+        assert_eq!(layout.board_col(4, row), Some(0));
+        assert_eq!(layout.board_col(5, row), Some(1));
+        assert_eq!(layout.board_col(40, row), Some(BOARD_W - 1));
+        // 테두리, 사이드 패널, 보드 위아래는 보드가 아니다
+        assert_eq!(layout.board_col(0, row), None);
+        assert_eq!(layout.board_col(41, row), None);
+        assert_eq!(layout.board_col(60, row), None);
+        assert_eq!(layout.board_col(5, row - 1), None);
+        assert_eq!(layout.board_col(5, layout.bottom_y()), None);
+        assert_eq!(layout.board_col(5, layout.bottom_y() - 1), Some(1));
+    }
+
+    #[test]
+    fn narrow_cells_halve_the_board_width() {
+        let layout = Layout::new(&Board::new(), NARROW_CELL_W);
+        assert_eq!(layout.screen(), (44, 45));
+        assert_eq!(layout.board_col(2, BOARD_Y), Some(0));
+        assert_eq!(layout.board_col(3, BOARD_Y), Some(1));
+    }
+
+    #[test]
+    fn second_board_starts_to_the_right_of_the_first() {
+        let mut renderer = Renderer::new(1, NARROW_CELL_W, Glyphs::UNICODE);
+        renderer.left = 46;
+        let layout = renderer.layout(&Board::new());
+        assert_eq!(layout.screen(), (46 + 44, 45));
+        assert_eq!(layout.board_col(46 + 3, BOARD_Y), Some(1));
+        assert_eq!(layout.board_col(3, BOARD_Y), None);
+
+        let mut screen = vec![vec![' '; 90]; 45];
+        let mut out = Vec::new();
+        renderer
+            .draw(
+                &mut out,
+                &Game::with_seed(1),
+                &KeyBindings::player_two(),
+                Theme::Classic,
+            )
+            .unwrap();
+        apply_to_screen(&mut screen, &out);
+        assert!(
+            screen
+                .iter()
+                .all(|line| line[..46].iter().all(|&c| c == ' '))
+        );
+        assert_eq!(screen[0][46], Glyphs::UNICODE.corners[0]);
+        let panel: String = screen[BOARD_Y as usize + 12][46 + 24..].iter().collect();
+        assert!(panel.starts_with("Score: 0"));
+    }
+
+    #[test]
+    fn flipped_board_draws_the_floor_at_the_top() {
+        let mut renderer = Renderer::new(1, NARROW_CELL_W, Glyphs::UNICODE);
+        renderer.flipped = true;
+        let layout = renderer.layout(&Board::new());
+        assert_eq!(layout.cell_y(BOARD_H - 1, 1), BOARD_Y);
+        assert_eq!(layout.cell_y(0, 0), layout.bottom_y() - 1);
+
+        let mut game = Game::with_seed(1);
+        game.board.grid[BOARD_H - 1][0] = Some(Block::Garbage);
+        let mut screen = vec![vec![' '; 60]; 45];
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+            .unwrap();
+        apply_to_screen(&mut screen, &out);
+        let bottom = layout.bottom_y() as usize;
+        assert_ne!(screen[BOARD_Y as usize][1], ' ');
+        assert_eq!(screen[bottom - 1][1], ' ');
+    }
+
+    #[test]
+    fn ruler_numbers_rows_from_the_floor_and_columns_from_the_left() {
+        let mut renderer = Renderer::new(1, CELL_W, Glyphs::ASCII);
+        let plain = renderer.layout(&Board::new());
+        renderer.ruler = true;
+        let layout = renderer.layout(&Board::new());
+        assert_eq!(layout.left, RULER_W);
+        assert_eq!(
+            layout.screen(),
+            (plain.screen().0 + RULER_W, plain.screen().1 + 1)
+        );
+
+        let game = Game::with_seed(1);
+        let mut screen = vec![vec![' '; 80]; 46];
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+            .unwrap();
+        apply_to_screen(&mut screen, &out);
+        let line = |y: u16| screen[y as usize].iter().collect::<String>();
+        assert!(line(layout.cell_y(BOARD_H - 1, 0)).starts_with(" 1 |"));
+        assert!(line(layout.cell_y(0, 0)).starts_with("20 |"));
+        let columns = line(layout.bottom_y() + 1);
+        assert!(columns[RULER_W as usize + 1..].starts_with(" 1   2   3 "));
+        assert!(columns.contains(" 10 "));
+
+        // 뒤집으면 바닥 줄 번호가 맨 위에 온다
+        renderer.flipped = true;
+        assert_eq!(
+            renderer.layout(&Board::new()).cell_y(BOARD_H - 1, 0),
+            BOARD_Y + 1
+        );
+    }
+
+    #[test]
+    fn border_color_cycles_every_few_levels() {
+        assert_eq!(border_color(1), Color::Reset);
+        assert_eq!(border_color(3), Color::Reset);
+        assert_eq!(border_color(4), Color::DarkCyan);
+        assert_eq!(border_color(16), Color::DarkYellow);
+        assert_eq!(border_color(19), Color::Reset);
+    }
+
+    #[test]
+    fn level_up_redraws_only_the_border() {
+        let mut game = Game::with_seed(1);
+        let keys = KeyBindings::default();
+        let mut renderer = Renderer::new(1, CELL_W, Glyphs::UNICODE);
+        renderer.level_colors = true;
+        renderer
+            .draw(&mut Vec::new(), &game, &keys, Theme::Classic)
+            .unwrap();
+        game.level = 4;
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &keys, Theme::Classic)
+            .unwrap();
+        assert_eq!(renderer.cells_drawn(), 0);
+        assert!(String::from_utf8_lossy(&out).contains('\u{250c}'));
+    }
+
+    #[test]
+    fn stack_near_the_top_tints_the_border() {
+        let mut board = Board::new();
+        assert_eq!(danger_rows(&board, 4), 0);
+        // 맨 위에서 5번째 줄까지는 아직 괜찮고, 4번째 줄에 닿으면 위험
+        board.grid[4][0] = Some(constris::Block::Garbage);
+        assert_eq!(danger_rows(&board, 4), 0);
+        board.grid[3][0] = Some(constris::Block::Garbage);
+        assert_eq!(danger_rows(&board, 4), 4);
+        assert_eq!(danger_rows(&board, 0), 0);
+
+        let mut game = Game::with_seed(1);
+        let keys = KeyBindings::default();
+        let mut renderer = Renderer::new(1, CELL_W, Glyphs::UNICODE);
+        renderer.danger_rows = 4;
+        renderer
+            .draw(&mut Vec::new(), &game, &keys, Theme::Classic)
+            .unwrap();
+        game.board.grid[3][BOARD_W - 1] = Some(constris::Block::Garbage);
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &keys, Theme::Classic)
+            .unwrap();
+        // 테두리와 새로 생긴 칸만 다시 그린다
+        assert_eq!(renderer.cells_drawn(), 1);
+        let red = format!("{}", style::SetForegroundColor(Color::Red));
+        assert!(String::from_utf8_lossy(&out).contains(&red));
+    }
+
+    #[test]
+    fn turning_on_grid_lines_redraws_the_empty_cells() {
+        let game = Game::with_seed(1);
+        let keys = KeyBindings::default();
+        let mut renderer = Renderer::new(1, CELL_W, Glyphs::UNICODE);
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &keys, Theme::Classic)
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&out).contains('\u{00b7}'));
+
+        renderer.grid_lines = true;
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &keys, Theme::Classic)
+            .unwrap();
+        // 피스와 고스트가 차지하지 않은 칸은 모두 격자선으로 다시 그린다
+        let frame = Frame::capture(&game, false, true, true, GhostStyle::Grey);
+        let grid = (0..BOARD_H)
+            .flat_map(|row| (0..BOARD_W).map(move |col| (col, row)))
+            .filter(|&(col, row)| frame.view(col, row) == CellView::Grid)
+            .count();
+        assert!(grid > BOARD_W * (BOARD_H - 4));
+        assert_eq!(renderer.cells_drawn(), grid);
+        assert!(String::from_utf8_lossy(&out).contains('\u{00b7}'));
+    }
+
+    #[test]
+    fn hard_drop_trail_can_be_turned_off() {
+        let mut game = Game::with_seed(1);
+        game.apply(constris::Input::HardDrop);
+        let (trail, _) = game.drop_trail(Instant::now()).expect("trail");
+        // 새 피스와 그 고스트가 닿지 않는 가운데 줄
+        let (col, row) = (trail.left as usize, (trail.bottom / 2) as usize);
+        assert!(matches!(
+            Frame::capture(&game, true, true, false, GhostStyle::Grey).view(col, row),
+            CellView::Trail(_)
+        ));
+        assert!(matches!(
+            Frame::capture(&game, false, true, false, GhostStyle::Grey).view(col, row),
+            CellView::Empty
+        ));
+    }
+
+    #[test]
+    fn reduce_motion_hides_the_drop_trail_and_lock_flash() {
+        let mut game = Game::with_seed(1);
+        game.apply(constris::Input::HardDrop);
+        let keys = KeyBindings::default();
+        let effects = |reduce_motion| {
+            let mut renderer = Renderer::new(1, CELL_W, Glyphs::UNICODE);
+            renderer.drop_trail = true;
+            renderer.reduce_motion = reduce_motion;
+            renderer
+                .draw(&mut Vec::new(), &game, &keys, Theme::Classic)
+                .unwrap();
+            let frame = renderer.prev.expect("drawn");
+            (0..BOARD_H)
+                .flat_map(|row| (0..BOARD_W).map(move |col| (col, row)))
+                .filter(|&(col, row)| {
+                    matches!(
+                        frame.view(col, row),
+                        CellView::Trail(_) | CellView::LockFlash(_)
+                    )
+                })
+                .count()
+        };
+        assert!(effects(false) > 0);
+        assert_eq!(effects(true), 0);
+    }
+
+    #[test]
+    fn tinted_ghost_carries_the_current_piece() {
+        let game = Game::with_seed(1);
+        let (col, row) = game.ghost_cells()[0];
+        let view = |style| {
+            Frame::capture(&game, false, true, false, style).view(col as usize, row as usize)
+        };
+        assert_eq!(view(GhostStyle::Grey), CellView::Ghost(None));
+        assert_eq!(
+            view(GhostStyle::Tint),
+            CellView::Ghost(Some(game.current.kind))
+        );
+    }
+
+    #[test]
+    fn pending_sticky_drop_highlights_the_landing_spot() {
+        let mut game = Game::with_seed(1);
+        game.sticky_drop = Duration::from_secs(60);
+        game.show_ghost = false;
+        let (col, row) = game.ghost_cells()[0];
+        let view = |game: &Game| {
+            Frame::capture(game, false, true, false, GhostStyle::Grey)
+                .view(col as usize, row as usize)
+        };
+        assert_eq!(view(&game), CellView::Empty);
+        assert!(!game.confirm_hard_drop(Instant::now()));
+        // 고스트를 꺼 두어도 착지 자리는 보인다
+        assert_eq!(view(&game), CellView::Landing(game.current.kind));
+        game.apply(constris::Input::Left);
+        assert_ne!(view(&game), CellView::Landing(game.current.kind));
+    }
+
+    #[test]
+    fn formats_time_as_minutes_seconds_millis() {
+        assert_eq!(format_time(Duration::from_millis(83_456)), "01:23.456");
+        assert_eq!(format_time(Duration::ZERO), "00:00.000");
+    }
+
+    #[test]
+    fn line_goal_modes_show_progress_toward_the_goal() {
+        assert_eq!(progress_bar(10, 40, 8, Glyphs::ASCII), "##------");
+        assert_eq!(progress_bar(0, 40, 4, Glyphs::ASCII), "----");
+        assert_eq!(progress_bar(45, 40, 4, Glyphs::ASCII), "####");
+
+        let panel = |mode, goal_bar| {
+            let mut game = Game::with_config(GameConfig {
+                mode,
+                ..GameConfig::default()
+            });
+            game.lines = 10;
+            let mut renderer = Renderer::new(1, CELL_W, Glyphs::ASCII);
+            renderer.goal_bar = goal_bar;
+            let mut out = Vec::new();
+            renderer
+                .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+                .unwrap();
+            String::from_utf8_lossy(&out).into_owned()
+        };
+        let sprint = panel(GameMode::Sprint, true);
+        assert!(sprint.contains("Lines: 10 / 40"));
+        assert!(sprint.contains(&progress_bar(10, 40, PANEL_W, Glyphs::ASCII)));
+        assert!(!panel(GameMode::Sprint, false).contains("#####-----"));
+        // 끝없는 모드는 목표도 막대도 없다
+        let endless = panel(GameMode::Endless, true);
+        assert!(endless.contains("Lines: 10 "));
+        assert!(!endless.contains(" / "));
+        assert!(!endless.contains("#####-----"));
+    }
+
+    #[test]
+    fn stats_screen_lists_clear_counts() {
+        let mut game = Game::with_seed(1);
+        game.clear_counts = [3, 0, 1, 2];
+        game.max_combo = 4;
+        game.hard_drop_cells = 57;
+        let mut out = Vec::new();
+        draw_stats(&mut out, Layout::new(&game.board, CELL_W), &game).unwrap();
+        let text = String::from_utf8_lossy(&out);
+        assert!(text.contains("Tetrises           2"));
+        assert!(text.contains("Max combo          4"));
+        assert!(text.contains("Hard drop         57"));
+    }
+
+    #[test]
+    fn highlights_cells_where_the_spawn_overlaps() {
+        let mut game = Game::with_seed(1);
+        for row in 0..2 {
+            for col in 3..7 {
+                game.board.grid[row][col] = Some(Block::Piece(PieceKind::O));
+            }
+        }
+        game.hard_drop();
+        assert_eq!(game.end_reason, Some(EndReason::TopOut));
+
+        let mut out = Vec::new();
+        Renderer::new(1, CELL_W, Glyphs::UNICODE)
+            .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+            .unwrap();
+        assert!(String::from_utf8_lossy(&out).contains('\u{2573}'));
+    }
+
+    #[test]
+    fn draws_the_requested_number_of_previews() {
+        let game = Game::with_seed(3);
+        let blocks = |previews, cell_w| {
+            let mut out = Vec::new();
+            Renderer::new(previews, cell_w, Glyphs::UNICODE)
+                .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+                .unwrap();
+            String::from_utf8_lossy(&out).matches('\u{2588}').count()
+        };
+        // 작은 미리보기 한 개는 4칸 × 칸 폭의 절반
+        assert_eq!(blocks(2, CELL_W), blocks(1, CELL_W) + 4 * CELL_W / 2);
+        assert_eq!(blocks(5, CELL_W), blocks(1, CELL_W) + 4 * 4 * CELL_W / 2);
+        assert_eq!(
+            blocks(2, NARROW_CELL_W),
+            blocks(1, NARROW_CELL_W) + 4 * NARROW_CELL_W / 2
+        );
+    }
+
+    #[test]
+    fn ascii_mode_draws_board_and_preview_without_unicode() {
+        let game = Game::with_seed(3);
+        let mut out = Vec::new();
+        Renderer::new(3, CELL_W, Glyphs::ASCII)
+            .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+            .unwrap();
+        let text = String::from_utf8_lossy(&out);
+        assert!(text.contains("+----") && text.contains("[][]") && text.contains('#'));
+        assert!(!text.contains('\u{2588}') && !text.contains('\u{2500}'));
+    }
+
+    #[test]
+    fn side_panel_shows_the_held_piece() {
+        let mut game = Game::with_seed(1);
+        let kind = game.current.kind;
+        game.hold();
+        let mut out = Vec::new();
+        Renderer::new(1, CELL_W, Glyphs::UNICODE)
+            .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+            .unwrap();
+        assert!(String::from_utf8_lossy(&out).contains(&format!("HOLD {kind:?}")));
+
+        // 홀드 횟수를 제한하면 남은 횟수도 보인다
+        game.holds_remaining = Some(0);
+        let mut out = Vec::new();
+        Renderer::new(1, CELL_W, Glyphs::UNICODE)
+            .draw(&mut out, &game, &KeyBindings::default(), Theme::Classic)
+            .unwrap();
+        assert!(String::from_utf8_lossy(&out).contains(&format!("HOLD {kind:?} 0")));
+    }
+
+    #[test]
+    fn moving_a_piece_leaves_no_ghost_behind() {
+        let mut game = Game::with_seed(1);
+        let keys = KeyBindings::default();
+        let mut renderer = Renderer::new(1, CELL_W, Glyphs::UNICODE);
+        let (w, h) = Layout::new(&game.board, CELL_W).screen();
+        let mut screen = vec![vec![' '; w as usize]; h as usize];
+        let ghost_cols = |game: &Game| {
+            let mut cols: Vec<usize> = game
+                .ghost_cells()
+                .iter()
+                .map(|&(x, _)| x as usize)
+                .collect();
+            cols.sort();
+            cols.dedup();
+            cols
+        };
+        // 보드 칸의 윗줄 첫 글자에 고스트 글자가 남아 있는 열
+        let ghost_on_screen = |screen: &[Vec<char>], row: usize| {
+            (0..BOARD_W)
+                .filter(|&col| {
+                    let y = BOARD_Y as usize + row * CELL_H;
+                    screen[y][1 + col * CELL_W] == '\u{2591}'
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &keys, Theme::Classic)
+            .unwrap();
+        apply_to_screen(&mut screen, &out);
+        let old = ghost_cols(&game);
+        let bottom = BOARD_H - 1;
+        assert!(!ghost_on_screen(&screen, bottom).is_empty());
+
+        // 피스가 높이 떠 있는 채로 왼쪽으로 옮기면 고스트의 오른쪽 끝 열이 비어야 한다
+        assert!(game.try_move(-1, 0));
+        let mut out = Vec::new();
+        renderer
+            .draw(&mut out, &game, &keys, Theme::Classic)
+            .unwrap();
+        apply_to_screen(&mut screen, &out);
+        let new = ghost_cols(&game);
+        assert_eq!(new.last(), Some(&(old.last().unwrap() - 1)));
+        for row in 0..BOARD_H {
+            let drawn = ghost_on_screen(&screen, row);
+            let mut expected: Vec<usize> = game
+                .ghost_cells()
+                .iter()
+                .filter(|&&(_, y)| y as usize == row)
+                .map(|&(x, _)| x as usize)
+                .collect();
+            expected.sort();
+            assert_eq!(drawn, expected, "row {row}");
+        }
+    }
+
+    #[test]
+    fn redraws_only_changed_cells() {
+        let mut game = Game::with_seed(1);
+        let keys = KeyBindings::default();
+        let mut renderer = Renderer::new(1, CELL_W, Glyphs::UNICODE);
+
+        let mut first = Vec::new();
+        renderer
+            .draw(&mut first, &game, &keys, Theme::Classic)
+            .unwrap();
+        let mut idle = Vec::new();
+        renderer
+            .draw(&mut idle, &game, &keys, Theme::Classic)
+            .unwrap();
+        game.try_move(1, 0);
+        let mut moved = Vec::new();
+        renderer
+            .draw(&mut moved, &game, &keys, Theme::Classic)
+            .unwrap();
+
+        // 변화가 없으면 보드 칸은 하나도 다시 그리지 않고, 한 칸 옮기면 일부만 그린다
+        assert!(!String::from_utf8_lossy(&idle).contains('\u{2588}'));
+        assert!(renderer.cells_drawn() > 0 && renderer.cells_drawn() < BOARD_W * BOARD_H);
+        assert!(idle.len() * 4 < first.len());
+        assert!(moved.len() > idle.len());
+        assert!(moved.len() * 2 < first.len());
+
+        renderer.invalidate();
+        let mut full = Vec::new();
+        renderer
+            .draw(&mut full, &game, &keys, Theme::Classic)
+            .unwrap();
+        assert!(full.len() * 2 > first.len());
+    }
+
+    #[test]
+    fn level_up_shows_a_banner_unless_a_perfect_clear_is_showing() {
+        // 10번째 줄을 지우면 레벨이 오른다. 보드에 한 칸 남겨 퍼펙트 클리어는 아니다.
+        let mut game = Game::with_seed(8);
+        game.lines = 9;
+        let bottom = BOARD_H - 1;
+        for x in 4..BOARD_W {
+            game.board.grid[bottom][x] = Some(Block::Garbage);
+        }
+        game.board.grid[bottom - 1][BOARD_W - 1] = Some(Block::Garbage);
+        game.take_events();
+        game.current = Piece::new(PieceKind::I);
+        while game.try_move(-1, 0) {}
+        game.hard_drop();
+        assert_eq!(game.level, 2);
+        let banners: Vec<_> = game
+            .take_events()
+            .into_iter()
+            .filter_map(|event| banner_for(event, false))
+            .collect();
+        assert_eq!(banners, ["LEVEL UP"]);
+
+        assert_eq!(banner_for(GameEvent::LevelUp, true), None);
+        assert_eq!(
+            banner_for(GameEvent::PerfectClear, true),
+            Some("PERFECT CLEAR")
+        );
+    }
+}
@@ -0,0 +1,670 @@
+use crate::{
+    BOARD_H, BOARD_W, Game, GameConfig, GravityCurve, REWIND_PENALTY, REWIND_PIECES,
+    RandomizerKind, ScoringMode, SoftDropLock, piece_set,
+};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+
+// 리플레이 파일 첫 줄. 형식이 바뀌면 숫자를 올린다.
+const HEADER: &str = "constris-replay 2";
+// 소프트 드롭 기본값이 락 딜레이로 바뀌기 전의 첫 줄. 이 기록은 바닥에서 바로 고정한다.
+const HEADER_V1: &str = "constris-replay 1";
+
+/// 게임 상태를 바꾸는 입력 하나. 중력과 락 딜레이로 일어난 일도 입력으로 기록해서
+/// 같은 시드에 같은 순서로 넣으면 똑같은 게임이 된다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+    Left,
+    Right,
+    SoftDrop,
+    SonicDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+    /// 인비저블 모드에서 쌓인 블록 엿보기(감점이 있어 기록한다)
+    Peek,
+    /// 중력으로 한 칸 내려감
+    Gravity,
+    /// 락 딜레이가 끝나 고정됨
+    Lock,
+    /// 줄 제거 애니메이션이 끝나 찬 줄이 지워짐
+    Clear,
+    /// 스폰 대기(ARE)가 끝나 다음 피스가 나옴
+    Spawn,
+    /// 구멍 열이 주어진 쓰레기 줄이 올라옴
+    Garbage(u8),
+    /// 연습 모드에서 마지막 고정을 되돌림
+    Undo,
+    /// 연습 모드에서 지금 피스를 다음 종류로 바꿈
+    Cycle,
+}
+
+impl Input {
+    const ALL: [Input; 16] = [
+        Input::Left,
+        Input::Right,
+        Input::SoftDrop,
+        Input::SonicDrop,
+        Input::HardDrop,
+        Input::RotateCw,
+        Input::RotateCcw,
+        Input::Rotate180,
+        Input::Hold,
+        Input::Peek,
+        Input::Gravity,
+        Input::Lock,
+        Input::Clear,
+        Input::Spawn,
+        Input::Undo,
+        Input::Cycle,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Input::Left => "left",
+            Input::Right => "right",
+            Input::SoftDrop => "soft",
+            Input::SonicDrop => "sonic",
+            Input::HardDrop => "hard",
+            Input::RotateCw => "cw",
+            Input::RotateCcw => "ccw",
+            Input::Rotate180 => "180",
+            Input::Hold => "hold",
+            Input::Peek => "peek",
+            Input::Gravity => "gravity",
+            Input::Lock => "lock",
+            Input::Clear => "clear",
+            Input::Spawn => "spawn",
+            Input::Garbage(_) => "garbage",
+            Input::Undo => "undo",
+            Input::Cycle => "cycle",
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Garbage(hole) => write!(f, "garbage {hole}"),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hole) = s.strip_prefix("garbage ") {
+            return hole
+                .parse()
+                .map(Input::Garbage)
+                .map_err(|_| format!("bad garbage hole '{hole}'"));
+        }
+        Input::ALL
+            .into_iter()
+            .find(|input| input.name() == s)
+            .ok_or_else(|| format!("unknown input '{s}'"))
+    }
+}
+
+impl Game {
+    /// 입력 하나를 게임에 적용한다. 플레이와 리플레이가 같은 경로를 탄다.
+    pub fn apply(&mut self, input: Input) {
+        // 애니메이션이나 스폰 대기 중에 다른 입력이 오면 줄부터 지우고 다음 피스를 꺼낸다.
+        // 애니메이션이나 스폰 대기를 끈 채 기록한 리플레이도 켠 채로 똑같이 재생된다.
+        if input != Input::Clear {
+            self.finish_clear();
+            self.finish_entry();
+        }
+        // 이동, 회전, 홀드는 확인을 기다리던 하드 드롭을 취소한다
+        if matches!(
+            input,
+            Input::Left
+                | Input::Right
+                | Input::RotateCw
+                | Input::RotateCcw
+                | Input::Rotate180
+                | Input::Hold
+        ) {
+            self.pending_drop = None;
+        }
+        match input {
+            Input::Left => {
+                self.try_move(-1, 0);
+            }
+            Input::Right => {
+                self.try_move(1, 0);
+            }
+            Input::SoftDrop => self.soft_drop(),
+            Input::SonicDrop => self.sonic_drop(),
+            Input::HardDrop => self.hard_drop(),
+            Input::RotateCw => {
+                self.try_rotate();
+            }
+            Input::RotateCcw => {
+                self.try_rotate_ccw();
+            }
+            Input::Rotate180 => {
+                self.try_rotate_180();
+            }
+            Input::Hold => {
+                self.hold();
+            }
+            Input::Peek => self.peek(Instant::now()),
+            Input::Gravity => self.gravity_step(),
+            Input::Lock => self.lock_and_advance(),
+            Input::Clear => self.finish_clear(),
+            // 위에서 이미 다음 피스를 꺼냈다
+            Input::Spawn => {}
+            Input::Garbage(hole) => self.add_garbage(hole as usize),
+            Input::Undo => {
+                self.undo();
+            }
+            Input::Cycle => {
+                self.cycle_piece();
+            }
+        }
+    }
+}
+
+/// 한 판을 다시 만들 수 있는 기록: 시드, 시작 설정, 시각(ms)별 입력
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: GameConfig,
+    /// 벽 킥을 켜고 기록했는지. 끈 기록만 `kicks off` 줄을 쓴다.
+    pub wall_kicks: bool,
+    /// 직접 정의한 피스 세트로 기록했으면 그 세트의 글자들. 있을 때만 `pieces` 줄을 쓴다.
+    pub pieces: Option<String>,
+    /// 종류별 스폰 회전 상태. 기본값(모두 0)이 아닐 때만 `spawn` 줄을 쓴다.
+    pub spawn_rotations: Vec<u8>,
+    /// 20G가 시작되는 레벨. 켰을 때만 `20g` 줄을 쓴다.
+    pub instant_gravity: Option<u32>,
+    /// 한 판에 쓸 수 있는 홀드 횟수. 정했을 때만 `holds` 줄을 쓴다.
+    pub hold_limit: Option<u32>,
+    /// 막혔을 때 되감는 횟수, 피스 수, 점수 감점. 횟수가 0이 아닐 때만 `rewind` 줄을 쓴다.
+    pub rewinds: u32,
+    pub rewind_pieces: usize,
+    pub rewind_penalty: u32,
+    /// 소프트 드롭이 바닥에서 바로 고정하는지. 기본값(락 딜레이)이 아닐 때만 `softdrop` 줄을 쓴다.
+    pub soft_drop_lock: SoftDropLock,
+    pub inputs: Vec<(u64, Input)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, config: GameConfig) -> Self {
+        Self {
+            seed,
+            config,
+            wall_kicks: true,
+            pieces: (!piece_set().is_standard()).then(|| piece_set().letters()),
+            spawn_rotations: vec![0; piece_set().len()],
+            instant_gravity: None,
+            hold_limit: None,
+            rewinds: 0,
+            rewind_pieces: REWIND_PIECES,
+            rewind_penalty: REWIND_PENALTY,
+            soft_drop_lock: SoftDropLock::default(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// 기록을 시작할 때와 같은 상태의 게임
+    pub fn new_game(&self) -> Game {
+        let mut game = Game::with_seed_and_config(self.seed, self.config);
+        game.wall_kicks = self.wall_kicks;
+        game.spawn_rotations = self.spawn_rotations.clone();
+        game.instant_gravity = self.instant_gravity;
+        game.holds_remaining = self.hold_limit;
+        game.rewinds_remaining = self.rewinds;
+        game.rewind_pieces = self.rewind_pieces;
+        game.rewind_penalty = self.rewind_penalty;
+        game.soft_drop_lock = self.soft_drop_lock;
+        game.respawn_current();
+        game
+    }
+
+    pub fn push(&mut self, ms: u64, input: Input) {
+        self.inputs.push((ms, input));
+    }
+
+    /// 한 줄에 하나씩 쓰는 텍스트 형식
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{HEADER}\nseed {}\nmode {}\nlevel {}\ngravity {}\nboard {}x{}\n",
+            self.seed,
+            self.config.mode,
+            self.config.start_level,
+            self.config.gravity,
+            self.config.width,
+            self.config.height
+        );
+        if self.config.scoring != ScoringMode::default() {
+            text.push_str(&format!("scoring {}\n", self.config.scoring));
+        }
+        if !self.wall_kicks {
+            text.push_str("kicks off\n");
+        }
+        if let Some(letters) = &self.pieces {
+            text.push_str(&format!("pieces {letters}\n"));
+        }
+        if self.spawn_rotations.iter().any(|&state| state != 0) {
+            let states: Vec<String> = self.spawn_rotations.iter().map(u8::to_string).collect();
+            text.push_str(&format!("spawn {}\n", states.join(" ")));
+        }
+        if let Some(level) = self.instant_gravity {
+            text.push_str(&format!("20g {level}\n"));
+        }
+        if let Some(limit) = self.hold_limit {
+            text.push_str(&format!("holds {limit}\n"));
+        }
+        if self.rewinds > 0 {
+            text.push_str(&format!(
+                "rewind {} {} {}\n",
+                self.rewinds, self.rewind_pieces, self.rewind_penalty
+            ));
+        }
+        if self.soft_drop_lock != SoftDropLock::default() {
+            text.push_str(&format!("softdrop {}\n", self.soft_drop_lock));
+        }
+        if self.config.garbage > 0 {
+            text.push_str(&format!("garbage {}\n", self.config.garbage));
+        }
+        if self.config.randomizer != RandomizerKind::default() {
+            text.push_str(&format!("randomizer {}\n", self.config.randomizer));
+        }
+        for (ms, input) in &self.inputs {
+            text.push_str(&format!("{ms} {input}\n"));
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().peekable();
+        let v1 = match lines.next().map(|(_, l)| l.trim()) {
+            Some(HEADER) => false,
+            Some(HEADER_V1) => true,
+            _ => return Err(format!("not a replay file (expected '{HEADER}')")),
+        };
+        let mut header = |key: &str| -> Result<String, String> {
+            let (n, line) = lines.next().ok_or(format!("missing '{key}' line"))?;
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix(' '))
+                .map(str::to_string)
+                .ok_or(format!("line {}: expected '{key} <value>'", n + 1))
+        };
+        let seed = header("seed")?;
+        let seed = seed.parse().map_err(|_| format!("bad seed '{seed}'"))?;
+        let mode = header("mode")?.parse()?;
+        let level = header("level")?;
+        let start_level = level.parse().map_err(|_| format!("bad level '{level}'"))?;
+        // 중력 곡선과 보드 크기를 고르기 전의 기록에는 없는 줄(그때는 기본값뿐이었다)
+        let gravity = match lines.next_if(|(_, line)| line.starts_with("gravity ")) {
+            Some((_, line)) => line["gravity ".len()..].trim().parse()?,
+            None => GravityCurve::default(),
+        };
+        let (width, height) = match lines.next_if(|(_, line)| line.starts_with("board ")) {
+            Some((_, line)) => {
+                let board = line["board ".len()..].trim();
+                board
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .ok_or(format!("bad board size '{board}'"))?
+            }
+            None => (BOARD_W, BOARD_H),
+        };
+
+        // 가이드라인이 아닌 점수 규칙으로 기록했을 때만 있는 줄
+        let scoring = match lines.next_if(|(_, line)| line.starts_with("scoring ")) {
+            Some((_, line)) => line.trim_start_matches("scoring ").trim().parse()?,
+            None => ScoringMode::default(),
+        };
+        let config = GameConfig {
+            mode,
+            start_level,
+            gravity,
+            scoring,
+            width,
+            height,
+            garbage: 0,
+            randomizer: RandomizerKind::default(),
+        };
+        let mut replay = Replay::new(seed, config);
+        // 킥을 끈 기록에만 있는 줄
+        if lines
+            .next_if(|(_, line)| line.trim() == "kicks off")
+            .is_some()
+        {
+            replay.wall_kicks = false;
+        }
+        // 직접 정의한 피스 세트로 기록했을 때만 있는 줄. 지금 쓰는 세트와 같아야 다시 볼 수 있다.
+        let pieces = lines
+            .next_if(|(_, line)| line.starts_with("pieces "))
+            .map(|(_, line)| line["pieces ".len()..].trim().to_string());
+        if pieces != replay.pieces {
+            let name = |letters: &Option<String>| {
+                letters.clone().unwrap_or_else(|| "standard".to_string())
+            };
+            return Err(format!(
+                "recorded with piece set '{}' but '{}' is loaded",
+                name(&pieces),
+                name(&replay.pieces)
+            ));
+        }
+        // 스폰 회전을 바꾼 기록에만 있는 줄(세트의 종류 순서, 기본 세트는 I O T S Z J L)
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("spawn ")) {
+            let states: Vec<u8> = line["spawn ".len()..]
+                .split_whitespace()
+                .map(|state| state.parse().ok().filter(|&s| s < 4))
+                .collect::<Option<_>>()
+                .ok_or(format!("line {}: bad spawn rotations", n + 1))?;
+            if states.len() != piece_set().len() {
+                return Err(format!(
+                    "line {}: expected {} spawn rotations",
+                    n + 1,
+                    piece_set().len()
+                ));
+            }
+            replay.spawn_rotations = states;
+        }
+        // 20G를 켠 기록에만 있는 줄
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("20g ")) {
+            let level = line["20g ".len()..].trim();
+            replay.instant_gravity = Some(
+                level
+                    .parse()
+                    .map_err(|_| format!("line {}: bad 20g level '{level}'", n + 1))?,
+            );
+        }
+        // 홀드 횟수를 제한한 기록에만 있는 줄
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("holds ")) {
+            let limit = line["holds ".len()..].trim();
+            replay.hold_limit = Some(
+                limit
+                    .parse()
+                    .map_err(|_| format!("line {}: bad hold limit '{limit}'", n + 1))?,
+            );
+        }
+        // 막혔을 때 되감기를 켠 기록에만 있는 줄: 횟수, 피스 수, 감점
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("rewind ")) {
+            let bad = || {
+                format!(
+                    "line {}: expected 'rewind <times> <pieces> <penalty>'",
+                    n + 1
+                )
+            };
+            let [times, pieces, penalty]: [&str; 3] = line["rewind ".len()..]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| bad())?;
+            replay.rewinds = times.parse().map_err(|_| bad())?;
+            replay.rewind_pieces = pieces.parse().map_err(|_| bad())?;
+            replay.rewind_penalty = penalty.parse().map_err(|_| bad())?;
+        }
+        // 소프트 드롭이 바닥에서 바로 고정하게 한 기록에만 있는 줄
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("softdrop ")) {
+            replay.soft_drop_lock = line["softdrop ".len()..]
+                .trim()
+                .parse()
+                .map_err(|e| format!("line {}: {e}", n + 1))?;
+        } else if v1 {
+            replay.soft_drop_lock = SoftDropLock::InstantLock;
+        }
+        // 쓰레기 줄을 깔고 시작한 기록에만 있는 줄(입력의 `<ms> garbage <hole>`과는 다르다)
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("garbage ")) {
+            let rows = line["garbage ".len()..].trim();
+            replay.config.garbage = rows
+                .parse()
+                .map_err(|_| format!("line {}: bad garbage rows '{rows}'", n + 1))?;
+        }
+        // 7-bag이 아닌 랜덤 방법으로 기록했을 때만 있는 줄
+        if let Some((n, line)) = lines.next_if(|(_, line)| line.starts_with("randomizer ")) {
+            replay.config.randomizer = line["randomizer ".len()..]
+                .trim()
+                .parse()
+                .map_err(|e| format!("line {}: {e}", n + 1))?;
+        }
+        for (n, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (ms, input) = line
+                .split_once(' ')
+                .ok_or(format!("line {}: expected '<ms> <input>'", n + 1))?;
+            let ms = ms
+                .parse()
+                .map_err(|_| format!("line {}: bad time '{ms}'", n + 1))?;
+            let input = input.parse().map_err(|e| format!("line {}: {e}", n + 1))?;
+            replay.push(ms, input);
+        }
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Block, Board, GameMode, Piece, PieceKind};
+
+    fn sample() -> Replay {
+        let mut replay = Replay::new(
+            42,
+            GameConfig {
+                mode: GameMode::Sprint,
+                start_level: 3,
+                gravity: GravityCurve::Nes,
+                scoring: ScoringMode::Nes,
+                width: 8,
+                height: 16,
+                garbage: 0,
+                randomizer: RandomizerKind::default(),
+            },
+        );
+        let inputs = [
+            Input::Left,
+            Input::RotateCw,
+            Input::HardDrop,
+            Input::Gravity,
+            Input::Right,
+            Input::Right,
+            Input::SonicDrop,
+            Input::Lock,
+            Input::Clear,
+            Input::Spawn,
+            Input::Garbage(4),
+            Input::Rotate180,
+            Input::HardDrop,
+        ];
+        for (i, input) in inputs.into_iter().enumerate() {
+            replay.push(i as u64 * 100, input);
+        }
+        replay
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut replay = sample();
+        assert_eq!(Replay::parse(&replay.to_text()), Ok(replay.clone()));
+        assert!(Replay::parse("seed 1\n").is_err());
+
+        replay.wall_kicks = false;
+        assert!(replay.to_text().contains("\nkicks off\n"));
+        let parsed = Replay::parse(&replay.to_text()).unwrap();
+        assert!(!parsed.new_game().wall_kicks);
+        assert_eq!(parsed, replay);
+
+        replay.spawn_rotations[PieceKind::T.index()] = 2;
+        assert!(replay.to_text().contains("\nspawn 0 0 2 0 0 0 0\n"));
+        assert_eq!(Replay::parse(&replay.to_text()), Ok(replay.clone()));
+        let broken = replay.to_text().replace("spawn 0 0 2", "spawn 0 0 4");
+        assert!(Replay::parse(&broken).is_err());
+
+        replay.instant_gravity = Some(15);
+        assert!(replay.to_text().contains("\n20g 15\n"));
+        assert_eq!(Replay::parse(&replay.to_text()), Ok(replay.clone()));
+
+        replay.hold_limit = Some(5);
+        assert!(replay.to_text().contains("\n20g 15\nholds 5\n"));
+        let parsed = Replay::parse(&replay.to_text()).unwrap();
+        assert_eq!(parsed.new_game().holds_remaining, Some(5));
+        assert_eq!(parsed, replay);
+
+        (replay.rewinds, replay.rewind_pieces, replay.rewind_penalty) = (2, 3, 400);
+        assert!(replay.to_text().contains("\nholds 5\nrewind 2 3 400\n"));
+        let parsed = Replay::parse(&replay.to_text()).unwrap();
+        assert_eq!(parsed.new_game().rewinds_remaining, 2);
+        assert_eq!(parsed, replay);
+        let broken = replay.to_text().replace("rewind 2 3 400", "rewind 2 3");
+        assert!(Replay::parse(&broken).is_err());
+
+        replay.soft_drop_lock = SoftDropLock::InstantLock;
+        assert!(
+            replay
+                .to_text()
+                .contains("\nrewind 2 3 400\nsoftdrop instant\n")
+        );
+        let parsed = Replay::parse(&replay.to_text()).unwrap();
+        assert_eq!(parsed.new_game().soft_drop_lock, SoftDropLock::InstantLock);
+        assert_eq!(parsed, replay);
+
+        replay.config.garbage = 4;
+        assert!(replay.to_text().contains("\nsoftdrop instant\ngarbage 4\n"));
+        let parsed = Replay::parse(&replay.to_text()).unwrap();
+        assert_eq!(parsed.new_game().board, replay.new_game().board);
+        assert_eq!(parsed, replay);
+
+        replay.config.randomizer = RandomizerKind::Tgm;
+        assert!(replay.to_text().contains("\ngarbage 4\nrandomizer tgm\n"));
+        let parsed = Replay::parse(&replay.to_text()).unwrap();
+        assert_eq!(parsed.new_game().randomizer(), RandomizerKind::Tgm);
+        assert_eq!(parsed, replay);
+    }
+
+    #[test]
+    fn old_replays_without_gravity_or_board_use_the_defaults() {
+        let parsed =
+            Replay::parse("constris-replay 1\nseed 7\nmode sprint\nlevel 2\n0 left\n").unwrap();
+        assert_eq!(parsed.config.gravity, GravityCurve::default());
+        assert_eq!(
+            (parsed.config.width, parsed.config.height),
+            (BOARD_W, BOARD_H)
+        );
+        assert_eq!(parsed.inputs, vec![(0, Input::Left)]);
+
+        let nes = Replay::parse("constris-replay 1\nseed 7\nmode sprint\nlevel 2\ngravity nes\n")
+            .unwrap();
+        assert_eq!(nes.config.gravity, GravityCurve::Nes);
+        assert_eq!(nes.config.width, BOARD_W);
+    }
+
+    #[test]
+    fn version_one_replays_lock_soft_drops_at_once() {
+        let old = "constris-replay 1\nseed 7\nmode sprint\nlevel 2\n";
+        let parsed = Replay::parse(old).unwrap();
+        assert_eq!(parsed.soft_drop_lock, SoftDropLock::InstantLock);
+        let delayed = Replay::parse(&old.replace("level 2", "level 2\nsoftdrop delay")).unwrap();
+        assert_eq!(delayed.soft_drop_lock, SoftDropLock::LockDelay);
+
+        // 새 기록은 줄이 없으면 지금 기본값인 락 딜레이다
+        let current = Replay::parse(&old.replace(HEADER_V1, HEADER)).unwrap();
+        assert_eq!(current.soft_drop_lock, SoftDropLock::LockDelay);
+        assert!(sample().to_text().starts_with("constris-replay 2\n"));
+    }
+
+    #[test]
+    fn same_inputs_rebuild_the_same_game() {
+        let replay = sample();
+        let play = |replay: &Replay| {
+            let mut game = replay.new_game();
+            for &(_, input) in &replay.inputs {
+                game.apply(input);
+            }
+            game
+        };
+        let (a, b) = (play(&replay), play(&replay));
+        assert_eq!(a.board.grid, b.board.grid);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.current.kind, b.current.kind);
+        assert_eq!(a.pieces_placed(), 3);
+    }
+
+    /// `expected`에 적힌 점수, 줄 수, 보드(`score N`, `lines N`, `board` 다음 줄부터)와
+    /// 기록을 끝까지 재생한 게임을 비교한다. 보드가 다르면 두 보드를 나란히 보여 준다.
+    fn verify(replay: &str, expected: &str) -> Game {
+        let replay = Replay::parse(replay).unwrap();
+        let mut game = replay.new_game();
+        for &(_, input) in &replay.inputs {
+            game.apply(input);
+        }
+        let (header, board) = expected.split_once("board\n").expect("no 'board' line");
+        let field = |key: &str| -> u32 {
+            header
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.trim().parse().ok())
+                .unwrap_or_else(|| panic!("no '{key}' line"))
+        };
+        let board: Board = board.parse().unwrap();
+        if game.board != board {
+            let mut diff = String::from("expected     actual\n");
+            let actual = game.board.to_string();
+            for (want, got) in board.to_string().lines().zip(actual.lines()) {
+                let mark = if want == got { ' ' } else { '<' };
+                diff.push_str(&format!("{want}   {got} {mark}\n"));
+            }
+            panic!("board differs after the replay\n{diff}");
+        }
+        assert_eq!(
+            (game.score, game.lines),
+            (field("score"), field("lines")),
+            "score and lines"
+        );
+        game
+    }
+
+    #[test]
+    fn sample_replay_matches_its_expectation() {
+        let game = verify(
+            include_str!("../testdata/tsd_tetris.replay"),
+            include_str!("../testdata/tsd_tetris.expected"),
+        );
+        // 이 기록은 T-스핀 더블과 테트리스를 하나씩 한다
+        assert_eq!(game.clear_counts[1], 1);
+        assert_eq!(game.clear_counts[3], 1);
+        assert_eq!(game.t_spins, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "board differs")]
+    fn verify_reports_a_different_board() {
+        let expected = include_str!("../testdata/tsd_tetris.expected").replacen("ZZ.", "ZZZ", 1);
+        verify(include_str!("../testdata/tsd_tetris.replay"), &expected);
+    }
+
+    #[test]
+    fn inputs_during_a_clear_animation_finish_it_first() {
+        let play = |delay_ms| {
+            let mut game = Game::with_seed(6);
+            game.clear_delay = std::time::Duration::from_millis(delay_ms);
+            for x in 4..BOARD_W {
+                game.board.grid[BOARD_H - 1][x] = Some(Block::Garbage);
+            }
+            game.current = Piece::new(PieceKind::I);
+            while game.try_move(-1, 0) {}
+            game.apply(Input::HardDrop);
+            game.apply(Input::Left);
+            game
+        };
+        // 애니메이션을 켜도 끈 것과 같은 판이 된다
+        let (animated, instant) = (play(200), play(0));
+        assert!(!animated.is_clearing());
+        assert_eq!(animated.board.grid, instant.board.grid);
+        assert_eq!(animated.current.x, instant.current.x);
+        assert_eq!(animated.lines, 1);
+    }
+}
@@ -0,0 +1,405 @@
+use crate::randomizer::PieceSource;
+use crate::{
+    Board, Game, GameConfig, MAX_PREVIEW, MAX_START_LEVEL, Piece, PieceKind, ScoringMode, piece_set,
+};
+use std::time::{Duration, Instant};
+
+// 저장 파일 첫 줄. 형식이 바뀌면 숫자를 올리고, 다른 숫자의 파일은 읽지 않는다.
+const HEADER: &str = "constris-save 8";
+// 되살릴 수 있는 쓰레기 줄 구멍 수. 5초마다 한 줄이면 1년도 넘게 걸린다.
+const MAX_GARBAGE_DRAWS: u32 = 10_000_000;
+
+impl Game {
+    /// 이어 하기에 필요한 상태를 한 줄에 하나씩 쓰는 텍스트 형식.
+    ///
+    /// 락 딜레이, 줄 제거 애니메이션, 인비저블 모드에서 잠깐 보이는 칸처럼 금방 지나가는
+    /// 상태는 저장하지 않는다.
+    pub fn save(&self) -> String {
+        let letters = |kinds: &mut dyn Iterator<Item = PieceKind>| {
+            let text: String = kinds.map(PieceKind::letter).collect();
+            if text.is_empty() {
+                "-".to_string()
+            } else {
+                text
+            }
+        };
+        let numbers = |values: &[u32]| {
+            values
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let held = self.held.map_or('-', PieceKind::letter);
+        let current = &self.current;
+        format!(
+            "{HEADER}\nseed {}\nset {}\nrandomizer {} {}\ngarbage {}\nmode {}\ngravity {}\nscoring {}\nstart {}\nlevel {}\n\
+             score {}\nlines {}\ncombo {}\nb2b {}\npieces {}\nclears {}\ntspins {}\n\
+             maxcombo {}\ndrops {} {}\ntime {}\nhold {held} {}\nholds {}\nrewinds {}\ncurrent {} {} {} {}\nqueue {}\n\
+             board {}x{}\n{}",
+            self.seed,
+            set_name(),
+            self.source.kind,
+            self.source.pulls,
+            self.garbage_draws(),
+            self.mode,
+            self.gravity,
+            self.scoring,
+            self.start_level,
+            self.level,
+            self.score,
+            self.lines,
+            self.combo,
+            self.back_to_back,
+            numbers(&self.piece_counts),
+            numbers(&self.clear_counts),
+            self.t_spins,
+            self.max_combo,
+            self.soft_drop_cells,
+            self.hard_drop_cells,
+            self.elapsed().as_millis(),
+            self.hold_used,
+            self.holds_remaining
+                .map_or("-".to_string(), |left| left.to_string()),
+            self.rewinds_remaining,
+            current.kind.letter(),
+            current.rotation,
+            current.x,
+            current.y,
+            letters(&mut self.queue.iter().copied()),
+            self.board.width(),
+            self.board.height(),
+            self.board,
+        )
+    }
+
+    /// `save`로 쓴 텍스트에서 게임을 되살린다. 형식이 맞지 않으면 아무것도 바꾸지 않고 실패한다.
+    ///
+    /// 플레이 시간이 흐른 게임은 일시정지 상태로 돌려주므로 재개할 때부터 시계가 다시 간다.
+    pub fn load(text: &str) -> Result<Game, String> {
+        let mut lines = text.lines().enumerate();
+        match lines.next().map(|(_, l)| l.trim()) {
+            Some(HEADER) => {}
+            Some(other) if other.starts_with("constris-save ") => {
+                return Err(format!("unsupported save version (expected '{HEADER}')"));
+            }
+            _ => return Err(format!("not a save file (expected '{HEADER}')")),
+        }
+        let mut field = |key: &str| -> Result<String, String> {
+            let (n, line) = lines.next().ok_or(format!("missing '{key}' line"))?;
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix(' '))
+                .map(str::to_string)
+                .ok_or(format!("line {}: expected '{key} <value>'", n + 1))
+        };
+
+        let seed = number(&field("seed")?, "seed")?;
+        // 피스 글자와 개수는 세트에 따라 뜻이 달라지니 같은 세트일 때만 읽는다
+        let set = field("set")?;
+        if set != set_name() {
+            return Err(format!(
+                "saved with piece set '{set}' but '{}' is loaded",
+                set_name()
+            ));
+        }
+        // 랜덤 방법과 꺼낸 피스 수. 같은 시드로 그만큼 다시 꺼내면 이어지는 순서가 같다.
+        let randomizer = field("randomizer")?;
+        let (randomizer, pulls) = randomizer
+            .split_once(' ')
+            .ok_or(format!("bad randomizer '{randomizer}'"))?;
+        let (randomizer, pulls) = (randomizer.parse()?, number(pulls, "randomizer")?);
+        let garbage_draws = number(&field("garbage")?, "garbage")?;
+        if garbage_draws > MAX_GARBAGE_DRAWS {
+            return Err(format!("bad garbage '{garbage_draws}'"));
+        }
+        let mode = field("mode")?.parse()?;
+        let gravity = field("gravity")?.parse()?;
+        let scoring: ScoringMode = field("scoring")?.parse()?;
+        let start_level = number(&field("start")?, "start level")?;
+        let level = number(&field("level")?, "level")?;
+        let score = number(&field("score")?, "score")?;
+        let lines_cleared = number(&field("lines")?, "lines")?;
+        // 레벨은 시작 레벨과 지운 줄 수로 정해진다
+        if !(1..=MAX_START_LEVEL).contains(&start_level) {
+            return Err(format!("bad start level '{start_level}'"));
+        }
+        if level != scoring.level(start_level, lines_cleared) {
+            return Err(format!(
+                "level {level} does not match {lines_cleared} lines"
+            ));
+        }
+        let combo = number(&field("combo")?, "combo")?;
+        let back_to_back = number(&field("b2b")?, "b2b")?;
+        let piece_counts = numbers(&field("pieces")?, "pieces", piece_set().len())?;
+        let clear_counts = numbers(&field("clears")?, "clears", 4)?
+            .try_into()
+            .expect("four numbers were read");
+        let t_spins = number(&field("tspins")?, "tspins")?;
+        let max_combo = number(&field("maxcombo")?, "maxcombo")?;
+        // 소프트 드롭과 하드 드롭으로 내린 칸 수
+        let drops = field("drops")?;
+        let (soft_drops, hard_drops) = drops
+            .split_once(' ')
+            .ok_or(format!("bad drops '{drops}'"))?;
+        let (soft_drops, hard_drops) = (number(soft_drops, "drops")?, number(hard_drops, "drops")?);
+        // 플레이 시간만큼 앞선 시작 시각. 그만큼 거슬러 갈 수 없는 시간이면 읽지 않는다.
+        let time = number(&field("time")?, "time")?;
+        let start_time = match time {
+            0 => None,
+            ms => Some(
+                Instant::now()
+                    .checked_sub(Duration::from_millis(ms))
+                    .ok_or(format!("bad time '{time}'"))?,
+            ),
+        };
+        let hold = field("hold")?;
+        let (held, hold_used) = hold.split_once(' ').ok_or(format!("bad hold '{hold}'"))?;
+        let held = match held {
+            "-" => None,
+            _ => Some(piece(held)?),
+        };
+        let hold_used = number(hold_used, "hold")?;
+        // 남은 홀드 횟수. 무제한이면 `-`.
+        let holds_remaining = match field("holds")?.as_str() {
+            "-" => None,
+            left => Some(number(left, "holds")?),
+        };
+        // 남은 되감기 횟수(되감기에 쓰는 기록은 저장하지 않는다)
+        let rewinds_remaining = number(&field("rewinds")?, "rewinds")?;
+        let current = field("current")?;
+        let [kind, rotation, x, y]: [&str; 4] =
+            current
+                .split(' ')
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| format!("bad current piece '{current}'"))?;
+        let (kind, rotation) = (piece(kind)?, number::<u8>(rotation, "rotation")?);
+        let (x, y) = (number(x, "piece x")?, number(y, "piece y")?);
+        let queue = pieces(&field("queue")?)?;
+        if queue.len() != MAX_PREVIEW || rotation > 3 {
+            return Err("bad queue or rotation".to_string());
+        }
+        let size = field("board")?;
+        let rows: Vec<&str> = lines.map(|(_, line)| line).collect();
+        let board: Board = rows.join("\n").parse()?;
+        if format!("{}x{}", board.width(), board.height()) != size {
+            return Err(format!("board does not match its size '{size}'"));
+        }
+
+        let mut game = Game::with_seed_and_config(
+            seed,
+            GameConfig {
+                mode,
+                start_level,
+                gravity,
+                scoring,
+                width: board.width(),
+                height: board.height(),
+                // 깔아 둔 쓰레기 줄은 아래에서 읽은 보드에 이미 들어 있다
+                garbage: 0,
+                randomizer,
+            },
+        );
+        // 모드가 허용하지 않는 크기(빅 모드의 홀수 크기 등)는 게임이 다른 크기로 맞춘다
+        if (game.board.width(), game.board.height()) != (board.width(), board.height()) {
+            return Err(format!("board size '{size}' is not allowed in {mode} mode"));
+        }
+        let mut current = Piece::spawn(kind, board.width()).scaled(mode.cell_scale());
+        for _ in 0..rotation {
+            current.cells = current.rotated_cw();
+        }
+        current.rotation = rotation;
+        (current.x, current.y) = (x, y);
+        if !board.fits(&current.absolute_cells()) {
+            return Err("current piece overlaps the board".to_string());
+        }
+        game.board = board;
+        game.current = current;
+        game.queue = queue.into();
+        // 놓은 피스마다 하나, 처음의 현재 피스와 다음 피스들, 홀드를 비울 때 하나씩 꺼낸다
+        let placed: u64 = piece_counts.iter().map(|&count| count as u64).sum();
+        let most = (placed + MAX_PREVIEW as u64 + 2).min(u32::MAX as u64) as u32;
+        game.source = PieceSource::restore(seed, randomizer, pulls, most)?;
+        game.sync_garbage(garbage_draws);
+        game.held = held;
+        game.hold_used = hold_used;
+        game.holds_remaining = holds_remaining;
+        game.rewinds_remaining = rewinds_remaining;
+        game.level = level;
+        game.score = score;
+        game.lines = lines_cleared;
+        game.combo = combo;
+        game.back_to_back = back_to_back;
+        game.piece_counts = piece_counts;
+        game.clear_counts = clear_counts;
+        game.t_spins = t_spins;
+        game.max_combo = max_combo;
+        game.soft_drop_cells = soft_drops;
+        game.hard_drop_cells = hard_drops;
+        if start_time.is_some() {
+            game.start_time = start_time;
+            game.set_paused(true);
+        }
+        Ok(game)
+    }
+}
+
+/// 저장 파일의 `set` 줄: 기본 세트는 `standard`, 직접 정의한 세트는 그 글자들
+fn set_name() -> String {
+    if piece_set().is_standard() {
+        "standard".to_string()
+    } else {
+        piece_set().letters()
+    }
+}
+
+fn number<T: std::str::FromStr>(text: &str, what: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("bad {what} '{text}'"))
+}
+
+/// 공백으로 나눈 숫자 `len`개(피스별 개수는 세트의 종류 수만큼)
+fn numbers(text: &str, what: &str, len: usize) -> Result<Vec<u32>, String> {
+    let values = text
+        .split(' ')
+        .map(|n| number(n, what))
+        .collect::<Result<Vec<_>, _>>()?;
+    if values.len() != len {
+        return Err(format!("expected {len} numbers for {what}"));
+    }
+    Ok(values)
+}
+
+fn piece(text: &str) -> Result<PieceKind, String> {
+    let mut chars = text.chars();
+    match (chars.next().and_then(PieceKind::from_letter), chars.next()) {
+        (Some(kind), None) => Ok(kind),
+        _ => Err(format!("bad piece '{text}'")),
+    }
+}
+
+/// 피스 글자를 이어 쓴 목록. 비어 있으면 `-`.
+fn pieces(text: &str) -> Result<Vec<PieceKind>, String> {
+    if text == "-" {
+        return Ok(Vec::new());
+    }
+    text.chars()
+        .map(|c| PieceKind::from_letter(c).ok_or(format!("bad piece '{c}'")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Block, GameMode, Input, RandomizerKind};
+
+    fn played() -> Game {
+        let mut game = Game::with_seed_and_config(
+            9,
+            GameConfig {
+                mode: GameMode::Marathon,
+                width: 8,
+                ..GameConfig::default()
+            },
+        );
+        for input in [Input::Hold, Input::HardDrop, Input::Left, Input::HardDrop] {
+            game.apply(input);
+        }
+        game.score = 1234;
+        game.lines = 7;
+        let bottom = game.board.height() - 1;
+        game.board.grid[bottom][0] = Some(Block::Garbage);
+        game
+    }
+
+    #[test]
+    fn saved_game_loads_back_the_same() {
+        let mut game = Game::with_seed(4);
+        game.apply(Input::Hold);
+        game.apply(Input::RotateCw);
+        game.apply(Input::HardDrop);
+        game.apply(Input::RotateCcw);
+        let mut loaded = Game::load(&game.save()).unwrap();
+        assert_eq!(loaded.save(), game.save());
+        assert_eq!(
+            loaded.current.absolute_cells(),
+            game.current.absolute_cells()
+        );
+
+        // 이어서 나오는 피스도 저장하지 않고 계속한 게임과 같다
+        for _ in 0..15 {
+            game.apply(Input::HardDrop);
+            loaded.apply(Input::HardDrop);
+        }
+        assert_eq!(loaded.board, game.board);
+        assert_eq!(loaded.queue, game.queue);
+    }
+
+    #[test]
+    fn randomizer_with_history_continues_the_same_pieces() {
+        let mut game = Game::with_seed_and_config(
+            6,
+            GameConfig {
+                randomizer: RandomizerKind::Tgm,
+                ..GameConfig::default()
+            },
+        );
+        for _ in 0..9 {
+            game.apply(Input::HardDrop);
+        }
+        let mut loaded = Game::load(&game.save()).unwrap();
+        assert_eq!(loaded.randomizer(), RandomizerKind::Tgm);
+        for _ in 0..20 {
+            game.apply(Input::HardDrop);
+            loaded.apply(Input::HardDrop);
+        }
+        assert_eq!(loaded.queue, game.queue);
+    }
+
+    #[test]
+    fn keeps_score_mode_and_board_size() {
+        let mut game = played();
+        game.next_garbage_hole();
+        let mut loaded = Game::load(&game.save()).unwrap();
+        // 쓰레기 줄 구멍도 저장하지 않고 계속한 게임과 같은 순서로 이어진다
+        assert_eq!(loaded.next_garbage_hole(), game.next_garbage_hole());
+        assert_eq!(loaded.mode, GameMode::Marathon);
+        assert_eq!(loaded.board.width(), 8);
+        assert_eq!((loaded.score, loaded.lines), (game.score, game.lines));
+        assert_eq!(loaded.piece_counts, game.piece_counts);
+        assert_eq!(loaded.held, game.held);
+        assert_eq!(loaded.hard_drop_cells, game.hard_drop_cells);
+        assert!(loaded.hard_drop_cells > 0);
+
+        // 남은 홀드 횟수와 되감기 횟수도 이어진다
+        game.holds_remaining = Some(2);
+        game.rewinds_remaining = 1;
+        let loaded = Game::load(&game.save()).unwrap();
+        assert_eq!(loaded.holds_remaining, Some(2));
+        assert_eq!(loaded.rewinds_remaining, 1);
+    }
+
+    #[test]
+    fn refuses_other_versions_and_broken_files() {
+        let text = Game::with_seed(1).save();
+        let newer = text.replacen(HEADER, "constris-save 9", 1);
+        assert!(Game::load(&newer).err().unwrap().contains("version"));
+        assert!(Game::load("constris-replay 1\n").is_err());
+        // 잘린 파일, 보드 크기가 맞지 않는 파일
+        let cut = &text[..text.len() / 2];
+        assert!(Game::load(cut).is_err());
+        assert!(Game::load(&text.replace("board 10x20", "board 10x21")).is_err());
+        // 범위를 벗어난 값은 오래 걸리는 되돌리기를 하기 전에 거절한다
+        for (from, to) in [
+            ("\nstart 1\n", "\nstart 99\n"),
+            ("\nlevel 1\n", "\nlevel 7\n"),
+            ("\ngarbage 0\n", "\ngarbage 4000000000\n"),
+            ("\nrandomizer bag 6\n", "\nrandomizer bag 4000000000\n"),
+        ] {
+            assert!(text.contains(from), "{from:?}");
+            assert!(Game::load(&text.replace(from, to)).is_err(), "{to:?}");
+        }
+        // 다른 피스 세트로 저장한 파일
+        let other = text.replace("\nset standard\n", "\nset PX\n");
+        assert!(Game::load(&other).err().unwrap().contains("piece set 'PX'"));
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+use std::str::FromStr;
+
+// NES 줄 제거 점수(레벨을 곱하기 전): 1줄, 2줄, 3줄, 4줄
+const NES_LINE_POINTS: [u32; 4] = [40, 100, 300, 1200];
+
+/// 줄 제거 점수와 레벨 상승을 정하는 규칙
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoringMode {
+    /// T-스핀, 백투백, 콤보, 퍼펙트 클리어 보너스가 있는 가이드라인식 점수. 10줄마다 레벨 업.
+    #[default]
+    Guideline,
+    /// 원조 NES 테트리스: 줄 수만 보고 40/100/300/1200 x 레벨, 보너스 없음.
+    /// 첫 레벨 업은 시작 레벨이 높을수록 늦게 온다.
+    Nes,
+}
+
+impl ScoringMode {
+    /// 메뉴에서 고를 수 있는 순서
+    pub const ALL: [ScoringMode; 2] = [ScoringMode::Guideline, ScoringMode::Nes];
+
+    /// NES 규칙의 줄 제거 점수. 레벨(1부터)은 NES의 `레벨+1`과 같다.
+    pub(crate) fn nes_line_points(cleared: u32, level: u32) -> u32 {
+        match cleared {
+            1..=4 => NES_LINE_POINTS[cleared as usize - 1] * level,
+            _ => 0,
+        }
+    }
+
+    /// `start_level`(1부터)에서 시작해 `lines`줄을 지웠을 때의 레벨
+    pub fn level(self, start_level: u32, lines: u32) -> u32 {
+        match self {
+            ScoringMode::Guideline => (lines / 10 + 1).max(start_level),
+            ScoringMode::Nes => {
+                let first = nes_first_transition(start_level);
+                if lines < first {
+                    start_level
+                } else {
+                    start_level + 1 + (lines - first) / 10
+                }
+            }
+        }
+    }
+}
+
+/// NES에서 첫 레벨 업까지 지워야 하는 줄 수.
+/// NES 레벨 `n`(우리 레벨 `n+1`)에서 `min(10n+10, max(100, 10n-50))`.
+fn nes_first_transition(start_level: u32) -> u32 {
+    let n = start_level.max(1) - 1;
+    (n * 10 + 10).min(100.max((n * 10).saturating_sub(50)))
+}
+
+impl fmt::Display for ScoringMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ScoringMode::Guideline => "guideline",
+            ScoringMode::Nes => "nes",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for ScoringMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "guideline" => Ok(ScoringMode::Guideline),
+            "nes" | "classic" => Ok(ScoringMode::Nes),
+            _ => Err(format!("unknown scoring '{s}' (expected guideline or nes)")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nes_first_transition_depends_on_the_start_level() {
+        // NES 레벨 0~9는 10(n+1)줄, 10~15는 100줄, 그 뒤는 10n-50줄
+        assert_eq!(nes_first_transition(1), 10);
+        assert_eq!(nes_first_transition(10), 100);
+        assert_eq!(nes_first_transition(11), 100);
+        assert_eq!(nes_first_transition(16), 100);
+        assert_eq!(nes_first_transition(17), 110);
+        assert_eq!(nes_first_transition(20), 140);
+    }
+
+    #[test]
+    fn nes_levels_up_every_ten_lines_after_the_first_transition() {
+        let nes = ScoringMode::Nes;
+        assert_eq!(nes.level(1, 9), 1);
+        assert_eq!(nes.level(1, 10), 2);
+        assert_eq!(nes.level(6, 59), 6);
+        assert_eq!(nes.level(6, 60), 7);
+        assert_eq!(nes.level(6, 69), 7);
+        assert_eq!(nes.level(6, 70), 8);
+        // 높은 레벨에서 시작하면 100줄까지 그대로다
+        assert_eq!(nes.level(15, 99), 15);
+        assert_eq!(nes.level(15, 100), 16);
+        // 가이드라인은 지운 줄이 시작 레벨을 따라잡아야 오른다
+        assert_eq!(ScoringMode::Guideline.level(6, 49), 6);
+        assert_eq!(ScoringMode::Guideline.level(6, 60), 7);
+    }
+
+    #[test]
+    fn nes_line_points_scale_with_the_level() {
+        assert_eq!(ScoringMode::nes_line_points(1, 1), 40);
+        assert_eq!(ScoringMode::nes_line_points(4, 1), 1200);
+        assert_eq!(ScoringMode::nes_line_points(2, 10), 1000);
+        assert_eq!(ScoringMode::nes_line_points(0, 10), 0);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for scoring in ScoringMode::ALL {
+            assert_eq!(scoring.to_string().parse::<ScoringMode>(), Ok(scoring));
+        }
+        assert!("tgm".parse::<ScoringMode>().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use constris::GameEvent;
+
+/// 효과음 한 음: 주파수(Hz)와 길이(ms)
+type Note = (f32, u64);
+
+// 소리 크기(0.0~1.0). 사인파는 작게 줄여도 잘 들린다.
+#[cfg(feature = "audio")]
+const VOLUME: f32 = 0.15;
+
+/// 게임 이벤트마다 짧은 효과음을 낸다.
+///
+/// `audio` 기능 없이 빌드했거나 오디오 장치를 열 수 없으면 아무 소리도 내지 않는다.
+pub struct Sound {
+    pub muted: bool,
+    output: Output,
+}
+
+impl Sound {
+    pub fn new() -> Self {
+        Self {
+            muted: false,
+            output: Output::open(),
+        }
+    }
+
+    /// 소리를 낼 수 있는 상태인지(기능과 장치가 모두 있어야 한다)
+    pub fn available(&self) -> bool {
+        self.output.available()
+    }
+
+    pub fn play(&self, event: GameEvent) {
+        if !self.muted {
+            self.output.play(notes(event));
+        }
+    }
+}
+
+/// 이벤트별 음들. 위로 올라가면 좋은 일, 내려가면 나쁜 일이다.
+fn notes(event: GameEvent) -> &'static [Note] {
+    match event {
+        GameEvent::Locked => &[(220.0, 30)],
+        GameEvent::Cleared(4) => &[(523.0, 70), (659.0, 70), (784.0, 70), (1047.0, 160)],
+        GameEvent::Cleared(_) => &[(523.0, 60), (659.0, 90)],
+        GameEvent::PerfectClear => &[(784.0, 60), (988.0, 60), (1175.0, 60), (1568.0, 200)],
+        GameEvent::LevelUp => &[(784.0, 80), (1047.0, 120)],
+        GameEvent::GameOver => &[(392.0, 150), (330.0, 150), (262.0, 300)],
+        GameEvent::Attack(_) => &[(659.0, 40), (880.0, 60)],
+        GameEvent::Rewound => &[(523.0, 80), (392.0, 80), (523.0, 160)],
+        GameEvent::Spawned => &[],
+    }
+}
+
+#[cfg(feature = "audio")]
+struct Output(Option<(rodio::OutputStream, rodio::OutputStreamHandle)>);
+
+#[cfg(feature = "audio")]
+impl Output {
+    fn open() -> Self {
+        Self(rodio::OutputStream::try_default().ok())
+    }
+
+    fn available(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// 음들을 이어서 재생한다. 재생은 오디오 스레드가 하므로 기다리지 않는다.
+    fn play(&self, notes: &[Note]) {
+        use rodio::Source;
+        use rodio::source::SineWave;
+        use std::time::Duration;
+
+        let Some((_, handle)) = &self.0 else {
+            return;
+        };
+        let mut start = 0;
+        for &(freq, ms) in notes {
+            let tone = SineWave::new(freq)
+                .take_duration(Duration::from_millis(ms))
+                .amplify(VOLUME)
+                .delay(Duration::from_millis(start));
+            // 장치가 중간에 사라져도 게임은 계속한다
+            let _ = handle.play_raw(tone);
+            start += ms;
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+struct Output;
+
+#[cfg(not(feature = "audio"))]
+impl Output {
+    fn open() -> Self {
+        Output
+    }
+
+    fn available(&self) -> bool {
+        false
+    }
+
+    fn play(&self, _notes: &[Note]) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tetris_gets_its_own_longer_jingle() {
+        let total = |event| notes(event).iter().map(|&(_, ms)| ms).sum::<u64>();
+        assert!(total(GameEvent::Cleared(4)) > total(GameEvent::Cleared(3)));
+        assert_eq!(notes(GameEvent::Cleared(1)), notes(GameEvent::Cleared(2)));
+    }
+}
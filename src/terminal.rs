@@ -0,0 +1,92 @@
+use crate::error::Result;
+use crossterm::{
+    cursor,
+    event::{
+        DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{self, ClearType},
+};
+use std::io;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// 패닉 훅과 Drop이 둘 다 불려도 터미널은 한 번만 되돌린다
+static RESTORED: AtomicBool = AtomicBool::new(false);
+
+/// raw mode와 대체 화면을 켜 두는 동안 살아 있는 값.
+///
+/// 정상 종료든 패닉이든 사라질 때 터미널을 원래대로 돌려놓는다. 패닉은 메시지를
+/// 찍기 전에 되돌려야 셸에서 읽을 수 있으므로 패닉 훅에서도 되돌린다.
+pub struct TerminalGuard {
+    das_enabled: bool,
+}
+
+impl TerminalGuard {
+    pub fn enter() -> Result<Self> {
+        let mut stdout = io::stdout();
+        // 입력 처리와 화면 제어를 위해 raw mode + 대체 화면 진입
+        terminal::enable_raw_mode()?;
+        // 여기부터 `?`로 돌아가도 guard가 사라지며 켜 둔 만큼 되돌린다
+        RESTORED.store(false, Ordering::SeqCst);
+        let mut guard = Self { das_enabled: false };
+        execute!(
+            stdout,
+            terminal::EnterAlternateScreen,
+            cursor::Hide,
+            terminal::Clear(ClearType::All),
+            // 창이 포커스를 잃으면 알려 주는 터미널에서는 자동으로 멈춘다
+            EnableFocusChange,
+            // 마우스로 피스를 옮기고 돌릴 수 있게 클릭을 받는다
+            EnableMouseCapture
+        )?;
+
+        // 키 떼기 이벤트를 받을 수 있는 터미널에서만 DAS(누르고 있기 자동 이동)를 켠다
+        let das_enabled = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if das_enabled {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+            guard.das_enabled = true;
+        }
+
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore(das_enabled);
+            default_hook(info);
+        }));
+        Ok(guard)
+    }
+
+    /// 키 떼기 이벤트를 받을 수 있는지(DAS를 켤 수 있는지)
+    pub fn das_enabled(&self) -> bool {
+        self.das_enabled
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore(self.das_enabled);
+    }
+}
+
+/// 진입할 때 바꾼 터미널 상태를 거꾸로 되돌린다.
+fn restore(das_enabled: bool) -> io::Result<()> {
+    if RESTORED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let mut stdout = io::stdout();
+    if das_enabled {
+        execute!(stdout, PopKeyboardEnhancementFlags)?;
+    }
+    execute!(
+        stdout,
+        DisableMouseCapture,
+        DisableFocusChange,
+        cursor::Show,
+        terminal::LeaveAlternateScreen
+    )?;
+    terminal::disable_raw_mode()
+}
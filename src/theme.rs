@@ -0,0 +1,364 @@
+use constris::{PieceKind, piece_set};
+use crossterm::style::{Color, StyledContent, Stylize};
+use std::str::FromStr;
+
+/// 칸과 테두리를 그리는 글자 모음. 보드와 미리보기가 같은 것을 쓴다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    // 칸을 채우는 글자들. 두 글자를 번갈아 칸 폭만큼 늘어놓는다.
+    block: [char; 2],
+    ghost: [char; 2],
+    shade: [char; 2],
+    overlap: [char; 2],
+    // 한 줄짜리 작은 미리보기 칸
+    mini: char,
+    /// 목표 진행 막대의 찬 칸과 빈 칸
+    pub bar: [char; 2],
+    /// 격자선을 켰을 때 빈칸에 찍는 점
+    pub grid: char,
+    /// 테두리 가로선과 세로선
+    pub horizontal: char,
+    pub vertical: char,
+    /// 테두리 모서리: 왼쪽 위, 오른쪽 위, 왼쪽 아래, 오른쪽 아래
+    pub corners: [char; 4],
+}
+
+impl Glyphs {
+    /// 블록 문자와 선 문자를 쓰는 기본 글자
+    pub const UNICODE: Glyphs = Glyphs {
+        block: ['\u{2588}'; 2],
+        ghost: ['\u{2591}'; 2],
+        shade: ['\u{2592}'; 2],
+        overlap: ['\u{2573}'; 2],
+        mini: '\u{2588}',
+        bar: ['\u{2588}', '\u{2591}'],
+        grid: '\u{00b7}',
+        horizontal: '\u{2500}',
+        vertical: '\u{2502}',
+        corners: ['\u{250c}', '\u{2510}', '\u{2514}', '\u{2518}'],
+    };
+
+    /// 글꼴이 블록 문자를 못 그리는 터미널용(`--ascii`)
+    pub const ASCII: Glyphs = Glyphs {
+        block: ['[', ']'],
+        ghost: [':', ':'],
+        shade: ['%', '%'],
+        overlap: ['X', 'X'],
+        mini: '#',
+        bar: ['#', '-'],
+        grid: '.',
+        horizontal: '-',
+        vertical: '|',
+        corners: ['+'; 4],
+    };
+
+    pub fn new(ascii: bool) -> Self {
+        if ascii { Self::ASCII } else { Self::UNICODE }
+    }
+}
+
+/// `pair`를 번갈아 `w`글자로 채운다.
+fn fill(pair: [char; 2], w: usize) -> String {
+    pair.iter().cycle().take(w).collect()
+}
+
+/// 고스트 피스를 칠하는 방법(설정 파일의 ghost_style)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GhostStyle {
+    /// 피스와 상관없이 어두운 회색
+    #[default]
+    Grey,
+    /// 지금 피스 색을 어둡게 한 색
+    Tint,
+}
+
+impl FromStr for GhostStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "grey" | "gray" => Ok(GhostStyle::Grey),
+            "tint" | "piece" => Ok(GhostStyle::Tint),
+            _ => Err(format!("unknown ghost_style '{s}' (expected grey or tint)")),
+        }
+    }
+}
+
+/// 블록 색/모양 테마
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    /// 피스마다 다른 색의 꽉 찬 블록
+    #[default]
+    Classic,
+    /// 색 구분이 어려운 플레이어용: 밝기가 서로 다른 회색 + 피스 글자
+    HighContrast,
+}
+
+impl Theme {
+    /// 실행 중 전환할 때 다음 테마
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Classic => Theme::HighContrast,
+            Theme::HighContrast => Theme::Classic,
+        }
+    }
+
+    /// 메뉴와 명령줄에서 쓰는 이름
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "classic",
+            Theme::HighContrast => "contrast",
+        }
+    }
+
+    pub fn piece_color(self, kind: PieceKind) -> Color {
+        match self {
+            // 직접 정의한 세트는 파일에 적힌 색
+            Theme::Classic if !piece_set().is_standard() => {
+                let (r, g, b) = kind.def().color;
+                Color::Rgb { r, g, b }
+            }
+            Theme::Classic => match kind {
+                PieceKind::I => Color::Cyan,
+                PieceKind::O => Color::Yellow,
+                PieceKind::T => Color::Magenta,
+                PieceKind::S => Color::Green,
+                PieceKind::Z => Color::Red,
+                PieceKind::J => Color::Blue,
+                PieceKind::L => Color::DarkYellow,
+                _ => unreachable!("the standard set has seven pieces"),
+            },
+            // 256색 회색조(232~255). S/Z처럼 헷갈리기 쉬운 쌍은 밝기 차이를 크게 둔다.
+            Theme::HighContrast => Color::AnsiValue(gray_level(kind)),
+        }
+    }
+
+    /// 블록 한 칸의 `sub`번째 줄(가로 `w`글자)
+    pub fn block(
+        self,
+        kind: PieceKind,
+        sub: usize,
+        w: usize,
+        glyphs: Glyphs,
+    ) -> StyledContent<String> {
+        match self {
+            Theme::Classic => fill(glyphs.block, w).with(self.piece_color(kind)),
+            Theme::HighContrast => {
+                // 위쪽 줄 가운데에 피스 글자를 찍고, 배경 밝기에 따라 글자색을 고른다
+                let text = if sub == 0 {
+                    format!("{:^w$}", format!("{kind:?}"))
+                } else {
+                    " ".repeat(w)
+                };
+                let fg = if gray_level(kind) >= 246 {
+                    Color::Black
+                } else {
+                    Color::White
+                };
+                text.with(fg).on(self.piece_color(kind))
+            }
+        }
+    }
+
+    /// 미리보기의 작은 블록 한 칸(가로 `w`글자, 한 줄)
+    pub fn mini_block(self, kind: PieceKind, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        match self {
+            Theme::Classic => glyphs
+                .mini
+                .to_string()
+                .repeat(w)
+                .with(self.piece_color(kind)),
+            Theme::HighContrast => format!("{:<w$}", format!("{kind:?}"))
+                .with(Color::Black)
+                .on(self.piece_color(kind)),
+        }
+    }
+
+    /// 아래에서 올라온 쓰레기 줄 한 칸
+    pub fn garbage(self, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        match self {
+            Theme::Classic => fill(glyphs.block, w).with(Color::Grey),
+            Theme::HighContrast => "#".repeat(w).with(Color::White).on(Color::AnsiValue(235)),
+        }
+    }
+
+    /// 고스트 피스 한 칸. `tint`가 있으면 회색 대신 그 피스 색을 어둡게 칠한다.
+    pub fn ghost(self, tint: Option<PieceKind>, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        match (self, tint) {
+            (Theme::Classic, None) => fill(glyphs.ghost, w).with(Color::DarkGrey),
+            (Theme::Classic, Some(kind)) => {
+                fill(glyphs.ghost, w).with(dimmed(self.piece_color(kind)))
+            }
+            (Theme::HighContrast, None) => fill(glyphs.shade, w).with(Color::Grey),
+            // 회색조에서 몇 단계 어둡게 해도 피스끼리의 밝기 순서는 그대로다
+            (Theme::HighContrast, Some(kind)) => fill(glyphs.shade, w).with(Color::AnsiValue(
+                gray_level(kind).saturating_sub(6).max(232),
+            )),
+        }
+    }
+
+    /// 끈끈한 드롭이 확인을 기다리는 착지 자리 한 칸. 고스트보다 밝게 피스 색으로 칠한다.
+    pub fn landing(self, kind: PieceKind, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        let text = fill(glyphs.shade, w);
+        match self {
+            Theme::Classic => text.with(self.piece_color(kind)),
+            Theme::HighContrast => text.with(Color::White).on(Color::Black),
+        }
+    }
+
+    /// 지워지는 중인 줄의 칸
+    pub fn flash(self, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        let text = fill(glyphs.block, w);
+        match self {
+            Theme::Classic => text.with(Color::White),
+            Theme::HighContrast => text.with(Color::White).on(Color::Black),
+        }
+    }
+
+    /// 하드 드롭으로 막 고정한 칸. `step`이 클수록 어두워지다가 제 색으로 돌아간다.
+    pub fn lock_flash(self, step: u8, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        let color = if step == 0 { Color::White } else { Color::Grey };
+        let text = fill(glyphs.block, w).with(color);
+        match self {
+            Theme::Classic => text,
+            Theme::HighContrast => text.on(Color::Black),
+        }
+    }
+
+    /// 하드 드롭한 피스가 지나온 빈칸. `step`이 클수록 흐려진다.
+    pub fn trail(self, step: u8, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        let color = match step {
+            0 => Color::Grey,
+            1 => Color::DarkGrey,
+            _ => Color::AnsiValue(236),
+        };
+        let text = fill(glyphs.ghost, w).with(color);
+        match self {
+            Theme::Classic => text,
+            Theme::HighContrast => text.on(Color::Black),
+        }
+    }
+
+    /// 막혀서 끝났을 때 새 피스와 쌓인 블록이 겹친 칸
+    pub fn overlap(self, w: usize, glyphs: Glyphs) -> StyledContent<String> {
+        let text = fill(glyphs.overlap, w);
+        match self {
+            Theme::Classic => text.with(Color::White).on(Color::Red),
+            Theme::HighContrast => text.with(Color::Black).on(Color::White),
+        }
+    }
+}
+
+/// 피스 색의 어두운 짝. 직접 정의한 색은 밝기를 절반으로 줄인다.
+fn dimmed(color: Color) -> Color {
+    match color {
+        Color::Cyan => Color::DarkCyan,
+        Color::Yellow => Color::DarkYellow,
+        Color::Magenta => Color::DarkMagenta,
+        Color::Green => Color::DarkGreen,
+        Color::Red => Color::DarkRed,
+        Color::Blue => Color::DarkBlue,
+        // L의 주황(DarkYellow)보다 한 단계 어두운 갈색
+        Color::DarkYellow => Color::AnsiValue(94),
+        Color::Rgb { r, g, b } => Color::Rgb {
+            r: r / 2,
+            g: g / 2,
+            b: b / 2,
+        },
+        other => other,
+    }
+}
+
+fn gray_level(kind: PieceKind) -> u8 {
+    // 직접 정의한 세트는 색의 밝기를 회색조 24단계에 맞춘다
+    if !piece_set().is_standard() {
+        let (r, g, b) = kind.def().color;
+        let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+        return 232 + (luma * 23 / 255) as u8;
+    }
+    match kind {
+        PieceKind::I => 252,
+        PieceKind::O => 255,
+        PieceKind::T => 246,
+        PieceKind::S => 249,
+        PieceKind::Z => 238,
+        PieceKind::J => 241,
+        PieceKind::L => 244,
+        _ => unreachable!("the standard set has seven pieces"),
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "classic" | "default" => Ok(Theme::Classic),
+            "contrast" | "high-contrast" => Ok(Theme::HighContrast),
+            _ => Err(format!(
+                "unknown theme '{s}' (expected classic or contrast)"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constris::ALL_PIECES;
+
+    #[test]
+    fn high_contrast_levels_are_all_distinct() {
+        let mut levels: Vec<u8> = ALL_PIECES.iter().map(|&k| gray_level(k)).collect();
+        levels.sort();
+        levels.dedup();
+        assert_eq!(levels.len(), ALL_PIECES.len());
+    }
+
+    #[test]
+    fn tinted_ghosts_are_dimmer_than_their_piece() {
+        let glyphs = Glyphs::UNICODE;
+        for &kind in &ALL_PIECES {
+            let tinted = Theme::Classic.ghost(Some(kind), 4, glyphs);
+            let color = tinted.style().foreground_color.unwrap();
+            assert_ne!(color, Theme::Classic.piece_color(kind));
+            assert_ne!(color, Color::DarkGrey);
+        }
+        assert_eq!(
+            Theme::Classic
+                .ghost(None, 4, glyphs)
+                .style()
+                .foreground_color,
+            Some(Color::DarkGrey)
+        );
+        assert_eq!("Tint".parse(), Ok(GhostStyle::Tint));
+        assert!("blue".parse::<GhostStyle>().is_err());
+    }
+
+    #[test]
+    fn ascii_glyphs_stay_ascii() {
+        let glyphs = Glyphs::ASCII;
+        assert_eq!(
+            Theme::Classic.block(PieceKind::T, 0, 4, glyphs).content(),
+            "[][]"
+        );
+        assert_eq!(Theme::Classic.ghost(None, 2, glyphs).content(), "::");
+        assert_eq!(
+            Theme::Classic.mini_block(PieceKind::I, 1, glyphs).content(),
+            "#"
+        );
+        for theme in [Theme::Classic, Theme::HighContrast] {
+            for text in [
+                theme.block(PieceKind::S, 1, 4, glyphs),
+                theme.garbage(4, glyphs),
+                theme.ghost(None, 4, glyphs),
+                theme.ghost(Some(PieceKind::L), 4, glyphs),
+                theme.flash(4, glyphs),
+                theme.lock_flash(1, 4, glyphs),
+                theme.overlap(4, glyphs),
+            ] {
+                assert!(text.content().is_ascii(), "{:?}", text.content());
+            }
+        }
+    }
+}